@@ -0,0 +1,248 @@
+//! Implements the tasks of async host functions, that settle their promises once their futures
+//! are ready.
+
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use crate::{
+    error::TypeError,
+    extension::new_type_error,
+    runtime::RuntimeData,
+    traits::Serialize,
+    value::{self, NewStringType, Seal, Unseal, ValueScope},
+};
+
+/// A value that is serialized once the future of an async host function is ready.
+trait AsyncValue {
+    fn serialize_boxed<'scope>(
+        self: Box<Self>,
+        scope: &mut ValueScope<'scope>,
+    ) -> Result<value::Value<'scope>, TypeError>;
+}
+
+impl<T> AsyncValue for T
+where
+    T: Serialize,
+{
+    fn serialize_boxed<'scope>(
+        self: Box<Self>,
+        scope: &mut ValueScope<'scope>,
+    ) -> Result<value::Value<'scope>, TypeError> {
+        (*self).serialize(scope)
+    }
+}
+
+/// The result of a task: the value that fulfills the promise, or the messages of the error and
+/// its sources that reject it.
+type TaskResult = Result<Box<dyn AsyncValue>, Vec<String>>;
+
+/// The future of an async host function together with the resolver of its promise.
+struct Task {
+    resolver: v8::Global<v8::PromiseResolver>,
+    future: Pin<Box<dyn Future<Output = TaskResult>>>,
+}
+
+/// Wakes up the event loop of a runtime once a future can make progress.
+#[derive(Default)]
+struct LoopWaker {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl LoopWaker {
+    /// The flag is only a plain bool, so a poisoned lock can still be used.
+    fn woken(&self) -> MutexGuard<'_, bool> {
+        self.woken
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Wake for LoopWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        *self.woken() = true;
+        self.condvar.notify_all();
+    }
+}
+
+/// The pending tasks of the async host functions of a runtime.
+#[derive(Default)]
+pub(crate) struct AsyncTasks {
+    tasks: Vec<Task>,
+    waker: Arc<LoopWaker>,
+}
+
+impl AsyncTasks {
+    /// Returns `true` if there are pending tasks.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Drops all pending tasks. Needs to be called while the isolate is still alive.
+    pub(crate) fn clear(&mut self) {
+        self.tasks.clear();
+    }
+
+    /// Blocks the current thread until a future of a pending task was woken up.
+    pub(crate) fn wait(&self) {
+        let waker = self.waker.clone();
+        let mut woken = waker.woken();
+        while !*woken && !self.tasks.is_empty() {
+            woken = waker
+                .condvar
+                .wait(woken)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+/// Creates a promise that is settled once the future is ready. The returned promise is set as
+/// the return value of the host function.
+#[inline(never)]
+pub(crate) fn spawn_task<'scope, FU, R, E>(
+    scope: &mut v8::HandleScope<'scope>,
+    mut rv: v8::ReturnValue,
+    future: FU,
+) where
+    FU: 'static + Future<Output = Result<R, E>>,
+    R: 'static + Serialize,
+    E: 'static + Into<Box<dyn Error>>,
+{
+    let Some(resolver) = v8::PromiseResolver::new(scope) else {
+        return;
+    };
+    rv.set(resolver.get_promise(scope).into());
+
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    let future = async move {
+        match future.await {
+            Ok(value) => Ok(Box::new(value) as Box<dyn AsyncValue>),
+            Err(err) => Err(error_messages(err.into())),
+        }
+    };
+
+    let mut async_tasks = runtime_data.async_tasks.borrow_mut();
+    async_tasks.tasks.push(Task {
+        resolver: v8::Global::new(scope, resolver),
+        future: Box::pin(future),
+    });
+
+    // The future of a new task needs to be polled once, so that it can register its waker.
+    async_tasks.waker.wake_by_ref();
+}
+
+/// Collects the message of the error and the messages of its sources.
+fn error_messages(err: Box<dyn Error>) -> Vec<String> {
+    let mut messages = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(err) = source {
+        messages.push(err.to_string());
+        source = err.source();
+    }
+    messages
+}
+
+/// Creates an `Error` for each message, where each error is the `cause` of the error before it.
+/// Returns the outermost error.
+fn new_error_chain<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    messages: Vec<String>,
+) -> v8::Local<'scope, v8::Value> {
+    let mut cause: Option<v8::Local<v8::Value>> = None;
+    for msg in messages.into_iter().rev() {
+        let msg = value::new_string(scope, msg, NewStringType::Normal);
+        let error = v8::Exception::error(scope, msg);
+
+        if let (Some(cause), Ok(object)) = (cause, v8::Local::<v8::Object>::try_from(error)) {
+            // Like the `cause` option of the `Error` constructor, which defines a
+            // non-enumerable property.
+            let key = value::new_string(scope, "cause", NewStringType::Internalized);
+            object.define_own_property(scope, key.into(), cause, v8::DONT_ENUM);
+        }
+        cause = Some(error);
+    }
+
+    // An error always has a message, so the chain is never empty.
+    cause.unwrap_or_else(|| v8::undefined(scope).into())
+}
+
+/// Polls the futures of all pending tasks once and settles the promises of the ready tasks.
+/// Returns `true` if a task was settled.
+pub(crate) fn poll_tasks(scope: &mut v8::HandleScope, runtime_data: &RuntimeData) -> bool {
+    // Futures can spawn new tasks while they are polled, so the tasks must not stay borrowed.
+    let (mut tasks, waker) = {
+        let mut async_tasks = runtime_data.async_tasks.borrow_mut();
+        *async_tasks.waker.woken() = false;
+        (
+            std::mem::take(&mut async_tasks.tasks),
+            Waker::from(async_tasks.waker.clone()),
+        )
+    };
+
+    let mut context = Context::from_waker(&waker);
+    let mut settled = false;
+
+    tasks.retain_mut(|task| {
+        let Poll::Ready(result) = task.future.as_mut().poll(&mut context) else {
+            return true;
+        };
+
+        let resolver = v8::Local::new(scope, &task.resolver);
+        match result {
+            Ok(value) => match value.serialize_boxed(scope.seal()) {
+                Ok(value) => {
+                    resolver.resolve(scope, value.unseal());
+                }
+                Err(err) => {
+                    let error = new_type_error(scope.seal(), err);
+                    resolver.reject(scope, error.unseal());
+                }
+            },
+            Err(messages) => {
+                let error = new_error_chain(scope, messages);
+                resolver.reject(scope, error);
+            }
+        }
+
+        settled = true;
+        false
+    });
+
+    let mut async_tasks = runtime_data.async_tasks.borrow_mut();
+    tasks.append(&mut async_tasks.tasks);
+    async_tasks.tasks = tasks;
+
+    settled
+}
+
+/// Drives the futures of the pending tasks and runs the microtasks, until the promise is settled
+/// or no task is left. Blocks the current thread while no future can make progress.
+pub(crate) fn drive_until_settled(scope: &mut v8::HandleScope, promise: v8::Local<v8::Promise>) {
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    while matches!(promise.state(), v8::PromiseState::Pending)
+        && !runtime_data.async_tasks.borrow().is_empty()
+    {
+        if !poll_tasks(scope, runtime_data) {
+            runtime_data.async_tasks.borrow().wait();
+            continue;
+        }
+
+        scope.perform_microtask_checkpoint();
+        runtime_data.count(|c| c.microtask_checkpoints += 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! Implements the interception of modifications of extension namespaces.
+
+use std::ffi::c_void;
+
+use crate::{
+    error::{create_error_from_exception, Error},
+    value::{new_string, NewStringType},
+};
+
+/// The operation, that a script attempted on a property of a namespace.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BindingOperation {
+    /// A property was assigned (e.g. `game.spawn = f`).
+    Set,
+    /// A property was deleted (e.g. `delete game.spawn`).
+    Delete,
+    /// A property was defined (e.g. `Object.defineProperty(game, 'spawn', ...)`).
+    Define,
+}
+
+impl BindingOperation {
+    const ALL: [BindingOperation; 3] = [
+        BindingOperation::Set,
+        BindingOperation::Delete,
+        BindingOperation::Define,
+    ];
+}
+
+/// The interceptor of a namespace. Is kept alive by the runtime, since the trap functions
+/// reference it.
+pub(crate) struct BindingInterceptor {
+    pub(crate) namespace: String,
+    pub(crate) callback: Box<dyn Fn(&str, BindingOperation) -> bool>,
+}
+
+/// Creates the traps of the proxy handler. `Reflect` and `String` are captured, so that scripts
+/// can't bypass the interceptor by replacing them.
+const HANDLER_FACTORY: &str = r#"(notify) => {
+    const { set, deleteProperty, defineProperty } = Reflect;
+    const toString = String;
+    return {
+        set: (target, key, value) => notify(toString(key), 0) && set(target, key, value),
+        deleteProperty: (target, key) => notify(toString(key), 1) && deleteProperty(target, key),
+        defineProperty: (target, key, descriptor) =>
+            notify(toString(key), 2) && defineProperty(target, key, descriptor),
+    };
+}"#;
+
+/// Replaces the namespace object inside its parent with a proxy, that notifies the interceptor
+/// before a script modifies the namespace. The binding of the namespace inside its parent is
+/// made read-only, so that the namespace itself can't be replaced.
+pub(crate) fn install_interceptor<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    parent: v8::Local<'scope, v8::Object>,
+    name: &str,
+    namespace: v8::Local<'scope, v8::Object>,
+    interceptor: *const BindingInterceptor,
+) -> Result<(), Error> {
+    let try_catch_scope = &mut v8::TryCatch::new(scope);
+    let source = new_string(try_catch_scope, HANDLER_FACTORY, NewStringType::Normal);
+
+    let Some(factory) = v8::Script::compile(try_catch_scope, source, None)
+        .and_then(|script| script.run(try_catch_scope))
+        .and_then(|value| v8::Local::<v8::Function>::try_from(value).ok())
+    else {
+        let exception = try_catch_scope.exception();
+        return Err(create_error_from_exception(try_catch_scope, exception));
+    };
+
+    let external = v8::External::new(try_catch_scope, interceptor as *mut c_void);
+    let notify = v8::Function::builder(notify)
+        .data(external.into())
+        .build(try_catch_scope)
+        .ok_or_else(|| Error::Internal("Can't build function".to_string()))?;
+
+    let receiver = v8::undefined(try_catch_scope).into();
+    let Some(handler) = factory
+        .call(try_catch_scope, receiver, &[notify.into()])
+        .and_then(|value| v8::Local::<v8::Object>::try_from(value).ok())
+    else {
+        let exception = try_catch_scope.exception();
+        return Err(create_error_from_exception(try_catch_scope, exception));
+    };
+
+    let proxy = v8::Proxy::new(try_catch_scope, namespace, handler)
+        .ok_or_else(|| Error::Internal("Can't create proxy".to_string()))?;
+
+    let key = new_string(try_catch_scope, name, NewStringType::Internalized);
+    let attributes = v8::READ_ONLY + v8::DONT_DELETE;
+    if parent
+        .define_own_property(try_catch_scope, key.into(), proxy.into(), attributes)
+        .is_none()
+    {
+        let exception = try_catch_scope.exception();
+        return Err(create_error_from_exception(try_catch_scope, exception));
+    }
+
+    Ok(())
+}
+
+/// The callback, that the traps call before a modification. Returns `true` if the interceptor
+/// allows the modification.
+fn notify(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+) {
+    let Ok(external) = v8::Local::<v8::External>::try_from(args.data()) else {
+        rv.set_bool(false);
+        return;
+    };
+
+    // SAFETY: The external was created from the interceptor, that is kept alive by the runtime.
+    let interceptor = unsafe { &*(external.value() as *const BindingInterceptor) };
+
+    let key = args.get(0).to_rust_string_lossy(scope);
+    let operation = args
+        .get(1)
+        .int32_value(scope)
+        .and_then(|operation| BindingOperation::ALL.get(operation as usize).copied())
+        .unwrap_or(BindingOperation::Set);
+
+    let path = format!("{}.{}", interceptor.namespace, key);
+    rv.set_bool((interceptor.callback)(&path, operation));
+}
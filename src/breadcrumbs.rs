@@ -0,0 +1,67 @@
+//! Implements the breadcrumbs of the recent host function calls, that are attached to script
+//! errors.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::c_void,
+};
+
+/// A recorded host function call. Closures are identified by the pointer of their callback data
+/// and are only named, once the breadcrumbs are attached to an error.
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum HostCall {
+    Closure(*const c_void),
+    Static(&'static str),
+}
+
+/// A ring buffer of the most recent host function calls.
+pub(crate) struct Breadcrumbs {
+    calls: VecDeque<HostCall>,
+    capacity: usize,
+    /// The registration names of the closures, keyed by the pointer of their callback data.
+    names: HashMap<*const c_void, String>,
+}
+
+impl Breadcrumbs {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            calls: VecDeque::with_capacity(capacity),
+            capacity,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Registers the name of a closure, that is used when the breadcrumbs are named.
+    pub(crate) fn register(&mut self, cb_data: *const c_void, name: &str) {
+        if self.capacity > 0 {
+            self.names.insert(cb_data, name.to_string());
+        }
+    }
+
+    /// Records a call. Drops the oldest call, if the buffer is full.
+    #[inline(always)]
+    pub(crate) fn record(&mut self, call: HostCall) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.calls.len() == self.capacity {
+            self.calls.pop_front();
+        }
+        self.calls.push_back(call);
+    }
+
+    /// Returns the names of the recorded calls, oldest first.
+    pub(crate) fn names(&self) -> Vec<String> {
+        self.calls
+            .iter()
+            .map(|call| match call {
+                HostCall::Closure(cb_data) => self
+                    .names
+                    .get(cb_data)
+                    .cloned()
+                    .unwrap_or_else(|| "<anonymous>".to_string()),
+                HostCall::Static(name) => name.to_string(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,295 @@
+//! Implements the script bundle format.
+//!
+//! A bundle is a single binary file (`.kopibundle`) with the following layout. All integers
+//! are little endian, all strings are UTF-8 and prefixed with their length as `u32`:
+//!
+//! | Field      | Type              | Description                                      |
+//! |------------|-------------------|--------------------------------------------------|
+//! | Magic      | `[u8; 8]`         | `KOPIBNDL`                                       |
+//! | Version    | `u16`             | Version of the format (currently `1`).           |
+//! | Entry      | string            | Name of the module, that is executed on load.    |
+//! | Count      | `u32`             | Number of sections.                              |
+//! | Sections   | `[Section]`       | Tag (`u8`), name (string) and data (bytes).      |
+//! | Checksum   | `u64`             | 64 bit FNV-1a hash of all preceding bytes.       |
+//!
+//! The tag of a section is `1` for modules, `2` for the code cache of a module and `3` for
+//! snapshot data.
+
+use std::path::Path;
+
+use crate::error::Error;
+
+/// Identifies a bundle.
+const MAGIC: &[u8; 8] = b"KOPIBNDL";
+
+/// The current version of the bundle format.
+const VERSION: u16 = 1;
+
+const TAG_MODULE: u8 = 1;
+const TAG_CODE_CACHE: u8 = 2;
+const TAG_SNAPSHOT: u8 = 3;
+
+/// A validated script bundle, that contains ES modules and optional data to speed up the
+/// startup. Load it with [`crate::Runtime::load_bundle()`], or directly from a file or bytes with
+/// [`crate::Runtime::load_bundle_file()`] and [`crate::Runtime::load_bundle_bytes()`].
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::Bundle;
+///
+/// let mut bundle = Bundle::new("main.js");
+/// bundle.add_module("main.js", "import { value } from 'lib.js'; export default value;");
+/// bundle.add_module("lib.js", "export const value = 42;");
+///
+/// let bytes = bundle.to_bytes();
+/// let bundle = Bundle::from_bytes(&bytes).expect("Invalid bundle");
+/// assert_eq!(bundle.entry(), "main.js");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bundle {
+    entry: String,
+    modules: Vec<(String, String)>,
+    code_caches: Vec<(String, Vec<u8>)>,
+    snapshot: Option<Vec<u8>>,
+}
+
+impl Bundle {
+    /// Creates an empty bundle with the given entry module.
+    pub fn new(entry: &str) -> Self {
+        Self {
+            entry: entry.to_string(),
+            modules: Vec::new(),
+            code_caches: Vec::new(),
+            snapshot: None,
+        }
+    }
+
+    /// Returns the name of the entry module.
+    pub fn entry(&self) -> &str {
+        &self.entry
+    }
+
+    /// Returns the modules of the bundle as pairs of name and source.
+    pub fn modules(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.modules
+            .iter()
+            .map(|(name, source)| (name.as_str(), source.as_str()))
+    }
+
+    /// Adds a module to the bundle.
+    pub fn add_module(&mut self, name: &str, source: &str) -> &mut Self {
+        self.modules.push((name.to_string(), source.to_string()));
+        self
+    }
+
+    /// Adds the code cache of a module to the bundle.
+    pub fn add_code_cache(&mut self, name: &str, data: Vec<u8>) -> &mut Self {
+        self.code_caches.push((name.to_string(), data));
+        self
+    }
+
+    /// Sets the snapshot data of the bundle.
+    ///
+    /// The snapshot can only be used while a runtime is created, so
+    /// [`crate::Runtime::load_bundle()`] rejects bundles with snapshot data.
+    pub fn set_snapshot(&mut self, data: Vec<u8>) -> &mut Self {
+        self.snapshot = Some(data);
+        self
+    }
+
+    /// Returns the code cache of the module with the given name.
+    pub fn code_cache(&self, name: &str) -> Option<&[u8]> {
+        self.code_caches
+            .iter()
+            .find(|(cache_name, _)| cache_name == name)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    /// Returns the snapshot data of the bundle.
+    pub fn snapshot(&self) -> Option<&[u8]> {
+        self.snapshot.as_deref()
+    }
+
+    /// Reads and validates the bundle at the given path.
+    pub fn read<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let data = std::fs::read(path).map_err(Error::Io)?;
+        Self::from_bytes(&data)
+    }
+
+    /// Validates the given data and creates a bundle from it.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        let Some(content_length) = data.len().checked_sub(8) else {
+            return Err(invalid("The bundle is truncated"));
+        };
+        let (content, checksum) = data.split_at(content_length);
+        let checksum = u64::from_le_bytes(checksum.try_into().expect("Checksum has 8 bytes"));
+        if checksum != fnv1a(content) {
+            return Err(invalid("The checksum of the bundle doesn't match"));
+        }
+
+        let mut reader = Reader { data: content };
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err(invalid("The data is not a bundle"));
+        }
+
+        let version = u16::from_le_bytes(reader.array()?);
+        if version != VERSION {
+            return Err(invalid(&format!(
+                "The bundle version {} is not supported",
+                version
+            )));
+        }
+
+        let mut bundle = Bundle::new(&reader.string()?);
+
+        let count = u32::from_le_bytes(reader.array()?);
+        for _ in 0..count {
+            let [tag] = reader.array()?;
+            let name = reader.string()?;
+            let data = reader.data()?;
+
+            match tag {
+                TAG_MODULE => {
+                    let source = String::from_utf8(data.to_vec())
+                        .map_err(|_| invalid("The source of a module is not valid UTF-8"))?;
+                    bundle.add_module(&name, &source);
+                }
+                TAG_CODE_CACHE => {
+                    bundle.add_code_cache(&name, data.to_vec());
+                }
+                TAG_SNAPSHOT => {
+                    bundle.set_snapshot(data.to_vec());
+                }
+                tag => return Err(invalid(&format!("Unknown section tag {}", tag))),
+            }
+        }
+
+        if !reader.data.is_empty() {
+            return Err(invalid("The bundle contains trailing data"));
+        }
+
+        if !bundle.modules.iter().any(|(name, _)| *name == bundle.entry) {
+            return Err(invalid("The entry module is missing"));
+        }
+
+        Ok(bundle)
+    }
+
+    /// Serializes the bundle into the bundle format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&VERSION.to_le_bytes());
+        write_data(&mut data, self.entry.as_bytes());
+
+        let count = self.modules.len() + self.code_caches.len() + self.snapshot.iter().count();
+        data.extend_from_slice(&(count as u32).to_le_bytes());
+
+        for (name, source) in self.modules.iter() {
+            write_section(&mut data, TAG_MODULE, name, source.as_bytes());
+        }
+        for (name, cache) in self.code_caches.iter() {
+            write_section(&mut data, TAG_CODE_CACHE, name, cache);
+        }
+        if let Some(snapshot) = self.snapshot.as_ref() {
+            write_section(&mut data, TAG_SNAPSHOT, "", snapshot);
+        }
+
+        let checksum = fnv1a(&data);
+        data.extend_from_slice(&checksum.to_le_bytes());
+        data
+    }
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::Bundle(msg.to_string())
+}
+
+fn write_data(data: &mut Vec<u8>, bytes: &[u8]) {
+    data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data.extend_from_slice(bytes);
+}
+
+fn write_section(data: &mut Vec<u8>, tag: u8, name: &str, bytes: &[u8]) {
+    data.push(tag);
+    write_data(data, name.as_bytes());
+    write_data(data, bytes);
+}
+
+/// Returns the 64 bit FNV-1a hash of the data.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Reads the fields of a bundle.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < length {
+            return Err(invalid("The bundle is truncated"));
+        }
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes = self.bytes(N)?;
+        Ok(bytes.try_into().expect("Slice has the length of the array"))
+    }
+
+    fn data(&mut self) -> Result<&'a [u8], Error> {
+        let length = u32::from_le_bytes(self.array()?);
+        self.bytes(length as usize)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let data = self.data()?;
+        String::from_utf8(data.to_vec()).map_err(|_| invalid("A name is not valid UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bundle;
+    use crate::error::Error;
+
+    #[test]
+    fn round_trip() {
+        let mut bundle = Bundle::new("main.js");
+        bundle
+            .add_module("main.js", "export default 1;")
+            .add_code_cache("main.js", vec![1, 2, 3]);
+
+        let bytes = bundle.to_bytes();
+        let loaded = Bundle::from_bytes(&bytes).expect("Invalid bundle");
+        assert_eq!(loaded, bundle);
+        assert_eq!(loaded.code_cache("main.js"), Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn integrity() {
+        let mut bundle = Bundle::new("main.js");
+        bundle.add_module("main.js", "export default 1;");
+        let mut bytes = bundle.to_bytes();
+
+        bytes[20] ^= 0xFF;
+        assert!(matches!(Bundle::from_bytes(&bytes), Err(Error::Bundle(_))));
+        assert!(matches!(
+            Bundle::from_bytes(&bytes[..4]),
+            Err(Error::Bundle(_))
+        ));
+
+        let bytes = Bundle::new("missing.js").to_bytes();
+        assert!(matches!(Bundle::from_bytes(&bytes), Err(Error::Bundle(_))));
+    }
+}
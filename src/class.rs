@@ -0,0 +1,582 @@
+//! Implements native classes, that expose a Rust type as an ECMAScript class.
+
+use std::{
+    any::{Any, TypeId},
+    cell::{RefCell, RefMut},
+    collections::HashMap,
+    ffi::c_void,
+    sync::Arc,
+};
+
+use crate::{
+    extension::{new_type_error, set_result, FunctionWithStateArguments, HostCallGuard},
+    runtime::RuntimeData,
+    traits::{DeserializeOwned, Serialize},
+    value::{new_string, NewStringType, Seal, Unseal},
+};
+
+/// Marks the objects, that were created by the constructor of a native class. Only the address
+/// of the static is used.
+static CLASS_TAG: u64 = 0;
+
+/// The internal field, that contains the address of [`CLASS_TAG`].
+const TAG_FIELD: usize = 0;
+/// The internal field, that contains the pointer to the [`Instance`].
+const INSTANCE_FIELD: usize = 1;
+/// The number of internal fields of the instances.
+const INTERNAL_FIELD_COUNT: usize = 2;
+
+/// Creates the function template of a native class. The pointer points to the `Class<T>`, that
+/// created the declaration.
+pub(crate) type ClassTemplate = for<'scope> fn(
+    &mut v8::HandleScope<'scope, ()>,
+    *mut c_void,
+) -> v8::Local<'scope, v8::FunctionTemplate>;
+
+/// The native data of the living instances of native classes, keyed by the address of the
+/// data. The weak handles remove the data, once the instances were garbage collected.
+#[derive(Default)]
+pub(crate) struct ClassInstances {
+    instances: RefCell<HashMap<usize, (v8::Weak<v8::Object>, Box<dyn Any>)>>,
+    /// The instances, that were garbage collected while the instances were borrowed. Their
+    /// data is removed with the next borrow.
+    finalized: RefCell<Vec<usize>>,
+}
+
+impl ClassInstances {
+    /// Borrows the instances after removing the data of the finalized instances.
+    pub(crate) fn borrow_mut(
+        &self,
+    ) -> RefMut<'_, HashMap<usize, (v8::Weak<v8::Object>, Box<dyn Any>)>> {
+        let mut instances = self.instances.borrow_mut();
+        for key in self.finalized.borrow_mut().drain(..) {
+            instances.remove(&key);
+        }
+        instances
+    }
+
+    /// Removes the data of a garbage collected instance. The removal is deferred to the next
+    /// borrow, if the instances are currently borrowed.
+    fn finalize(&self, key: usize) {
+        match self.instances.try_borrow_mut() {
+            Ok(mut instances) => {
+                instances.remove(&key);
+            }
+            Err(_) => self.finalized.borrow_mut().push(key),
+        }
+    }
+}
+
+/// A Rust type, that is exposed to scripts as a class. Scripts create instances with
+/// `new Name(...)`, which calls the constructor of the class. The value returned by the
+/// constructor is owned by the instance and is dropped after the instance was garbage collected.
+///
+/// Add the class to an extension with [`crate::Extension::add_class()`].
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::{Class, Extension};
+///
+/// struct Counter {
+///     count: i32,
+/// }
+///
+/// let class = Class::new("Counter")
+///     .constructor(|(start,): (i32,)| Counter { count: start })
+///     .method("increment", |counter, ()| {
+///         counter.count += 1;
+///         counter.count
+///     })
+///     .property(
+///         "count",
+///         |counter| counter.count,
+///         |counter, count: i32| counter.count = count,
+///     );
+///
+/// let mut extension = Extension::<()>::new(None);
+/// extension.add_class(class);
+/// ```
+pub struct Class<T> {
+    pub(crate) name: String,
+    constructor: Option<(Arc<dyn Any + Send + Sync>, v8::FunctionCallback)>,
+    methods: Vec<Method>,
+    accessors: HashMap<String, Accessor<T>>,
+}
+
+/// A method of the prototype of a class.
+struct Method {
+    name: String,
+    closure: Arc<dyn Any + Send + Sync>,
+    function_callback: v8::FunctionCallback,
+}
+
+/// A property of the instances of a class. The thunks know the concrete types of the closures.
+struct Accessor<T> {
+    getter: Arc<dyn Any + Send + Sync>,
+    get: fn(&mut v8::HandleScope, *const c_void, &T, v8::ReturnValue),
+    setter: Option<(
+        Arc<dyn Any + Send + Sync>,
+        for<'scope> fn(
+            &mut v8::HandleScope<'scope>,
+            *const c_void,
+            &mut T,
+            v8::Local<'scope, v8::Value>,
+        ),
+    )>,
+}
+
+/// The native data of an instance. The type ID is the first field, so that the type of the
+/// value can be verified before the rest of the instance is accessed.
+#[repr(C)]
+struct Instance<T> {
+    type_id: TypeId,
+    class: *const Class<T>,
+    value: RefCell<T>,
+}
+
+impl<T> Class<T>
+where
+    T: 'static,
+{
+    /// Creates a new class with the given name. The name is also the name of the constructor
+    /// inside the extension.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            constructor: None,
+            methods: Vec::new(),
+            accessors: HashMap::new(),
+        }
+    }
+
+    /// Sets the constructor, that creates the value of a new instance from the arguments of
+    /// `new`. Arguments, that can't be converted, throw a `TypeError`. Classes without a
+    /// constructor can't be instantiated by scripts.
+    pub fn constructor<F, A>(mut self, constructor: F) -> Self
+    where
+        F: 'static + Send + Sync + Fn(A) -> T,
+        A: 'static
+            + for<'scope> FunctionWithStateArguments<
+                'scope,
+                ConstructFn<F, A, T>,
+                (),
+                ConstructorSlot<F, T>,
+            >,
+    {
+        use v8::MapFnTo;
+
+        let function_callback = constructor_callback::<T, F, A>.map_fn_to();
+
+        self.constructor = Some((Arc::new(constructor), function_callback));
+        self
+    }
+
+    /// Adds a method to the prototype of the class. The method can mutate the value of the
+    /// instance it's called on.
+    pub fn method<F, A, R>(mut self, name: &str, method: F) -> Self
+    where
+        F: 'static + Send + Sync + Fn(&mut T, A) -> R,
+        A: for<'scope> FunctionWithStateArguments<'scope, F, R, T>,
+        R: Serialize,
+    {
+        use v8::MapFnTo;
+
+        self.methods.push(Method {
+            name: name.to_string(),
+            closure: Arc::new(method),
+            function_callback: method_callback::<T, F, A, R>.map_fn_to(),
+        });
+        self
+    }
+
+    /// Adds a read-only property to the instances of the class. Assigning the property throws a
+    /// `TypeError`.
+    pub fn getter<G, R>(mut self, name: &str, getter: G) -> Self
+    where
+        G: 'static + Send + Sync + Fn(&T) -> R,
+        R: 'static + Serialize,
+    {
+        self.accessors.insert(
+            name.to_string(),
+            Accessor {
+                getter: Arc::new(getter),
+                get: get_thunk::<T, G, R>,
+                setter: None,
+            },
+        );
+        self
+    }
+
+    /// Adds a property to the instances of the class. Assigning a value, that can't be converted,
+    /// throws a `TypeError`.
+    pub fn property<G, R, S, V>(mut self, name: &str, getter: G, setter: S) -> Self
+    where
+        G: 'static + Send + Sync + Fn(&T) -> R,
+        R: 'static + Serialize,
+        S: 'static + Send + Sync + Fn(&mut T, V),
+        V: DeserializeOwned,
+    {
+        self.accessors.insert(
+            name.to_string(),
+            Accessor {
+                getter: Arc::new(getter),
+                get: get_thunk::<T, G, R>,
+                setter: Some((Arc::new(setter), set_thunk::<T, S, V>)),
+            },
+        );
+        self
+    }
+}
+
+/// The signature, that adapts the constructor of a class to the arguments of functions with a
+/// state.
+#[doc(hidden)]
+pub type ConstructFn<F, A, T> = fn(&mut ConstructorSlot<F, T>, A);
+
+/// The state of a constructor call. Receives the value, that the constructor created.
+#[doc(hidden)]
+pub struct ConstructorSlot<F, T> {
+    constructor: *const F,
+    value: Option<T>,
+}
+
+fn construct<F, A, T>(slot: &mut ConstructorSlot<F, T>, args: A)
+where
+    F: Fn(A) -> T,
+{
+    // SAFETY: The slot only exists during the call of the constructor callback, which keeps
+    //         the class and its constructor alive.
+    let constructor = unsafe { &*slot.constructor };
+    slot.value = Some(constructor(args));
+}
+
+/// Creates the function template of the class, that is stored behind the pointer.
+pub(crate) fn class_template<'scope, T>(
+    scope: &mut v8::HandleScope<'scope, ()>,
+    cb_data: *mut c_void,
+) -> v8::Local<'scope, v8::FunctionTemplate>
+where
+    T: 'static,
+{
+    use v8::MapFnTo;
+
+    // SAFETY: The runtime keeps the class alive as long as the isolate exists and the
+    //         declaration contains the pointer of a class of this type.
+    let class = unsafe { &*(cb_data as *const Class<T>) };
+
+    let function_callback = match &class.constructor {
+        Some((_, function_callback)) => *function_callback,
+        None => illegal_constructor.map_fn_to(),
+    };
+
+    let external = v8::External::new(scope, cb_data);
+    let template = v8::FunctionTemplate::builder_raw(function_callback)
+        .data(external.into())
+        .build(scope);
+
+    let class_name = new_string(scope, &class.name, NewStringType::Internalized);
+    template.set_class_name(class_name);
+
+    let instance_template = template.instance_template(scope);
+    instance_template.set_internal_field_count(INTERNAL_FIELD_COUNT);
+    for name in class.accessors.keys() {
+        let key = new_string(scope, name, NewStringType::Internalized);
+        instance_template.set_accessor_with_setter(key.into(), getter::<T>, setter::<T>);
+    }
+
+    let prototype_template = template.prototype_template(scope);
+    for method in class.methods.iter() {
+        let external = v8::External::new(
+            scope,
+            Arc::as_ptr(&method.closure) as *const c_void as *mut c_void,
+        );
+        let function = v8::FunctionTemplate::builder_raw(method.function_callback)
+            .data(external.into())
+            .build(scope);
+        let key = new_string(scope, &method.name, NewStringType::Internalized);
+        prototype_template.set(key.into(), function.into());
+    }
+
+    template
+}
+
+fn constructor_callback<'borrow, 'scope, T, F, A>(
+    scope: &'borrow mut v8::HandleScope<'scope>,
+    args: v8::FunctionCallbackArguments<'scope>,
+    rv: v8::ReturnValue,
+) where
+    T: 'static,
+    F: 'static + Send + Sync + Fn(A) -> T,
+    A: 'static
+        + FunctionWithStateArguments<'scope, ConstructFn<F, A, T>, (), ConstructorSlot<F, T>>,
+{
+    let Some(_guard) = HostCallGuard::enter(scope) else {
+        return;
+    };
+
+    if args.new_target().is_undefined() {
+        throw_type_error(scope, "Class constructor can't be called without 'new'");
+        return;
+    }
+
+    let this = args.this();
+    if this.internal_field_count() != INTERNAL_FIELD_COUNT {
+        throw_type_error(scope, "Illegal constructor");
+        return;
+    }
+
+    // SAFETY: This is safe since the runtime keeps the class alive as long as the isolate
+    //         exists and the template of the class stores the pointer of the class as data.
+    let class =
+        unsafe { &*(v8::Local::<v8::External>::cast(args.data()).value() as *const Class<T>) };
+    let Some((closure, _)) = &class.constructor else {
+        return;
+    };
+
+    // The constructor was stored by `Class::constructor()` together with this function
+    // callback, so it has the expected type.
+    let mut slot = ConstructorSlot {
+        constructor: Arc::as_ptr(closure) as *const F,
+        value: None,
+    };
+
+    // If the arguments can't be converted, the `TypeError` is thrown and no instance is created.
+    let construct_fn: ConstructFn<F, A, T> = construct::<F, A, T>;
+    A::call(scope, args, rv, &construct_fn, &mut slot);
+    let Some(value) = slot.value else {
+        return;
+    };
+
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    let instance = Box::new(Instance {
+        type_id: TypeId::of::<T>(),
+        class: class as *const Class<T>,
+        value: RefCell::new(value),
+    });
+    let instance_ptr = &*instance as *const Instance<T>;
+
+    // SAFETY: The instance template of the class reserves the internal fields.
+    unsafe {
+        this.set_aligned_pointer_in_internal_field(
+            TAG_FIELD,
+            &CLASS_TAG as *const u64 as *const c_void,
+        );
+        this.set_aligned_pointer_in_internal_field(INSTANCE_FIELD, instance_ptr as *const c_void);
+    }
+
+    let key = instance_ptr as usize;
+    let weak = v8::Weak::with_finalizer(
+        scope,
+        this,
+        Box::new(move |isolate: &mut v8::Isolate| {
+            if let Some(runtime_data) = RuntimeData::from_isolate(isolate) {
+                runtime_data.class_instances.finalize(key);
+            }
+        }),
+    );
+
+    runtime_data
+        .class_instances
+        .borrow_mut()
+        .insert(key, (weak, instance as Box<dyn Any>));
+}
+
+fn illegal_constructor(
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    _rv: v8::ReturnValue,
+) {
+    throw_type_error(scope, "Illegal constructor");
+}
+
+fn method_callback<'borrow, 'scope, T, F, A, R>(
+    scope: &'borrow mut v8::HandleScope<'scope>,
+    args: v8::FunctionCallbackArguments<'scope>,
+    rv: v8::ReturnValue,
+) where
+    T: 'static,
+    F: 'static + Send + Sync + Fn(&mut T, A) -> R,
+    A: FunctionWithStateArguments<'scope, F, R, T>,
+    R: Serialize,
+{
+    let Some(_guard) = HostCallGuard::enter(scope) else {
+        return;
+    };
+
+    let Some(instance) = unwrap_instance::<T>(scope, args.this()) else {
+        return;
+    };
+
+    // SAFETY: This is safe since the runtime keeps the closure alive as long as the isolate
+    //         exists and the implementation makes sure, that the data contains the pointer
+    //         of the expected closure callback for this function callback.
+    let cb_data = unsafe {
+        &*(v8::Local::<v8::External>::cast(args.data()).value() as *const c_void as *const F)
+    };
+
+    let Ok(mut value) = instance.value.try_borrow_mut() else {
+        throw_type_error(
+            scope,
+            "Instance of a native class can't be used recursively",
+        );
+        return;
+    };
+
+    A::call(scope, args, rv, cb_data, &mut value);
+}
+
+fn getter<'scope, T>(
+    scope: &mut v8::HandleScope<'scope>,
+    key: v8::Local<'scope, v8::Name>,
+    args: v8::PropertyCallbackArguments<'scope>,
+    rv: v8::ReturnValue,
+) where
+    T: 'static,
+{
+    let Some(_guard) = HostCallGuard::enter(scope) else {
+        return;
+    };
+
+    let Some(instance) = unwrap_instance::<T>(scope, args.this()) else {
+        return;
+    };
+
+    // SAFETY: The class outlives its instances, since the runtime keeps it alive.
+    let class = unsafe { &*instance.class };
+
+    let name = key.to_rust_string_lossy(scope);
+    let Some(accessor) = class.accessors.get(&name) else {
+        return;
+    };
+
+    let Ok(value) = instance.value.try_borrow() else {
+        throw_type_error(
+            scope,
+            "Instance of a native class can't be used recursively",
+        );
+        return;
+    };
+
+    let getter = Arc::as_ptr(&accessor.getter) as *const c_void;
+    (accessor.get)(scope, getter, &value, rv);
+}
+
+fn setter<'scope, T>(
+    scope: &mut v8::HandleScope<'scope>,
+    key: v8::Local<'scope, v8::Name>,
+    value: v8::Local<'scope, v8::Value>,
+    args: v8::PropertyCallbackArguments<'scope>,
+) where
+    T: 'static,
+{
+    let Some(_guard) = HostCallGuard::enter(scope) else {
+        return;
+    };
+
+    let Some(instance) = unwrap_instance::<T>(scope, args.this()) else {
+        return;
+    };
+
+    // SAFETY: The class outlives its instances, since the runtime keeps it alive.
+    let class = unsafe { &*instance.class };
+
+    let name = key.to_rust_string_lossy(scope);
+    let Some((setter, set)) = class
+        .accessors
+        .get(&name)
+        .and_then(|accessor| accessor.setter.as_ref())
+    else {
+        throw_type_error(scope, &format!("Property '{}' is read-only", name));
+        return;
+    };
+
+    let Ok(mut instance_value) = instance.value.try_borrow_mut() else {
+        throw_type_error(
+            scope,
+            "Instance of a native class can't be used recursively",
+        );
+        return;
+    };
+
+    let setter = Arc::as_ptr(setter) as *const c_void;
+    set(scope, setter, &mut instance_value, value);
+}
+
+fn get_thunk<T, G, R>(
+    scope: &mut v8::HandleScope,
+    getter: *const c_void,
+    value: &T,
+    rv: v8::ReturnValue,
+) where
+    G: Fn(&T) -> R,
+    R: 'static + Serialize,
+{
+    // SAFETY: The pointer was created from the getter, that was stored with this thunk.
+    let getter = unsafe { &*(getter as *const G) };
+    set_result(scope, rv, getter(value));
+}
+
+fn set_thunk<'scope, T, S, V>(
+    scope: &mut v8::HandleScope<'scope>,
+    setter: *const c_void,
+    instance_value: &mut T,
+    value: v8::Local<'scope, v8::Value>,
+) where
+    S: Fn(&mut T, V),
+    V: DeserializeOwned,
+{
+    // SAFETY: The pointer was created from the setter, that was stored with this thunk.
+    let setter = unsafe { &*(setter as *const S) };
+
+    let scope = scope.seal();
+    match V::deserialize(scope, value.seal()) {
+        Ok(value) => setter(instance_value, value),
+        Err(err) => {
+            let error = new_type_error(scope, err);
+            scope.unseal().throw_exception(error.unseal());
+        }
+    }
+}
+
+/// Returns the instance of the native class of type `T`, that is wrapped by the object. Throws a
+/// `TypeError` and returns `None` if the object isn't such an instance.
+fn unwrap_instance<'instance, T>(
+    scope: &mut v8::HandleScope,
+    object: v8::Local<v8::Object>,
+) -> Option<&'instance Instance<T>>
+where
+    T: 'static,
+{
+    let mut instance = None;
+
+    if object.internal_field_count() == INTERNAL_FIELD_COUNT {
+        // SAFETY: Reading the internal fields is safe, since the objects of the instance
+        //         templates always have the internal fields. The pointer to the instance is only
+        //         read if the tag shows, that the constructor of a native class created it.
+        unsafe {
+            let tag = object.get_aligned_pointer_from_internal_field(TAG_FIELD);
+            if tag == &CLASS_TAG as *const u64 as *const c_void {
+                let ptr = object.get_aligned_pointer_from_internal_field(INSTANCE_FIELD)
+                    as *const Instance<T>;
+                if !ptr.is_null() && *(ptr as *const TypeId) == TypeId::of::<T>() {
+                    instance = Some(&*ptr);
+                }
+            }
+        }
+    }
+
+    if instance.is_none() {
+        throw_type_error(scope, "Illegal invocation");
+    }
+    instance
+}
+
+fn throw_type_error(scope: &mut v8::HandleScope, msg: &str) {
+    let msg = new_string(scope, msg, NewStringType::Normal);
+    let exception = v8::Exception::type_error(scope, msg);
+    scope.throw_exception(exception);
+}
@@ -0,0 +1,64 @@
+//! Implements the verification of scripts before they are compiled.
+
+use crate::{error::Error, runtime::RuntimeData};
+
+/// Verifies the source of a script before it's compiled, for example by checking its signature.
+pub(crate) type CodeVerifier = Box<dyn Fn(&ScriptSource) -> Result<(), VerifyError>>;
+
+/// The kind of a script, that is verified.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScriptKind {
+    /// A classic script.
+    Script,
+    /// An ES module.
+    Module,
+    /// The expression, that evaluates to the import object of a WebAssembly module (see
+    /// [`crate::Runtime::instantiate_wasm()`]).
+    WasmImports,
+}
+
+/// The source of a script, that is verified before it's compiled.
+#[derive(Copy, Clone, Debug)]
+pub struct ScriptSource<'a> {
+    /// The kind of the script.
+    pub kind: ScriptKind,
+    /// The name of the module. Classic scripts have no name.
+    pub name: Option<&'a str>,
+    /// The source of the script, as it was given to the runtime.
+    pub source: &'a str,
+}
+
+/// The error a code verifier returns for scripts that must not be compiled.
+#[derive(Clone, Debug)]
+pub struct VerifyError {
+    /// Describes why the script was rejected.
+    pub message: String,
+}
+
+impl VerifyError {
+    /// Creates a new verification error with the given message.
+    pub fn new<S>(message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Verifies the script with the code verifier of the runtime, if one is configured.
+pub(crate) fn verify_source(data: &RuntimeData, source: &ScriptSource) -> Result<(), Error> {
+    match data.code_verifier.as_ref() {
+        Some(verifier) => verifier(source).map_err(Error::Verification),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,281 @@
+//! Implements the `console` API, that routes the messages of scripts to a sink of the host.
+
+use std::io::Write;
+
+use crate::{
+    runtime::RuntimeData,
+    value::{new_string, NewStringType},
+};
+
+/// The level of a console message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConsoleLevel {
+    /// Written by `console.log()`.
+    Log,
+    /// Written by `console.debug()`.
+    Debug,
+    /// Written by `console.info()`.
+    Info,
+    /// Written by `console.warn()`.
+    Warn,
+    /// Written by `console.error()`.
+    Error,
+}
+
+impl ConsoleLevel {
+    const ALL: [ConsoleLevel; 5] = [
+        ConsoleLevel::Log,
+        ConsoleLevel::Debug,
+        ConsoleLevel::Info,
+        ConsoleLevel::Warn,
+        ConsoleLevel::Error,
+    ];
+
+    /// Returns the name of the console method.
+    fn method_name(self) -> &'static str {
+        match self {
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Info => "info",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+        }
+    }
+}
+
+/// Receives the formatted messages, that scripts write with the `console` API.
+///
+/// Set with [`crate::RuntimeOptions::console`]. Closures with the signature
+/// `FnMut(ConsoleLevel, &str)` implement the trait.
+pub trait ConsoleSink {
+    /// Writes the formatted message.
+    fn write(&mut self, level: ConsoleLevel, message: &str);
+}
+
+impl<F> ConsoleSink for F
+where
+    F: FnMut(ConsoleLevel, &str),
+{
+    fn write(&mut self, level: ConsoleLevel, message: &str) {
+        self(level, message)
+    }
+}
+
+/// Writes the console messages to the `log` crate with the target `kopi::console`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LogConsoleSink;
+
+impl ConsoleSink for LogConsoleSink {
+    fn write(&mut self, level: ConsoleLevel, message: &str) {
+        let level = match level {
+            ConsoleLevel::Log | ConsoleLevel::Info => log::Level::Info,
+            ConsoleLevel::Debug => log::Level::Debug,
+            ConsoleLevel::Warn => log::Level::Warn,
+            ConsoleLevel::Error => log::Level::Error,
+        };
+        log::log!(target: "kopi::console", level, "{}", message);
+    }
+}
+
+/// Writes the console messages to stdout. Warnings and errors are written to stderr.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StdoutConsoleSink;
+
+impl ConsoleSink for StdoutConsoleSink {
+    fn write(&mut self, level: ConsoleLevel, message: &str) {
+        // Failing to write a console message must not abort the script.
+        let _ = match level {
+            ConsoleLevel::Log | ConsoleLevel::Debug | ConsoleLevel::Info => {
+                writeln!(std::io::stdout(), "{}", message)
+            }
+            ConsoleLevel::Warn | ConsoleLevel::Error => writeln!(std::io::stderr(), "{}", message),
+        };
+    }
+}
+
+/// Replaces the methods of the `console` object of the context with methods, that write to the
+/// sink of the runtime.
+pub(crate) fn install_console(scope: &mut v8::HandleScope, global: v8::Local<v8::Object>) {
+    let name = new_string(scope, "console", NewStringType::Internalized);
+    let console = match global
+        .get(scope, name.into())
+        .and_then(|value| v8::Local::<v8::Object>::try_from(value).ok())
+    {
+        Some(console) => console,
+        None => {
+            let console = v8::Object::new(scope);
+            global.set(scope, name.into(), console.into());
+            console
+        }
+    };
+
+    for level in ConsoleLevel::ALL {
+        let data = v8::Integer::new(scope, level as i32);
+        let Some(function) = v8::Function::builder(console_method)
+            .data(data.into())
+            .build(scope)
+        else {
+            continue;
+        };
+
+        let name = new_string(scope, level.method_name(), NewStringType::Internalized);
+        console.set(scope, name.into(), function.into());
+    }
+}
+
+/// The callback of the console methods. The level is stored in the data of the function.
+fn console_method(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    _rv: v8::ReturnValue,
+) {
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    let level = args
+        .data()
+        .int32_value(scope)
+        .and_then(|level| ConsoleLevel::ALL.get(level as usize).copied())
+        .unwrap_or(ConsoleLevel::Log);
+
+    let values: Vec<v8::Local<v8::Value>> = (0..args.length()).map(|i| args.get(i)).collect();
+    let message = format_message(scope, &values);
+
+    // A sink that calls back into the runtime would write while the sink is borrowed.
+    if let Ok(mut console) = runtime_data.console.try_borrow_mut() {
+        if let Some(console) = console.as_mut() {
+            console.write(level, &message);
+        }
+    }
+}
+
+/// Formats the arguments of a console method. The first argument can contain the format
+/// specifiers `%s`, `%d`, `%i`, `%f`, `%o`, `%O`, `%c` and `%%`. Remaining arguments are
+/// appended separated by spaces.
+fn format_message(scope: &mut v8::HandleScope, values: &[v8::Local<v8::Value>]) -> String {
+    let mut message = String::new();
+    let mut remaining = values.iter();
+
+    if let Some(first) = values.first().filter(|value| value.is_string()) {
+        remaining.next();
+
+        let format = first.to_rust_string_lossy(scope);
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            let specifier = match (c, chars.peek()) {
+                ('%', Some(&specifier)) if "sdifoOc%".contains(specifier) => specifier,
+                _ => {
+                    message.push(c);
+                    continue;
+                }
+            };
+            chars.next();
+
+            if specifier == '%' {
+                message.push('%');
+                continue;
+            }
+
+            let Some(value) = remaining.next() else {
+                message.push('%');
+                message.push(specifier);
+                continue;
+            };
+
+            match specifier {
+                'd' | 'i' => match value.number_value(scope) {
+                    Some(number) if number.is_finite() => {
+                        message.push_str(&number.trunc().to_string())
+                    }
+                    _ => message.push_str("NaN"),
+                },
+                'f' => match value.number_value(scope) {
+                    Some(number) => message.push_str(&number.to_string()),
+                    None => message.push_str("NaN"),
+                },
+                'c' => {}
+                _ => message.push_str(&format_value(scope, *value)),
+            }
+        }
+    }
+
+    for value in remaining {
+        if !message.is_empty() {
+            message.push(' ');
+        }
+        message.push_str(&format_value(scope, *value));
+    }
+
+    message
+}
+
+/// Formats a single value. Plain objects and arrays are formatted as JSON.
+fn format_value(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> String {
+    if value.is_string() {
+        return value.to_rust_string_lossy(scope);
+    }
+
+    let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+    if value.is_object() && !value.is_function() && !value.is_native_error() {
+        if let Some(json) = v8::json::stringify(try_catch_scope, value) {
+            return json.to_rust_string_lossy(try_catch_scope);
+        }
+        try_catch_scope.reset();
+    }
+
+    match value.to_detail_string(try_catch_scope) {
+        Some(string) => string.to_rust_string_lossy(try_catch_scope),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{initialize_with_defaults, ConsoleLevel, Runtime, RuntimeOptions};
+
+    #[test]
+    fn console_sink() {
+        initialize_with_defaults();
+
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let sink_messages = messages.clone();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                console: Some(Box::new(move |level: ConsoleLevel, message: &str| {
+                    sink_messages
+                        .borrow_mut()
+                        .push((level, message.to_string()));
+                })),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        runtime
+            .execute::<(), _>(
+                "console.log('hello', 'world', 42); \
+                 console.warn('%s has %d items (%f%%)', 'bag', 3.7, 0.5); \
+                 console.error({ a: [1, 2] }, new Error('fail')); \
+                 console.debug(Symbol('id'), undefined, null); \
+                 console.info('%o', [1]);",
+            )
+            .expect("Can't execute code");
+
+        assert_eq!(
+            *messages.borrow(),
+            vec![
+                (ConsoleLevel::Log, "hello world 42".to_string()),
+                (ConsoleLevel::Warn, "bag has 3 items (0.5%)".to_string()),
+                (ConsoleLevel::Error, "{\"a\":[1,2]} Error: fail".to_string()),
+                (ConsoleLevel::Debug, "Symbol(id) undefined null".to_string()),
+                (ConsoleLevel::Info, "[1]".to_string()),
+            ]
+        );
+    }
+}
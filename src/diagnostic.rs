@@ -0,0 +1,49 @@
+//! Implements the diagnostics of [`crate::Runtime::check()`], that report the early errors of a
+//! script without executing it.
+
+/// A diagnostic the engine reported while compiling a script.
+///
+/// Lines are 1-based, columns and positions are 0-based. Columns are relative to the line,
+/// positions are relative to the start of the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The message of the diagnostic.
+    pub message: String,
+    /// The line in which the diagnosed range starts.
+    pub line: usize,
+    /// The column in which the diagnosed range starts.
+    pub start_column: usize,
+    /// The column in which the diagnosed range ends.
+    pub end_column: usize,
+    /// The position in the source at which the diagnosed range starts.
+    pub start_position: usize,
+    /// The position in the source at which the diagnosed range ends.
+    pub end_position: usize,
+}
+
+impl Diagnostic {
+    /// Creates a new [`Diagnostic`] from the message of an exception.
+    pub(crate) fn from_message(
+        scope: &mut v8::HandleScope,
+        message: v8::Local<v8::Message>,
+    ) -> Self {
+        Self {
+            message: message.get(scope).to_rust_string_lossy(scope),
+            line: message.get_line_number(scope).unwrap_or(0),
+            start_column: message.get_start_column(),
+            end_column: message.get_end_column(),
+            start_position: message.get_start_position().max(0) as usize,
+            end_position: message.get_end_position().max(0) as usize,
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}: {}",
+            self.line, self.start_column, self.end_column, self.message
+        )
+    }
+}
@@ -2,18 +2,44 @@
 
 use std::fmt::Debug;
 
-use crate::value::{Value, ValueScope};
+use crate::{
+    code_verifier::VerifyError,
+    runtime::{RuntimeData, STRICT_MODE_PREFIX},
+    value::{OwnedStackFrame, Seal, Value, ValueScope},
+};
 
 /// Errors that the crate can throw.
+///
+/// New variants can be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// The V8 engine was expected to be initialized before calling this functionality.
     V8NotInitialized,
     /// An script error.
-    Script(String),
+    Script(ScriptError),
     /// A general type error (e.g. when type conversion failed or an unexpected tape in in argument
     /// or return value was encountered).
     Type(TypeError),
+    /// An extension couldn't be registered.
+    Extension(String),
+    /// The script evaluated to `undefined`, but a value was expected. Most likely the script
+    /// is missing a final expression.
+    UndefinedResult,
+    /// The script evaluated to a promise, that is still pending after all microtasks were run.
+    PendingPromise,
+    /// The execution of the script was terminated, because it exceeded its time limit.
+    Terminated,
+    /// The execution of the script was terminated, because the heap reached its limit.
+    HeapLimit,
+    /// An IO error occurred while writing a result.
+    Io(std::io::Error),
+    /// The code verifier rejected a script.
+    Verification(VerifyError),
+    /// A script bundle is invalid or failed its integrity check.
+    Bundle(String),
+    /// The isolate of a runtime couldn't be created, for example because of invalid heap limits.
+    IsolateCreation(String),
     /// An implementation specific error occurred.
     Internal(String),
 }
@@ -24,17 +50,164 @@ impl std::fmt::Display for Error {
             Error::V8NotInitialized => write!(f, "V8 engine is not initialized"),
             Error::Script(msg) => write!(f, "Script error: {}", msg),
             Error::Type(err) => write!(f, "Type error: {}", err),
+            Error::Extension(msg) => write!(f, "Extension error: {}", msg),
+            Error::UndefinedResult => write!(f, "Script evaluated to undefined"),
+            Error::PendingPromise => write!(f, "Promise is still pending"),
+            Error::Terminated => write!(f, "Script execution was terminated"),
+            Error::HeapLimit => write!(f, "Heap limit reached"),
+            Error::Io(err) => write!(f, "IO error: {}", err),
+            Error::Verification(err) => write!(f, "Verification error: {}", err),
+            Error::Bundle(msg) => write!(f, "Invalid bundle: {}", msg),
+            Error::IsolateCreation(msg) => write!(f, "Can't create isolate: {}", msg),
             Error::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Script(err) => err.source(),
+            Error::Type(err) => err.source(),
+            Error::Io(err) => Some(err),
+            Error::Verification(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// An error that was thrown by a script.
+///
+/// The `cause` chain of the ECMAScript error is available as the chain of error sources.
+///
+/// Lines are 1-based, columns are 0-based and relative to the line. The display format
+/// contains the position and the offending source line with a caret under the thrown range:
+///
+/// ```text
+/// 'Uncaught Error: boom' at main.js:2:11
+/// const x = fail();
+///           ^^^^^^
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScriptError {
+    /// The message of the error.
+    pub message: String,
+    /// The name of the resource (module) in which the error was thrown. Classic scripts have
+    /// no resource name.
+    pub resource_name: Option<String>,
+    /// The line in which the error was thrown.
+    pub line: usize,
+    /// The column in which the thrown range starts.
+    pub start_column: usize,
+    /// The column in which the thrown range ends.
+    pub end_column: usize,
+    /// The source line in which the error was thrown.
+    pub source_line: Option<String>,
+    /// The frames of the stack, that was captured when the error was created, starting with the
+    /// innermost frame.
+    ///
+    /// Is only captured, if [`crate::RuntimeOptions::capture_stack_trace_for_uncaught_exceptions`]
+    /// is set.
+    pub stack: Vec<OwnedStackFrame>,
+    /// The error that caused this error (`error.cause`).
+    pub cause: Option<Box<ScriptError>>,
+    /// The label of the execution in which the error was thrown.
+    pub label: Option<String>,
+    /// The names of the most recent host functions, that were called before the error was
+    /// thrown, oldest first. Static and fastcall functions are named after their Rust function.
+    ///
+    /// The number of calls is set with [`crate::RuntimeOptions::host_call_breadcrumbs`]. Causes
+    /// have no breadcrumbs.
+    pub host_calls: Vec<String>,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "[{}] ", label)?;
+        }
+        write!(
+            f,
+            "'{}' at {}:{}:{}",
+            self.message,
+            self.resource_name.as_deref().unwrap_or("<anonymous>"),
+            self.line,
+            self.start_column + 1
+        )?;
+
+        if let Some(source_line) = self.source_line.as_deref() {
+            // Keep tabs, so that the caret is aligned with the source line.
+            let indentation: String = source_line
+                .chars()
+                .take(self.start_column)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let length = self.end_column.saturating_sub(self.start_column).max(1);
+            write!(
+                f,
+                "\n{}\n{}{}",
+                source_line,
+                indentation,
+                "^".repeat(length)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Stable codes of type errors, so that the category of an error can be checked without relying
+/// on the message.
+///
+/// When a host function receives an invalid argument, the code is set as the `code` property of
+/// the `TypeError`, that is returned to the script (e.g. `error.code === "ERR_ARG_RANGE"`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The value has a type that can't be converted (`ERR_ARG_TYPE`).
+    ArgumentType,
+    /// The value is not inside the range of the target type (`ERR_ARG_RANGE`).
+    ArgumentRange,
+    /// The value has the right type, but an invalid content, like a string with unpaired
+    /// surrogates (`ERR_ARG_VALUE`).
+    ArgumentValue,
+    /// Any other conversion error, for example from a custom serde implementation
+    /// (`ERR_CONVERSION`).
+    Conversion,
+}
+
+impl ErrorCode {
+    /// Returns the code as a string, as it is seen by scripts.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::ArgumentType => "ERR_ARG_TYPE",
+            ErrorCode::ArgumentRange => "ERR_ARG_RANGE",
+            ErrorCode::ArgumentValue => "ERR_ARG_VALUE",
+            ErrorCode::Conversion => "ERR_CONVERSION",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
 
 /// A general type error (e.g. when type conversion failed or an unexpected tape in in argument
 /// or return value was encountered).
 #[derive(Debug)]
 pub struct TypeError {
+    /// The stable category of the type error.
+    pub code: ErrorCode,
     /// The message of the type error.
     pub msg: String,
 }
@@ -47,6 +220,36 @@ impl std::fmt::Display for TypeError {
 
 impl std::error::Error for TypeError {}
 
+/// Error when a string is longer than the maximal string length of the engine.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StringTooLong {
+    /// The length of the string in UTF-8 bytes.
+    pub len: usize,
+    /// The maximal string length.
+    pub max: usize,
+}
+
+impl std::fmt::Display for StringTooLong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "String with a length of {} exceeds the maximal string length of {}",
+            self.len, self.max
+        )
+    }
+}
+
+impl std::error::Error for StringTooLong {}
+
+impl From<StringTooLong> for TypeError {
+    fn from(err: StringTooLong) -> Self {
+        Self {
+            code: ErrorCode::ArgumentRange,
+            msg: err.to_string(),
+        }
+    }
+}
+
 #[cfg(feature = "serde")]
 impl serde::de::Error for TypeError {
     fn custom<T>(msg: T) -> Self
@@ -54,6 +257,7 @@ impl serde::de::Error for TypeError {
         T: std::fmt::Display,
     {
         Self {
+            code: ErrorCode::Conversion,
             msg: msg.to_string(),
         }
     }
@@ -66,6 +270,7 @@ impl serde::ser::Error for TypeError {
         T: std::fmt::Display,
     {
         Self {
+            code: ErrorCode::Conversion,
             msg: msg.to_string(),
         }
     }
@@ -73,6 +278,7 @@ impl serde::ser::Error for TypeError {
 
 /// Shortcut to create a type error.
 pub fn create_type_error<'scope, S>(
+    code: ErrorCode,
     msg: S,
     scope: &mut ValueScope<'scope>,
     value: &Value<'scope>,
@@ -82,6 +288,7 @@ where
 {
     let source = value.to_string_representation(scope);
     TypeError {
+        code,
         msg: format!("{}: {}", msg.as_ref(), source),
     }
 }
@@ -95,14 +302,81 @@ pub(crate) fn create_error_from_exception(
         return Error::Internal("Exception was not set".to_string());
     };
 
+    Error::Script(create_script_error(scope, exception, 0))
+}
+
+/// Maximal number of causes we follow, since causes could form a cycle.
+const MAX_CAUSE_DEPTH: usize = 16;
+
+/// Creates a script error from an exception and follows its `cause` chain.
+fn create_script_error(
+    scope: &mut v8::HandleScope,
+    exception: v8::Local<v8::Value>,
+    depth: usize,
+) -> ScriptError {
     let msg = v8::Exception::create_message(scope, exception);
 
-    // TODO create a proper EcmaScript error from the Local<Message> (lines etc.).
-    let message_string = msg.get(scope).to_rust_string_lossy(scope);
+    let message = msg.get(scope).to_rust_string_lossy(scope);
+    let resource_name = msg
+        .get_script_resource_name(scope)
+        .filter(|name| !name.is_null_or_undefined())
+        .map(|name| name.to_rust_string_lossy(scope));
+    let line = msg.get_line_number(scope).unwrap_or(0);
+    let mut start_column = msg.get_start_column();
+    let mut end_column = msg.get_end_column();
+    let mut source_line = msg
+        .get_source_line(scope)
+        .map(|source_line| source_line.to_rust_string_lossy(scope));
 
-    let line_number = msg.get_line_number(scope).unwrap_or(0);
+    // Hide the injected strict mode directive, so that the position matches the original source.
+    if line == 1 {
+        if let Some(stripped) = source_line
+            .as_deref()
+            .and_then(|source_line| source_line.strip_prefix(STRICT_MODE_PREFIX))
+        {
+            source_line = Some(stripped.to_string());
+            start_column = start_column.saturating_sub(STRICT_MODE_PREFIX.len());
+            end_column = end_column.saturating_sub(STRICT_MODE_PREFIX.len());
+        }
+    }
 
-    let formatted = format!("'{}' in line: {}", message_string, line_number);
+    let stack = v8::Exception::get_stack_trace(scope, exception)
+        .or_else(|| msg.get_stack_trace(scope))
+        .map(|stack_trace| stack_trace.seal().to_owned_frames(scope.seal()))
+        .unwrap_or_default();
 
-    Error::Script(formatted)
+    let cause = if depth < MAX_CAUSE_DEPTH && exception.is_native_error() {
+        exception
+            .to_object(scope)
+            .and_then(|object| {
+                let key = v8::String::new(scope, "cause")?;
+                object.get(scope, key.into())
+            })
+            .filter(|cause| !cause.is_undefined())
+            .map(|cause| Box::new(create_script_error(scope, cause, depth + 1)))
+    } else {
+        None
+    };
+
+    let host_calls = if depth == 0 {
+        match RuntimeData::from_isolate(scope) {
+            Some(runtime_data) => runtime_data.breadcrumbs.borrow().names(),
+            None => Vec::new(),
+        }
+    } else {
+        Vec::new()
+    };
+
+    ScriptError {
+        message,
+        resource_name,
+        line,
+        start_column,
+        end_column,
+        source_line,
+        stack,
+        cause,
+        label: None,
+        host_calls,
+    }
 }
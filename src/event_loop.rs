@@ -0,0 +1,54 @@
+//! Implements the event loop abstraction, that lets the host drive the pending work of scripts.
+
+use std::time::Instant;
+
+/// Lets the host drive all pending work of scripts from its own loop (e.g. a game loop or an
+/// async executor).
+///
+/// Call [`ScriptEventLoop::tick()`] once per iteration of the host loop. The timers of a runtime
+/// are provided by the [`crate::TimerExtension`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Instant;
+///
+/// use kopi::{initialize_with_defaults, Runtime, RuntimeOptions, ScriptEventLoop, TimerExtension};
+///
+/// initialize_with_defaults();
+///
+/// let mut runtime = Runtime::new(
+///     RuntimeOptions {
+///         extensions: vec![TimerExtension::create()],
+///         ..Default::default()
+///     },
+///     (),
+/// )
+/// .expect("Can't create runtime");
+/// runtime
+///     .execute::<(), _>("setTimeout(() => Promise.resolve().then(() => {}), 10);")
+///     .expect("Can't execute code");
+///
+/// while runtime.has_pending_work() {
+///     runtime.tick(Instant::now());
+/// }
+/// ```
+pub trait ScriptEventLoop {
+    /// Runs the callbacks of all timers, that expired at or before `now`. Returns the number of
+    /// callbacks that were run.
+    fn poll_timers(&mut self, now: Instant) -> usize;
+
+    /// Runs all pending microtasks (e.g. promise reactions).
+    fn drain_microtasks(&mut self);
+
+    /// Returns `true` if there is work left, that will be run by a later tick.
+    fn has_pending_work(&self) -> bool;
+
+    /// Runs all expired timers and drains the microtasks afterwards. Returns `true` if there is
+    /// work left, that will be run by a later tick.
+    fn tick(&mut self, now: Instant) -> bool {
+        self.poll_timers(now);
+        self.drain_microtasks();
+        self.has_pending_work()
+    }
+}
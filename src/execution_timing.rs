@@ -0,0 +1,85 @@
+//! Implements the timing of executions.
+
+use std::time::Duration;
+
+/// The timing of an execution, measured by [`crate::Runtime::execute_timed()`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionTiming {
+    /// The wall time of the whole call.
+    pub wall: Duration,
+    /// The wall time spent compiling the script. Is zero if the script was taken from the
+    /// script cache.
+    pub compile: Duration,
+    /// The wall time spent executing the script, including the host functions it called.
+    pub execute: Duration,
+    /// The wall time spent inside host functions. Scripts that are called by host functions
+    /// are counted as part of the host function.
+    pub host_calls: Duration,
+    /// The CPU time of the current thread during the call. Is `None` on platforms that don't
+    /// provide the CPU time of a thread.
+    pub cpu: Option<Duration>,
+}
+
+impl ExecutionTiming {
+    /// Returns the wall time spent executing the script itself, without the time spent inside
+    /// host functions.
+    pub fn script(&self) -> Duration {
+        self.execute.saturating_sub(self.host_calls)
+    }
+}
+
+/// The timings, that are collected by the runtime while an execution is timed.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct TimingCollector {
+    pub(crate) compile: Duration,
+    pub(crate) host_calls: Duration,
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod cpu_time {
+    use std::time::Duration;
+
+    pub(crate) fn thread_cpu_time() -> Option<Duration> {
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+
+        // SAFETY: The pointer is valid for the duration of the call.
+        if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } != 0 {
+            return None;
+        }
+
+        let secs = u64::try_from(time.tv_sec).ok()?;
+        let nanos = u32::try_from(time.tv_nsec).ok()?;
+        Some(Duration::new(secs, nanos))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod cpu_time {
+    use std::time::Duration;
+
+    pub(crate) fn thread_cpu_time() -> Option<Duration> {
+        None
+    }
+}
+
+pub(crate) use cpu_time::thread_cpu_time;
+
+#[cfg(all(test, any(target_os = "linux", target_os = "macos")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn thread_cpu_time_advances() {
+        let start = thread_cpu_time().expect("No thread CPU time");
+
+        // Busy wait, so that the thread uses CPU time.
+        let wall = std::time::Instant::now();
+        while wall.elapsed() < Duration::from_millis(20) {}
+
+        let end = thread_cpu_time().expect("No thread CPU time");
+        assert!(end > start);
+    }
+}
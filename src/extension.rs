@@ -2,14 +2,23 @@ use std::{
     any::Any,
     cell::RefCell,
     collections::HashMap,
+    error::Error,
     ffi::{c_int, c_void},
+    future::Future,
     marker::PhantomData,
     sync::Arc,
+    time::Instant,
 };
 
 use crate::{
-    runtime::STATE_DATA_SLOT,
-    traits::{Deserialize, Serialize},
+    async_tasks::spawn_task,
+    binding_interceptor::BindingOperation,
+    breadcrumbs::HostCall,
+    class::{class_template, Class, ClassTemplate},
+    error::TypeError,
+    rate_limit::{RateLimit, RateLimiter},
+    runtime::{RuntimeData, STATE_DATA_SLOT},
+    traits::{Deserialize, DeserializeOwned, Serialize},
     value::{self, NewStringType, Seal, Unseal},
 };
 
@@ -62,11 +71,150 @@ pub trait FunctionWithStateArguments<'scope, F, R, S>: private::Sealed {
     );
 }
 
+/// Trait for the arguments of extension functions that have default values for missing arguments.
+///
+/// This is a sealed trait that is not supposed to be implemented outside the crate.
+pub trait FunctionWithDefaultsArguments<'scope, F, R>: private::Sealed {
+    #[doc(hidden)]
+    fn call(
+        scope: &mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+        cb_data: &F,
+        defaults: &Self,
+    );
+}
+
+/// Trait for the arguments of extension functions that can mutate their captured variables.
+///
+/// This is a sealed trait that is not supposed to be implemented outside the crate.
+pub trait FunctionMutArguments<'scope, F, R>: private::Sealed {
+    #[doc(hidden)]
+    fn call(
+        scope: &mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+        cb_data: &mut F,
+    );
+}
+
+/// Trait for the arguments of async extension functions.
+///
+/// This is a sealed trait that is not supposed to be implemented outside the crate.
+pub trait FunctionAsyncArguments<'scope, F, FU>: private::Sealed {
+    #[doc(hidden)]
+    fn call(
+        scope: &mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+        cb_data: &F,
+    );
+}
+
 mod private {
     /// Seal for the [`super::FunctionArguments`] trait.
     pub trait Sealed {}
 }
 
+// Must be public because of the `static_function` macro.
+/// Tracks the depth of nested host function calls for the lifetime of a host function call.
+#[doc(hidden)]
+pub struct HostCallGuard {
+    data: *const RuntimeData,
+    /// The start of the outermost host function call, if the execution is timed.
+    start: Option<Instant>,
+}
+
+impl HostCallGuard {
+    /// Enters a host function call. Throws a `RangeError` and returns `None` if the maximal
+    /// depth of nested host function calls is exceeded.
+    #[inline(always)]
+    pub fn enter(scope: &mut v8::HandleScope) -> Option<Self> {
+        let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+            return Some(Self {
+                data: std::ptr::null(),
+                start: None,
+            });
+        };
+
+        let depth = runtime_data.host_call_depth.get();
+        if let Some(max_depth) = runtime_data.max_host_call_depth {
+            if depth >= max_depth {
+                let msg = value::new_string(
+                    scope,
+                    format!("Maximum host function call depth of {} exceeded", max_depth),
+                    NewStringType::Normal,
+                );
+                let exception = v8::Exception::range_error(scope, msg);
+                scope.throw_exception(exception);
+                return None;
+            }
+        }
+        runtime_data.host_call_depth.set(depth + 1);
+        runtime_data.count(|c| c.host_calls += 1);
+
+        let start = match runtime_data.timing.get() {
+            Some(_) if depth == 0 => Some(Instant::now()),
+            _ => None,
+        };
+
+        Some(Self {
+            data: runtime_data,
+            start,
+        })
+    }
+
+    /// Enters the call of a closure and records it in the breadcrumbs of the runtime.
+    #[inline(always)]
+    pub(crate) fn enter_closure(
+        scope: &mut v8::HandleScope,
+        args: &v8::FunctionCallbackArguments,
+    ) -> Option<Self> {
+        let guard = Self::enter(scope)?;
+        let cb_data = v8::Local::<v8::External>::cast(args.data()).value() as *const c_void;
+        guard.record(HostCall::Closure(cb_data));
+        Some(guard)
+    }
+
+    /// Enters the call of a static function and records its name in the breadcrumbs of the
+    /// runtime.
+    #[inline(always)]
+    pub fn enter_static(scope: &mut v8::HandleScope, name: &'static str) -> Option<Self> {
+        let guard = Self::enter(scope)?;
+        guard.record(HostCall::Static(name));
+        Some(guard)
+    }
+
+    #[inline(always)]
+    fn record(&self, call: HostCall) {
+        // SAFETY: This is safe since the guard never outlives the host function call, which
+        //         is bound to the lifetime of this runtime.
+        if let Some(runtime_data) = unsafe { self.data.as_ref() } {
+            // A host function, that is called while the breadcrumbs are named, isn't recorded.
+            if let Ok(mut breadcrumbs) = runtime_data.breadcrumbs.try_borrow_mut() {
+                breadcrumbs.record(call);
+            }
+        }
+    }
+}
+
+impl Drop for HostCallGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        // SAFETY: This is safe since the guard never outlives the host function call, which
+        //         is bound to the lifetime of this runtime.
+        if let Some(runtime_data) = unsafe { self.data.as_ref() } {
+            let depth = runtime_data.host_call_depth.get();
+            runtime_data.host_call_depth.set(depth.saturating_sub(1));
+
+            if let (Some(start), Some(mut timing)) = (self.start, runtime_data.timing.get()) {
+                timing.host_calls += start.elapsed();
+                runtime_data.timing.set(Some(timing));
+            }
+        }
+    }
+}
+
 // Must be public because of the `static_function` macro.
 #[doc(hidden)]
 #[inline(always)]
@@ -83,10 +231,7 @@ pub fn set_result<'scope, R>(
     if R::DEFINED_RETURN_VALUE {
         let value = match result.serialize(scope) {
             Ok(value) => value,
-            Err(err) => {
-                let msg = value::String::new(scope, err.msg, NewStringType::Normal);
-                value::Error::new_type_error(scope, msg)
-            }
+            Err(err) => new_type_error(scope, err),
         };
         rv.set(value.unseal());
     }
@@ -110,14 +255,151 @@ where
     return match A::deserialize(scope, local_value.seal()) {
         Ok(arg) => Some(arg),
         Err(err) => {
-            let msg = value::String::new(scope, err.msg, NewStringType::Normal);
-            let error = value::Error::new_type_error(scope, msg);
-            rv.set(error.unseal());
+            count_conversion_error(scope.unseal(), args);
+            let error = new_type_error(scope, err);
+            set_validation_details::<A>(scope, error, pos, local_value);
+
+            // A constructor must not evaluate to the error instead of the new instance.
+            if args.new_target().is_undefined() {
+                rv.set(error.unseal());
+            } else {
+                scope.unseal().throw_exception(error.unseal());
+            }
             None
         }
     };
 }
 
+/// Creates the `TypeError` that is returned to the script. The code of the error is set as the
+/// `code` property.
+#[inline(never)]
+pub(crate) fn new_type_error<'scope>(
+    scope: &mut value::ValueScope<'scope>,
+    err: TypeError,
+) -> value::Value<'scope> {
+    let msg = value::String::new_truncated(scope, err.msg, NewStringType::Normal);
+    let error = value::Error::new_type_error(scope, msg);
+
+    if let Ok(object) = v8::Local::<v8::Object>::try_from(error.unseal()) {
+        let key = value::new_string(scope.unseal(), "code", NewStringType::Normal);
+        let code = value::new_string(scope.unseal(), err.code.as_str(), NewStringType::Normal);
+        object.set(scope.unseal(), key.into(), code.into());
+    }
+
+    error
+}
+
+/// Sets the `path`, `expected` and `actual` properties of the `TypeError` of a failed argument
+/// conversion, so that script authors can see which argument was invalid (e.g.
+/// `{ path: "arguments[1]", expected: "u8", actual: "string" }`).
+#[inline(never)]
+fn set_validation_details<'scope, A>(
+    scope: &mut value::ValueScope<'scope>,
+    error: value::Value<'scope>,
+    pos: c_int,
+    value: v8::Local<'scope, v8::Value>,
+) {
+    let Ok(object) = v8::Local::<v8::Object>::try_from(error.unseal()) else {
+        return;
+    };
+
+    let scope = scope.unseal();
+    let details = [
+        ("path", format!("arguments[{}]", pos)),
+        ("expected", short_type_name(std::any::type_name::<A>())),
+        ("actual", type_description(scope, value)),
+    ];
+
+    for (name, detail) in details {
+        let key = value::new_string(scope, name, NewStringType::Normal);
+        let detail = value::new_string(scope, detail, NewStringType::Normal);
+        object.set(scope, key.into(), detail.into());
+    }
+}
+
+/// Returns the type name without the module paths (e.g. `Vec<String>` instead of
+/// `alloc::vec::Vec<alloc::string::String>`).
+fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut token_start = 0;
+
+    for (index, c) in name.char_indices() {
+        if !(c.is_alphanumeric() || c == '_' || c == ':') {
+            short.push_str(
+                name[token_start..index]
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or_default(),
+            );
+            short.push(c);
+            token_start = index + c.len_utf8();
+        }
+    }
+    short.push_str(name[token_start..].rsplit("::").next().unwrap_or_default());
+
+    short
+}
+
+/// Returns the type of the value as seen by script authors.
+fn type_description(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> String {
+    if value.is_null() {
+        "null".to_string()
+    } else if value.is_array() {
+        "array".to_string()
+    } else {
+        value.type_of(scope).to_rust_string_lossy(scope)
+    }
+}
+
+/// Returns the closure, that is stored in the data of the host function.
+///
+/// # Safety
+///
+/// The data must be the pointer returned by [`Extension::store_closure()`] for a closure of
+/// type `C`. The runtime keeps the closure alive as long as its isolate exists, so the returned
+/// reference is valid for the duration of the call.
+#[inline(always)]
+unsafe fn closure_data<'a, C>(args: &v8::FunctionCallbackArguments) -> &'a C {
+    &*(v8::Local::<v8::External>::cast(args.data()).value() as *const C)
+}
+
+/// Counts a failed argument conversion of a host function. Only closures are counted, since
+/// static functions have no callback data to identify them.
+#[inline(never)]
+fn count_conversion_error(scope: &mut v8::HandleScope, args: &v8::FunctionCallbackArguments) {
+    let Ok(external) = v8::Local::<v8::External>::try_from(args.data()) else {
+        return;
+    };
+
+    if let Some(runtime_data) = RuntimeData::from_isolate(scope) {
+        *runtime_data
+            .conversion_errors
+            .borrow_mut()
+            .entry(external.value() as *const c_void)
+            .or_insert(0) += 1;
+    }
+}
+
+/// Returns the argument at the given position or the default value, if the argument is
+/// `undefined`, which is the case when the argument was not given.
+#[inline(always)]
+fn get_argument_or_default<'scope, A>(
+    scope: &mut v8::HandleScope<'scope>,
+    args: &v8::FunctionCallbackArguments<'scope>,
+    rv: &mut v8::ReturnValue,
+    pos: c_int,
+    default: &A,
+) -> Option<A>
+where
+    A: Deserialize<'scope> + Clone,
+{
+    if args.get(pos).is_undefined() {
+        Some(default.clone())
+    } else {
+        get_argument(scope, args, rv, pos)
+    }
+}
+
 #[rustfmt::skip]
 macro_rules! impl_function_arguments {
     () => (
@@ -138,6 +420,23 @@ macro_rules! impl_function_arguments {
             }
         }
         
+        impl<'scope, FN, RE> FunctionMutArguments<'scope, FN, RE> for ()
+        where
+            FN: 'static + FnMut(()) -> RE,
+            RE: 'static + Serialize,
+        {
+            #[inline(always)]
+            fn call(
+                scope: &mut v8::HandleScope<'scope>,
+                _args: v8::FunctionCallbackArguments<'scope>,
+                rv: v8::ReturnValue,
+                op: &mut FN,
+            ) {
+                let result = op(());
+                set_result(scope, rv, result);
+            }
+        }
+
         impl<'scope, FN, RE, STATE> FunctionWithStateArguments<'scope, FN, RE, STATE> for ()
         where
             FN: 'static + Send + Sync + Fn(&mut STATE, ()) -> RE,
@@ -155,10 +454,29 @@ macro_rules! impl_function_arguments {
                 set_result(scope, rv, result);
             }
         }
-        
+
+        impl<'scope, FN, FU, RE, ER> FunctionAsyncArguments<'scope, FN, FU> for ()
+        where
+            FN: 'static + Send + Sync + Fn(()) -> FU,
+            FU: 'static + Future<Output = Result<RE, ER>>,
+            RE: 'static + Serialize,
+            ER: 'static + Into<Box<dyn Error>>,
+        {
+            #[inline(always)]
+            fn call(
+                scope: &mut v8::HandleScope<'scope>,
+                _args: v8::FunctionCallbackArguments<'scope>,
+                rv: v8::ReturnValue,
+                op: &FN,
+            ) {
+                let future = op(());
+                spawn_task(scope, rv, future);
+            }
+        }
+
         impl private::Sealed for () {}
     );
-    ($($generic:ident)*; $($arg:ident)*; $($count:literal)*) => {
+    ($($generic:ident)*; $($arg:ident)*; $($count:tt)*) => {
         impl<'scope, FN, RE, $($generic,)*> FunctionArguments<'scope, FN, RE> for ($($generic,)*)
         where
             FN: 'static + Send + Sync + Fn(($($generic,)*)) -> RE,
@@ -182,6 +500,29 @@ macro_rules! impl_function_arguments {
             }
         }
 
+        impl<'scope, FN, RE, $($generic,)*> FunctionMutArguments<'scope, FN, RE> for ($($generic,)*)
+        where
+            FN: 'static + FnMut(($($generic,)*)) -> RE,
+            RE: 'static + Serialize,
+            $($generic: Deserialize<'scope>,)*
+        {
+            #[inline(always)]
+            fn call(
+                scope: &mut v8::HandleScope<'scope>,
+                args: v8::FunctionCallbackArguments<'scope>,
+                mut rv: v8::ReturnValue,
+                op: &mut FN,
+            ) {
+                $(
+                let Some($arg) = get_argument(scope, &args, &mut rv, $count) else {
+                    return;
+                };
+                )*
+                let result = op(($($arg,)*));
+                set_result(scope, rv, result);
+            }
+        }
+
         impl<'scope, FN, RE, STATE, $($generic,)*> FunctionWithStateArguments<'scope, FN, RE, STATE> for ($($generic,)*)
         where
             FN: 'static + Send + Sync + Fn(&mut STATE, ($($generic,)*)) -> RE,
@@ -206,6 +547,55 @@ macro_rules! impl_function_arguments {
             }
         }
 
+        impl<'scope, FN, RE, $($generic,)*> FunctionWithDefaultsArguments<'scope, FN, RE> for ($($generic,)*)
+        where
+            FN: 'static + Send + Sync + Fn(($($generic,)*)) -> RE,
+            RE: 'static + Serialize,
+            $($generic: Deserialize<'scope> + Clone,)*
+        {
+            #[inline(always)]
+            fn call(
+                scope: &mut v8::HandleScope<'scope>,
+                args: v8::FunctionCallbackArguments<'scope>,
+                mut rv: v8::ReturnValue,
+                op: &FN,
+                defaults: &Self,
+            ) {
+                $(
+                let Some($arg) = get_argument_or_default(scope, &args, &mut rv, $count, &defaults.$count) else {
+                    return;
+                };
+                )*
+                let result = op(($($arg,)*));
+                set_result(scope, rv, result);
+            }
+        }
+
+        impl<'scope, FN, FU, RE, ER, $($generic,)*> FunctionAsyncArguments<'scope, FN, FU> for ($($generic,)*)
+        where
+            FN: 'static + Send + Sync + Fn(($($generic,)*)) -> FU,
+            FU: 'static + Future<Output = Result<RE, ER>>,
+            RE: 'static + Serialize,
+            ER: 'static + Into<Box<dyn Error>>,
+            $($generic: Deserialize<'scope>,)*
+        {
+            #[inline(always)]
+            fn call(
+                scope: &mut v8::HandleScope<'scope>,
+                args: v8::FunctionCallbackArguments<'scope>,
+                mut rv: v8::ReturnValue,
+                op: &FN,
+            ) {
+                $(
+                let Some($arg) = get_argument(scope, &args, &mut rv, $count) else {
+                    return;
+                };
+                )*
+                let future = op(($($arg,)*));
+                spawn_task(scope, rv, future);
+            }
+        }
+
         impl<$($generic,)*> private::Sealed for ($($generic,)*) {}
     };
 }
@@ -293,6 +683,48 @@ impl_function_arguments!(
     0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
 );
 
+/// The declarations of an extension keyed by their path. Keeps the order in which the
+/// declarations were added, so that they are listed and installed in a stable order.
+#[derive(Default)]
+pub(crate) struct Declarations {
+    entries: Vec<(String, FunctionDeclaration)>,
+    indices: HashMap<String, usize>,
+}
+
+impl Declarations {
+    /// Inserts the declaration. A declaration with the same path is replaced in place.
+    pub(crate) fn insert(&mut self, path: String, declaration: FunctionDeclaration) {
+        match self.indices.get(&path) {
+            Some(&index) => self.entries[index].1 = declaration,
+            None => {
+                self.indices.insert(path.clone(), self.entries.len());
+                self.entries.push((path, declaration));
+            }
+        }
+    }
+
+    /// Returns the number of declarations. Replaced declarations are only counted once.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the paths of the declarations in the order they were added.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|(path, _)| path.as_str())
+    }
+
+    /// Returns the declarations in the order they were added.
+    pub(crate) fn values(&self) -> impl Iterator<Item = &FunctionDeclaration> + '_ {
+        self.entries.iter().map(|(_, declaration)| declaration)
+    }
+
+    /// Removes all declarations and returns them in the order they were added.
+    pub(crate) fn drain(&mut self) -> std::vec::Drain<'_, (String, FunctionDeclaration)> {
+        self.indices.clear();
+        self.entries.drain(..)
+    }
+}
+
 pub(crate) enum FunctionDeclaration {
     Closure {
         cb_data: *mut c_void,
@@ -303,66 +735,417 @@ pub(crate) enum FunctionDeclaration {
         fastcall: Box<dyn v8::fast_api::FastFunction>,
         function_callback: v8::FunctionCallback,
     },
+    Class {
+        cb_data: *mut c_void,
+        template: ClassTemplate,
+    },
+    Property {
+        getter: (*mut c_void, v8::FunctionCallback),
+        setter: Option<(*mut c_void, v8::FunctionCallback)>,
+    },
+}
+
+/// The resources an extension contributes to every runtime, returned by
+/// [`crate::Runtime::extension_footprint()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionFootprint {
+    /// The namespace of the extension, or `global` for global extensions.
+    pub name: String,
+    /// The number of functions of the extension.
+    pub functions: usize,
+    /// The number of closures, that the runtime keeps alive.
+    pub closures: usize,
+    /// The size of the data captured by the closures in bytes. Heap allocations owned by the
+    /// captured data are not included.
+    pub closure_bytes: usize,
+    /// The number of function and object templates created for the extension.
+    pub templates: usize,
+    /// The size of the scripts, that are evaluated while the runtime is created (e.g. the
+    /// prototype expression) in bytes.
+    pub script_bytes: usize,
 }
 
 /// Creates a extension, which provide the functionality to call native Rust code from within scripts.
 pub struct Extension<STATE> {
     pub(crate) namespace: Option<String>,
-    pub(crate) declarations: HashMap<String, FunctionDeclaration>,
-    pub(crate) closures: Vec<Arc<dyn Any>>,
+    /// The namespace is an existing object that is extended.
+    pub(crate) extending: bool,
+    pub(crate) declarations: Declarations,
+    /// The closures of the extension functions together with their registration names.
+    pub(crate) closures: Vec<(String, Arc<dyn Any>)>,
+    /// The rate limiters of the rate limited extension functions.
+    pub(crate) rate_limiters: Vec<Arc<RateLimiter>>,
+    /// The namespaces of the extensions that need to be registered before this extension.
+    pub(crate) requires: Vec<String>,
+    /// The expression that evaluates to the prototype of the namespace object.
+    pub(crate) prototype: Option<String>,
+    /// Is notified when a script attempts to modify the namespace object.
+    pub(crate) interceptor: Option<Box<dyn Fn(&str, BindingOperation) -> bool>>,
     _state_marker: PhantomData<STATE>,
 }
 
 impl<STATE> Extension<STATE> {
     /// Creates a new [`Extension`]. If no namespace is given, then the functions will be created
     /// in the global namespace.
+    ///
+    /// Nested namespaces can be given as a path like `a.b.c`. Missing objects along the path are
+    /// created and existing namespace objects of other extensions are shared, so that multiple
+    /// extensions can add functions to the same namespace. Creating the runtime fails, if two
+    /// extensions add a function with the same name to the same namespace.
     pub fn new(namespace: Option<&str>) -> Self {
         let namespace = namespace.map(|n| n.into());
         Self {
             namespace,
-            declarations: HashMap::default(),
+            extending: false,
+            declarations: Declarations::default(),
             closures: Vec::default(),
+            rate_limiters: Vec::default(),
+            requires: Vec::default(),
+            prototype: None,
+            interceptor: None,
             _state_marker: PhantomData::default(),
         }
     }
 
-    #[inline(always)]
-    fn v8_func<'borrow, 'scope, F, A, R>(
-        scope: &'borrow mut v8::HandleScope<'scope>,
-        args: v8::FunctionCallbackArguments<'scope>,
-        rv: v8::ReturnValue,
-    ) where
-        F: 'static + Send + Sync + Fn(A) -> R,
-        A: FunctionArguments<'scope, F, R>,
-        R: Serialize,
-    {
-        // SAFETY: This is safe since we made sure to leak the boxed callback (static lifetime)
-        //         and the implementation makes sure, that the data contains the pointer of the
-        //         expected closure callback for this function callback.
-        let cb_data = unsafe {
-            &*(v8::Local::<v8::External>::cast(args.data()).value() as *const c_void as *const F)
-        };
+    /// Creates a new [`Extension`] that adds its functions to an existing object, for example
+    /// `Math` or `console`. Nested objects can be targeted with a path like `a.b.c`.
+    ///
+    /// Creating the runtime fails, if the object doesn't exist or if it already contains a
+    /// property with the name of a function.
+    pub fn new_extending(object_path: &str) -> Self {
+        Self {
+            namespace: Some(object_path.into()),
+            extending: true,
+            declarations: Declarations::default(),
+            closures: Vec::default(),
+            rate_limiters: Vec::default(),
+            requires: Vec::default(),
+            prototype: None,
+            interceptor: None,
+            _state_marker: PhantomData::default(),
+        }
+    }
 
-        A::call(scope, args, rv, cb_data);
+    /// Declares that this extension depends on the extension with the given namespace.
+    ///
+    /// The runtime registers the extensions in the order of their dependencies. Creating the
+    /// runtime fails, if a dependency is missing or if the dependencies form a cycle.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::Extension;
+    ///
+    /// let core = Extension::<()>::new(Some("core"));
+    ///
+    /// let mut math = Extension::<()>::new_extending("core");
+    /// math.requires("core");
+    /// ```
+    pub fn requires(&mut self, namespace: &str) {
+        self.requires.push(namespace.into());
     }
 
-    #[inline(always)]
-    fn v8_func_with_state<'borrow, 'scope, F, A, R>(
-        scope: &'borrow mut v8::HandleScope<'scope>,
-        args: v8::FunctionCallbackArguments<'scope>,
-        rv: v8::ReturnValue,
-    ) where
-        F: 'static + Send + Sync + Fn(&mut STATE, A) -> R,
+    /// Sets the prototype of the namespace object. The expression is evaluated inside the
+    /// runtime before the namespace object is created and must evaluate to an object or `null`.
+    ///
+    /// The prototype can be defined by the expression itself or by an extension that is
+    /// registered before this extension (see [`Extension::requires()`]).
+    ///
+    /// Creating the runtime fails, if the expression can't be evaluated, or if the extension
+    /// has no namespace or extends an existing object.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::Extension;
+    ///
+    /// let mut events = Extension::<()>::new(Some("events"));
+    /// events.set_prototype("({ on(name, listener) { this[name] = listener; } })");
+    /// ```
+    pub fn set_prototype(&mut self, expression: &str) {
+        self.prototype = Some(expression.into());
+    }
+
+    /// Sets an interceptor, that is called with the path of the property (e.g. `game.spawn`) and
+    /// the operation, when a script attempts to set, delete or define a property of the
+    /// namespace object. The operation is denied if the interceptor returns `false`, which
+    /// throws a `TypeError` in strict mode.
+    ///
+    /// The interceptor is also called for denied operations, so it can be used to audit attempts
+    /// to tamper with host bindings. The binding of the namespace itself is made read-only.
+    ///
+    /// Creating the runtime fails, if the extension has no namespace or if another extension
+    /// already set an interceptor for the same namespace.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::{BindingOperation, Extension};
+    ///
+    /// let mut game = Extension::<()>::new(Some("game"));
+    /// game.intercept_modifications(|path, operation| {
+    ///     log::warn!("Script attempted {:?} on {}", operation, path);
+    ///     false
+    /// });
+    /// ```
+    pub fn intercept_modifications<F>(&mut self, interceptor: F)
+    where
+        F: Fn(&str, BindingOperation) -> bool + 'static,
+    {
+        self.interceptor = Some(Box::new(interceptor));
+    }
+
+    /// Returns a builder to add functions to a nested namespace of the extension. The functions
+    /// are registered under the path `namespace.function`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::Extension;
+    ///
+    /// let mut extension = Extension::<()>::new(None);
+    /// extension
+    ///     .namespace("game")
+    ///     .function("spawn", |(id,): (u32,)| id)
+    ///     .function("despawn", |(_id,): (u32,)| true);
+    /// ```
+    pub fn namespace(&mut self, name: &str) -> Namespace<'_, STATE> {
+        Namespace {
+            extension: self,
+            path: name.into(),
+        }
+    }
+
+    /// Returns the names of the declared functions, properties and classes in the order they
+    /// were added. Declarations inside nested namespaces are named by their path, for example
+    /// `game.spawn`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::Extension;
+    ///
+    /// let mut extension = Extension::<()>::new(Some("math"));
+    /// extension.add_function("sub", |(a, b): (i32, i32)| a - b);
+    /// extension.add_function("add", |(a, b): (i32, i32)| a + b);
+    ///
+    /// assert_eq!(extension.names().collect::<Vec<_>>(), vec!["sub", "add"]);
+    /// ```
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.declarations.keys()
+    }
+
+    /// Stores the closure of a function and returns the pointer, that is passed to the engine as
+    /// the data of the function.
+    ///
+    /// The closures are owned by the extension and then by the runtime, which keeps them alive
+    /// as long as its isolate exists. This keeps the pointer valid for every call of the function.
+    // The runtime is single threaded, so closures that are neither `Send` nor `Sync` are never
+    // shared between threads.
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn store_closure<C>(&mut self, registration_name: String, closure: C) -> *mut c_void
+    where
+        C: 'static,
+    {
+        let closure = Arc::new(closure);
+        let cb_data = Arc::as_ptr(&closure) as *mut C as *mut c_void;
+        self.closures.push((registration_name, closure));
+        cb_data
+    }
+
+    /// Returns the name of the function as seen from inside a script.
+    fn registration_name(&self, name: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}.{}", namespace, name),
+            None => name.to_string(),
+        }
+    }
+
+    #[inline(always)]
+    fn v8_func<'borrow, 'scope, F, A, R>(
+        scope: &'borrow mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+    ) where
+        F: 'static + Send + Sync + Fn(A) -> R,
+        A: FunctionArguments<'scope, F, R>,
+        R: Serialize,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let cb_data = unsafe { closure_data::<F>(&args) };
+
+        A::call(scope, args, rv, cb_data);
+    }
+
+    #[inline(always)]
+    fn v8_getter<G, R>(
+        scope: &mut v8::HandleScope,
+        args: v8::FunctionCallbackArguments,
+        rv: v8::ReturnValue,
+    ) where
+        G: 'static + Send + Sync + Fn() -> R,
+        R: 'static + Serialize,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let getter = unsafe { closure_data::<G>(&args) };
+
+        set_result(scope, rv, getter());
+    }
+
+    #[inline(always)]
+    fn v8_setter<'scope, S, V>(
+        scope: &mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        _rv: v8::ReturnValue,
+    ) where
+        S: 'static + Send + Sync + Fn(V),
+        V: DeserializeOwned,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let setter = unsafe { closure_data::<S>(&args) };
+
+        // The return value of a setter is ignored, so the error must be thrown.
+        let scope = scope.seal();
+        match V::deserialize(scope, args.get(0).seal()) {
+            Ok(value) => setter(value),
+            Err(err) => {
+                count_conversion_error(scope.unseal(), &args);
+                let error = new_type_error(scope, err);
+                scope.unseal().throw_exception(error.unseal());
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn v8_func_async<'borrow, 'scope, F, A, FU>(
+        scope: &'borrow mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+    ) where
+        F: 'static + Send + Sync + Fn(A) -> FU,
+        A: FunctionAsyncArguments<'scope, F, FU>,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let cb_data = unsafe { closure_data::<F>(&args) };
+
+        A::call(scope, args, rv, cb_data);
+    }
+
+    #[inline(always)]
+    fn v8_func_with_defaults<'borrow, 'scope, F, A, R>(
+        scope: &'borrow mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+    ) where
+        F: 'static + Send + Sync + Fn(A) -> R,
+        A: FunctionWithDefaultsArguments<'scope, F, R>,
+        R: Serialize,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let (cb_data, defaults) = unsafe { closure_data::<(F, A)>(&args) };
+
+        A::call(scope, args, rv, cb_data, defaults);
+    }
+
+    #[inline(always)]
+    fn v8_func_with_rate_limit<'borrow, 'scope, F, A, R>(
+        scope: &'borrow mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+    ) where
+        F: 'static + Send + Sync + Fn(A) -> R,
+        A: FunctionArguments<'scope, F, R>,
+        R: Serialize,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let (cb_data, rate_limiter) = unsafe { closure_data::<(F, Arc<RateLimiter>)>(&args) };
+
+        if !rate_limiter.try_call() {
+            let msg = value::new_string(
+                scope,
+                format!("Rate limit of '{}' exceeded", rate_limiter.name()),
+                NewStringType::Normal,
+            );
+            let exception = v8::Exception::range_error(scope, msg);
+            scope.throw_exception(exception);
+            return;
+        }
+
+        A::call(scope, args, rv, cb_data);
+    }
+
+    #[inline(always)]
+    fn v8_func_mut<'borrow, 'scope, F, A, R>(
+        scope: &'borrow mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+    ) where
+        F: 'static + FnMut(A) -> R,
+        A: FunctionMutArguments<'scope, F, R>,
+        R: Serialize,
+    {
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
+        };
+
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let cb_data = unsafe { closure_data::<RefCell<F>>(&args) };
+
+        let Ok(mut function) = cb_data.try_borrow_mut() else {
+            let msg = value::new_string(
+                scope,
+                "Mutable host function can't be called recursively",
+                NewStringType::Normal,
+            );
+            let exception = v8::Exception::error(scope, msg);
+            scope.throw_exception(exception);
+            return;
+        };
+
+        A::call(scope, args, rv, &mut function);
+    }
+
+    #[inline(always)]
+    fn v8_func_with_state<'borrow, 'scope, F, A, R>(
+        scope: &'borrow mut v8::HandleScope<'scope>,
+        args: v8::FunctionCallbackArguments<'scope>,
+        rv: v8::ReturnValue,
+    ) where
+        F: 'static + Send + Sync + Fn(&mut STATE, A) -> R,
         A: FunctionWithStateArguments<'scope, F, R, STATE>,
         R: Serialize,
     {
-        // SAFETY: This is safe since we made sure to leak the boxed callback (static lifetime)
-        //         and the implementation makes sure, that the data contains the pointer of the
-        //         expected closure callback for this function callback.
-        let cb_data = unsafe {
-            &*(v8::Local::<v8::External>::cast(args.data()).value() as *const c_void as *const F)
+        let Some(_guard) = HostCallGuard::enter_closure(scope, &args) else {
+            return;
         };
 
+        // SAFETY: The data of the function was stored by `Extension::store_closure()`.
+        let cb_data = unsafe { closure_data::<F>(&args) };
+
         // SAFETY: This is safe since we know that the state is stored in that slot
         //         and the data is bound to the lifetime of this runtime.
         let state = unsafe { &*(scope.get_data(STATE_DATA_SLOT) as *const RefCell<STATE>) };
@@ -389,24 +1172,180 @@ impl<STATE> Extension<STATE> {
     {
         use v8::MapFnTo;
 
-        let name = name.into();
+        let cb_data = self.store_closure(self.registration_name(name), function);
+        let function_callback = Self::v8_func::<F, A, R>.map_fn_to();
 
-        // We wrap the function in an Arc, so that it's lifetime can be tracked on runtimes and
-        // snapshots.
-        let closure = Arc::new(function);
+        self.declarations.insert(
+            name.into(),
+            FunctionDeclaration::Closure {
+                cb_data,
+                function_callback,
+            },
+        );
+    }
 
-        let cb_data = Arc::as_ptr(&closure) as *mut F as *mut c_void;
-        let function_callback = Self::v8_func::<F, A, R>.map_fn_to();
+    /// Add an async function to the extension with the given name as function name. The function
+    /// returns a future, which is exposed to the script as a promise.
+    ///
+    /// The promise is fulfilled with the value of the future, or rejected with an `Error` that
+    /// contains the message of the error of the future. The sources of the error are attached as
+    /// the `cause` chain of the rejected `Error`. The futures are driven by the event loop
+    /// of the runtime (see [`crate::Runtime::run_event_loop()`]), which doesn't provide an I/O
+    /// reactor. Futures that depend on the reactor of a specific async runtime need to be
+    /// driven by that runtime, for example by awaiting a channel that is fed by it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::convert::Infallible;
+    ///
+    /// use kopi::Extension;
+    ///
+    /// let mut extension = Extension::<()>::new(None);
+    /// extension.add_async_function("double", |(x,): (i32,)| async move {
+    ///     Ok::<_, Infallible>(x * 2)
+    /// });
+    /// ```
+    pub fn add_async_function<F, A, FU, R, E>(&mut self, name: &str, function: F)
+    where
+        F: 'static + Send + Sync + Fn(A) -> FU,
+        A: for<'s> FunctionAsyncArguments<'s, F, FU>,
+        FU: 'static + Future<Output = Result<R, E>>,
+        R: 'static + Serialize,
+        E: 'static + Into<Box<dyn Error>>,
+    {
+        use v8::MapFnTo;
+
+        let cb_data = self.store_closure(self.registration_name(name), function);
+        let function_callback = Self::v8_func_async::<F, A, FU>.map_fn_to();
 
         self.declarations.insert(
-            name,
+            name.into(),
+            FunctionDeclaration::Closure {
+                cb_data,
+                function_callback,
+            },
+        );
+    }
+
+    /// Add a function to the extension with the given name as function name. Arguments that are
+    /// not given by the caller (or are `undefined`) are replaced with the given default values.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::Extension;
+    ///
+    /// let mut extension = Extension::<()>::new(None);
+    /// extension.add_function_with_defaults(
+    ///     "repeat",
+    ///     ("-".to_string(), 3u32),
+    ///     move |(text, count): (String, u32)| text.repeat(count as usize),
+    /// );
+    /// ```
+    pub fn add_function_with_defaults<F, A, R>(&mut self, name: &str, defaults: A, function: F)
+    where
+        F: 'static + Send + Sync + Fn(A) -> R,
+        A: 'static + Send + Sync + for<'s> FunctionWithDefaultsArguments<'s, F, R>,
+        R: Serialize,
+    {
+        use v8::MapFnTo;
+
+        let cb_data = self.store_closure(self.registration_name(name), (function, defaults));
+        let function_callback = Self::v8_func_with_defaults::<F, A, R>.map_fn_to();
+
+        self.declarations.insert(
+            name.into(),
+            FunctionDeclaration::Closure {
+                cb_data,
+                function_callback,
+            },
+        );
+    }
+
+    /// Add a function to the extension with the given name as function name, that can only be
+    /// called as often as the given [`RateLimit`] allows.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::{Extension, RateLimit};
+    ///
+    /// let mut extension = Extension::<()>::new(None);
+    /// extension.add_function_with_rate_limit(
+    ///     "expensive",
+    ///     RateLimit {
+    ///         calls_per_execute: Some(10),
+    ///         ..Default::default()
+    ///     },
+    ///     move |(x,): (f64,)| x.sqrt(),
+    /// );
+    /// ```
+    pub fn add_function_with_rate_limit<F, A, R>(
+        &mut self,
+        name: &str,
+        rate_limit: RateLimit,
+        function: F,
+    ) where
+        F: 'static + Send + Sync + Fn(A) -> R,
+        A: for<'s> FunctionArguments<'s, F, R>,
+        R: Serialize,
+    {
+        use v8::MapFnTo;
+
+        let registration_name = self.registration_name(name);
+        let rate_limiter = Arc::new(RateLimiter::new(registration_name.clone(), rate_limit));
+
+        let cb_data = self.store_closure(registration_name, (function, rate_limiter.clone()));
+        let function_callback = Self::v8_func_with_rate_limit::<F, A, R>.map_fn_to();
+
+        self.declarations.insert(
+            name.into(),
             FunctionDeclaration::Closure {
                 cb_data,
                 function_callback,
             },
         );
 
-        self.closures.push(closure);
+        self.rate_limiters.push(rate_limiter);
+    }
+
+    /// Add a function to the extension with the given name as function name, that can mutate its
+    /// captured variables.
+    ///
+    /// Since a runtime is single threaded, the function doesn't need to be `Send` or `Sync`.
+    /// Calling the function recursively (host → script → same host function) throws an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::Extension;
+    ///
+    /// let mut extension = Extension::<()>::new(None);
+    /// let mut counter = 0;
+    /// extension.add_function_mut("next", move |()| {
+    ///     counter += 1;
+    ///     counter
+    /// });
+    /// ```
+    pub fn add_function_mut<F, A, R>(&mut self, name: &str, function: F)
+    where
+        F: 'static + FnMut(A) -> R,
+        A: for<'s> FunctionMutArguments<'s, F, R>,
+        R: Serialize,
+    {
+        use v8::MapFnTo;
+
+        let cb_data = self.store_closure(self.registration_name(name), RefCell::new(function));
+        let function_callback = Self::v8_func_mut::<F, A, R>.map_fn_to();
+
+        self.declarations.insert(
+            name.into(),
+            FunctionDeclaration::Closure {
+                cb_data,
+                function_callback,
+            },
+        );
     }
 
     /// Add a function to the extension with the given name as function name and the state of the
@@ -430,14 +1369,11 @@ impl<STATE> Extension<STATE> {
     {
         use v8::MapFnTo;
 
-        let name = name.into();
-
-        // We leak the callback to give it a static lifetime, so that V8 can call it safely.
-        let cb_data = Box::leak(Box::new(function)) as *mut F as *mut c_void;
+        let cb_data = self.store_closure(self.registration_name(name), function);
         let function_callback = Self::v8_func_with_state::<F, A, R>.map_fn_to();
 
         self.declarations.insert(
-            name,
+            name.into(),
             FunctionDeclaration::Closure {
                 cb_data,
                 function_callback,
@@ -507,4 +1443,211 @@ impl<STATE> Extension<STATE> {
             },
         );
     }
+
+    /// Add a property to the extension with the given name. The getter is called each time a
+    /// script reads the property and the setter each time a script assigns it. Assigning a
+    /// value, that can't be converted, throws a `TypeError`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::{
+    ///     atomic::{AtomicU32, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// use kopi::Extension;
+    ///
+    /// let speed = Arc::new(AtomicU32::new(1));
+    /// let setter_speed = speed.clone();
+    ///
+    /// let mut extension = Extension::<()>::new(Some("game"));
+    /// extension.add_property(
+    ///     "speed",
+    ///     move || speed.load(Ordering::Relaxed),
+    ///     move |value: u32| setter_speed.store(value, Ordering::Relaxed),
+    /// );
+    /// ```
+    pub fn add_property<G, R, S, V>(&mut self, name: &str, getter: G, setter: S)
+    where
+        G: 'static + Send + Sync + Fn() -> R,
+        R: 'static + Serialize,
+        S: 'static + Send + Sync + Fn(V),
+        V: DeserializeOwned,
+    {
+        use v8::MapFnTo;
+
+        let getter = self.store_closure(self.registration_name(name), getter);
+        let setter = self.store_closure(self.registration_name(name), setter);
+
+        self.declarations.insert(
+            name.into(),
+            FunctionDeclaration::Property {
+                getter: (getter, Self::v8_getter::<G, R>.map_fn_to()),
+                setter: Some((setter, Self::v8_setter::<S, V>.map_fn_to())),
+            },
+        );
+    }
+
+    /// Add a read-only property to the extension with the given name. The getter is called each
+    /// time a script reads the property.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Instant;
+    ///
+    /// use kopi::Extension;
+    ///
+    /// let start = Instant::now();
+    ///
+    /// let mut extension = Extension::<()>::new(Some("game"));
+    /// extension.add_getter("uptime", move || start.elapsed().as_secs_f64());
+    /// ```
+    pub fn add_getter<G, R>(&mut self, name: &str, getter: G)
+    where
+        G: 'static + Send + Sync + Fn() -> R,
+        R: 'static + Serialize,
+    {
+        use v8::MapFnTo;
+
+        let getter = self.store_closure(self.registration_name(name), getter);
+
+        self.declarations.insert(
+            name.into(),
+            FunctionDeclaration::Property {
+                getter: (getter, Self::v8_getter::<G, R>.map_fn_to()),
+                setter: None,
+            },
+        );
+    }
+
+    /// Add a native class to the extension. The name of the class is used as the name of its
+    /// constructor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::{Class, Extension};
+    ///
+    /// struct Point {
+    ///     x: f64,
+    ///     y: f64,
+    /// }
+    ///
+    /// let mut extension = Extension::<()>::new(Some("geometry"));
+    /// extension.add_class(
+    ///     Class::new("Point")
+    ///         .constructor(|(x, y): (f64, f64)| Point { x, y })
+    ///         .method("length", |point, ()| point.x.hypot(point.y)),
+    /// );
+    /// ```
+    pub fn add_class<T>(&mut self, class: Class<T>)
+    where
+        T: 'static,
+    {
+        let name = class.name.clone();
+        let cb_data = self.store_closure(self.registration_name(&name), class);
+
+        self.declarations.insert(
+            name,
+            FunctionDeclaration::Class {
+                cb_data,
+                template: class_template::<T>,
+            },
+        );
+    }
+}
+
+/// Builder to add functions to a nested namespace of an [`Extension`].
+///
+/// Created by [`Extension::namespace()`].
+pub struct Namespace<'extension, STATE> {
+    extension: &'extension mut Extension<STATE>,
+    path: String,
+}
+
+impl<'extension, STATE> Namespace<'extension, STATE> {
+    /// Returns the path of the namespace inside the extension.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns a builder for a namespace nested inside this namespace.
+    pub fn namespace(self, name: &str) -> Self {
+        let path = format!("{}.{}", self.path, name);
+        Self {
+            extension: self.extension,
+            path,
+        }
+    }
+
+    /// Add a function to the namespace. See [`Extension::add_function()`].
+    pub fn function<F, A, R>(self, name: &str, function: F) -> Self
+    where
+        F: 'static + Send + Sync + Fn(A) -> R,
+        A: for<'s> FunctionArguments<'s, F, R>,
+        R: Serialize,
+    {
+        let name = format!("{}.{}", self.path, name);
+        self.extension.add_function(&name, function);
+        self
+    }
+
+    /// Add a function with access to the state to the namespace.
+    /// See [`Extension::add_function_with_state()`].
+    pub fn function_with_state<F, A, R>(self, name: &str, function: F) -> Self
+    where
+        F: 'static + Send + Sync + Fn(&mut STATE, A) -> R,
+        A: for<'scope> FunctionWithStateArguments<'scope, F, R, STATE>,
+        R: Serialize,
+    {
+        let name = format!("{}.{}", self.path, name);
+        self.extension.add_function_with_state(&name, function);
+        self
+    }
+
+    /// Add a static function to the namespace. See [`Extension::add_static_function()`].
+    pub fn static_function<F>(self, name: &str, function: F) -> Self
+    where
+        F: 'static + StaticFunction,
+    {
+        let name = format!("{}.{}", self.path, name);
+        self.extension.add_static_function(&name, function);
+        self
+    }
+
+    /// Add a property to the namespace. See [`Extension::add_property()`].
+    pub fn property<G, R, S, V>(self, name: &str, getter: G, setter: S) -> Self
+    where
+        G: 'static + Send + Sync + Fn() -> R,
+        R: 'static + Serialize,
+        S: 'static + Send + Sync + Fn(V),
+        V: DeserializeOwned,
+    {
+        let name = format!("{}.{}", self.path, name);
+        self.extension.add_property(&name, getter, setter);
+        self
+    }
+
+    /// Add a read-only property to the namespace. See [`Extension::add_getter()`].
+    pub fn getter<G, R>(self, name: &str, getter: G) -> Self
+    where
+        G: 'static + Send + Sync + Fn() -> R,
+        R: 'static + Serialize,
+    {
+        let name = format!("{}.{}", self.path, name);
+        self.extension.add_getter(&name, getter);
+        self
+    }
+
+    /// Add a fastcall function to the namespace. See [`Extension::add_fastcall_function()`].
+    pub fn fastcall_function<F>(self, name: &str, function: F) -> Self
+    where
+        F: 'static + FastcallFunction,
+    {
+        let name = format!("{}.{}", self.path, name);
+        self.extension.add_fastcall_function(&name, function);
+        self
+    }
 }
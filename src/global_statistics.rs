@@ -0,0 +1,133 @@
+//! Implements the registry of all live runtimes, that is used to aggregate their statistics.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use crate::HeapStatistics;
+
+/// The heap statistics of all live runtimes, identified by the id of their registration.
+static REGISTRY: Mutex<Option<HashMap<u64, Snapshot>>> = Mutex::new(None);
+
+/// The id of the next registration.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The heap statistics of a runtime at a specific point in time.
+#[derive(Copy, Clone, Default)]
+struct Snapshot {
+    total_heap_size: usize,
+    used_heap_size: usize,
+    total_physical_size: usize,
+    external_memory: usize,
+    malloced_memory: usize,
+    number_of_native_contexts: usize,
+    number_of_detached_contexts: usize,
+}
+
+/// Aggregated statistics of all live runtimes of the process.
+///
+/// Isolates can only be accessed by the thread that owns them, so the heap statistics of a
+/// runtime are snapshots, that are taken when the runtime is created, after each execution of
+/// a script and when [`crate::Runtime::heap_statistics()`] is called.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GlobalStatistics {
+    /// Number of live runtimes.
+    pub runtimes: usize,
+    /// Number of bytes all engines have allocated for their heaps.
+    pub total_heap_size: usize,
+    /// Number of bytes that are used by all heaps.
+    pub used_heap_size: usize,
+    /// Number of bytes of committed memory of all heaps.
+    pub total_physical_size: usize,
+    /// Amount of external memory in bytes.
+    pub external_memory: usize,
+    /// Amount of allocated memory in bytes.
+    pub malloced_memory: usize,
+    /// Number of native contexts.
+    pub number_of_native_contexts: usize,
+    /// Number of detached contexts.
+    pub number_of_detached_contexts: usize,
+}
+
+/// Returns the aggregated statistics of all live runtimes of the process.
+pub fn global_statistics() -> GlobalStatistics {
+    let registry = lock_registry();
+
+    let Some(snapshots) = registry.as_ref() else {
+        return GlobalStatistics::default();
+    };
+
+    snapshots.values().fold(
+        GlobalStatistics {
+            runtimes: snapshots.len(),
+            ..Default::default()
+        },
+        |mut statistics, snapshot| {
+            statistics.total_heap_size += snapshot.total_heap_size;
+            statistics.used_heap_size += snapshot.used_heap_size;
+            statistics.total_physical_size += snapshot.total_physical_size;
+            statistics.external_memory += snapshot.external_memory;
+            statistics.malloced_memory += snapshot.malloced_memory;
+            statistics.number_of_native_contexts += snapshot.number_of_native_contexts;
+            statistics.number_of_detached_contexts += snapshot.number_of_detached_contexts;
+            statistics
+        },
+    )
+}
+
+fn lock_registry() -> std::sync::MutexGuard<'static, Option<HashMap<u64, Snapshot>>> {
+    match REGISTRY.lock() {
+        Ok(registry) => registry,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// The registration of a runtime inside the registry. Unregisters the runtime when dropped.
+pub(crate) struct Registration {
+    id: u64,
+}
+
+impl Registration {
+    /// Registers a new runtime.
+    pub(crate) fn new() -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        lock_registry()
+            .get_or_insert_with(HashMap::new)
+            .insert(id, Snapshot::default());
+        Self { id }
+    }
+
+    /// Returns the id of the registration, that is unique for the lifetime of the process.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Updates the heap statistics of the registered runtime.
+    pub(crate) fn update(&self, statistics: &HeapStatistics) {
+        let snapshot = Snapshot {
+            total_heap_size: statistics.total_heap_size(),
+            used_heap_size: statistics.used_heap_size(),
+            total_physical_size: statistics.total_physical_size(),
+            external_memory: statistics.external_memory(),
+            malloced_memory: statistics.malloced_memory(),
+            number_of_native_contexts: statistics.number_of_native_contexts(),
+            number_of_detached_contexts: statistics.number_of_detached_contexts(),
+        };
+
+        if let Some(snapshots) = lock_registry().as_mut() {
+            snapshots.insert(self.id, snapshot);
+        }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Some(snapshots) = lock_registry().as_mut() {
+            snapshots.remove(&self.id);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Implements the snapshots of the global object, that are used to reset the global state.
+
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroI32,
+};
+
+use crate::value::{new_string, NewStringType, Seal, Unseal};
+
+/// The captured value of a property.
+enum PropertyValue {
+    /// A data property. `cloned` is `true` if the value is a structured clone, that needs to be
+    /// cloned again on restore.
+    Data {
+        value: v8::Global<v8::Value>,
+        cloned: bool,
+    },
+    /// An accessor property.
+    Accessor {
+        getter: Option<v8::Global<v8::Function>>,
+        setter: Option<v8::Global<v8::Function>>,
+    },
+}
+
+/// A captured own property of an object.
+struct Property {
+    key: v8::Global<v8::Name>,
+    value: PropertyValue,
+    attributes: v8::PropertyAttribute,
+}
+
+/// The captured own properties and the prototype of an object.
+struct ObjectState {
+    object: v8::Global<v8::Object>,
+    prototype: v8::Global<v8::Value>,
+    properties: Vec<Property>,
+    /// The names of the properties with string keys.
+    names: HashSet<String>,
+}
+
+/// The state of the global object, captured with [`crate::Runtime::capture_globals()`].
+///
+/// All own properties of the global object, including the non-enumerable ones and the ones with
+/// symbol keys, are captured together with their attributes. The objects that are reachable
+/// through the properties, like the built-in objects and the namespace objects of extensions,
+/// are captured the same way together with their prototype, so that replaced functions
+/// (e.g. `Math.random`) and modified data are reset.
+///
+/// Maps, Sets, Dates, RegExps, array buffers and their views store their content outside of
+/// their properties and are captured with the structured clone algorithm instead. Their content
+/// is not captured if they can't be cloned (e.g. a `Map` containing functions). Lexical
+/// declarations (`let`, `const` and `class`) are not properties of the global object and are
+/// not captured.
+pub struct GlobalsSnapshot {
+    /// The state of the global object comes first.
+    objects: Vec<ObjectState>,
+}
+
+impl GlobalsSnapshot {
+    /// Returns the number of captured objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns `true` if no objects were captured.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+/// Returns the keys of all own properties of the object, including the non-enumerable ones and
+/// the ones with symbol keys.
+fn own_keys<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    object: v8::Local<v8::Object>,
+) -> Option<Vec<v8::Local<'scope, v8::Name>>> {
+    let keys = object.get_own_property_names(
+        scope,
+        v8::GetPropertyNamesArgs {
+            property_filter: v8::PropertyFilter::ALL_PROPERTIES,
+            key_conversion: v8::KeyConversionMode::ConvertToString,
+            ..Default::default()
+        },
+    )?;
+
+    let mut names = Vec::with_capacity(keys.length() as usize);
+    for index in 0..keys.length() {
+        let key = keys.get_index(scope, index)?;
+        names.push(v8::Local::<v8::Name>::try_from(key).ok()?);
+    }
+
+    Some(names)
+}
+
+/// Returns the object, whose properties are captured, if the value is an object. The target of
+/// a proxy is captured instead of the proxy, so that no traps are triggered.
+fn captured_object<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    mut value: v8::Local<'scope, v8::Value>,
+) -> Option<v8::Local<'scope, v8::Object>> {
+    while let Ok(proxy) = v8::Local::<v8::Proxy>::try_from(value) {
+        value = proxy.get_target(scope);
+    }
+    v8::Local::<v8::Object>::try_from(value).ok()
+}
+
+/// Returns `true` if the content of the value is stored outside of its properties, so that it
+/// needs to be captured with the structured clone algorithm.
+fn has_internal_content(value: v8::Local<v8::Value>) -> bool {
+    value.is_map()
+        || value.is_set()
+        || value.is_date()
+        || value.is_reg_exp()
+        || value.is_array_buffer()
+        || value.is_array_buffer_view()
+}
+
+/// Returns the boolean field of a property descriptor.
+fn descriptor_flag(
+    scope: &mut v8::HandleScope,
+    descriptor: v8::Local<v8::Object>,
+    field: &str,
+) -> Option<bool> {
+    let key = new_string(scope, field, NewStringType::Internalized);
+    let value = descriptor.get(scope, key.into())?;
+    Some(value.boolean_value(scope))
+}
+
+/// Captures the own properties of the object. Objects that are referenced by the properties and
+/// need to be captured as well are added to the pending objects.
+fn capture_properties<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    object: v8::Local<'scope, v8::Object>,
+    pending: &mut Vec<v8::Local<'scope, v8::Object>>,
+) -> Option<(Vec<Property>, HashSet<String>)> {
+    let mut properties = Vec::new();
+    let mut names = HashSet::new();
+
+    for key in own_keys(scope, object)? {
+        let descriptor = object.get_own_property_descriptor(scope, key)?;
+        let Ok(descriptor) = v8::Local::<v8::Object>::try_from(descriptor) else {
+            continue;
+        };
+
+        // The descriptor must not inherit fields from a modified `Object.prototype`.
+        let null = v8::null(scope);
+        descriptor.set_prototype(scope, null.into())?;
+
+        let mut attributes = v8::NONE;
+        if !descriptor_flag(scope, descriptor, "enumerable")? {
+            attributes = attributes + v8::DONT_ENUM;
+        }
+        if !descriptor_flag(scope, descriptor, "configurable")? {
+            attributes = attributes + v8::DONT_DELETE;
+        }
+
+        let value_key = new_string(scope, "value", NewStringType::Internalized);
+        let value = if descriptor.has_own_property(scope, value_key.into())? {
+            if !descriptor_flag(scope, descriptor, "writable")? {
+                attributes = attributes + v8::READ_ONLY;
+            }
+
+            let value = descriptor.get(scope, value_key.into())?;
+            let clone = match has_internal_content(value) {
+                true => value.seal().deep_clone(scope.seal()).ok(),
+                false => None,
+            };
+            let (value, cloned) = match clone {
+                Some(clone) => (clone.unseal(), true),
+                None => {
+                    pending.extend(captured_object(scope, value));
+                    (value, false)
+                }
+            };
+
+            PropertyValue::Data {
+                value: v8::Global::new(scope, value),
+                cloned,
+            }
+        } else {
+            let mut accessor = |name: &str| -> Option<Option<v8::Global<v8::Function>>> {
+                let key = new_string(scope, name, NewStringType::Internalized);
+                let function = descriptor.get(scope, key.into())?;
+                let function = v8::Local::<v8::Function>::try_from(function).ok();
+                pending.extend(function.map(v8::Local::<v8::Object>::from));
+                Some(function.map(|function| v8::Global::new(scope, function)))
+            };
+
+            PropertyValue::Accessor {
+                getter: accessor("get")?,
+                setter: accessor("set")?,
+            }
+        };
+
+        if key.is_string() {
+            names.insert(key.to_rust_string_lossy(scope));
+        }
+
+        properties.push(Property {
+            key: v8::Global::new(scope, key),
+            value,
+            attributes,
+        });
+    }
+
+    Some((properties, names))
+}
+
+/// Captures the own properties of the global object and of the objects, that are reachable from
+/// it.
+pub(crate) fn capture<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    global: v8::Local<'scope, v8::Object>,
+) -> Option<GlobalsSnapshot> {
+    let mut objects = Vec::new();
+    let mut visited: HashMap<NonZeroI32, Vec<v8::Local<v8::Object>>> = HashMap::new();
+    let mut pending = vec![global];
+
+    while let Some(object) = pending.pop() {
+        // Objects can reference each other in cycles.
+        let candidates = visited.entry(object.get_identity_hash()).or_default();
+        if candidates.iter().any(|o| o.strict_equals(object.into())) {
+            continue;
+        }
+        candidates.push(object);
+
+        let prototype = object.get_prototype(scope)?;
+        pending.extend(captured_object(scope, prototype));
+
+        let (properties, names) = capture_properties(scope, object, &mut pending)?;
+
+        objects.push(ObjectState {
+            object: v8::Global::new(scope, object),
+            prototype: v8::Global::new(scope, prototype),
+            properties,
+            names,
+        });
+    }
+
+    Some(GlobalsSnapshot { objects })
+}
+
+/// Restores the own properties and the prototypes of the captured objects. Properties that were
+/// added after the snapshot was captured are removed, or set to `undefined` if they can't be
+/// removed.
+pub(crate) fn restore(scope: &mut v8::HandleScope, snapshot: &GlobalsSnapshot) -> Option<()> {
+    for state in snapshot.objects.iter() {
+        let object = v8::Local::new(scope, &state.object);
+
+        // Objects with an immutable prototype can't be changed and keep their prototype.
+        let prototype = v8::Local::new(scope, &state.prototype);
+        if !object.get_prototype(scope)?.strict_equals(prototype) {
+            object.set_prototype(scope, prototype)?;
+        }
+
+        for key in own_keys(scope, object)? {
+            let captured = if key.is_string() {
+                state.names.contains(&key.to_rust_string_lossy(scope))
+            } else {
+                state
+                    .properties
+                    .iter()
+                    .any(|p| v8::Local::new(scope, &p.key).strict_equals(key.into()))
+            };
+            if captured {
+                continue;
+            }
+
+            if object.delete(scope, key.into()) != Some(true) {
+                let undefined = v8::undefined(scope);
+                object.set(scope, key.into(), undefined.into())?;
+            }
+        }
+
+        // Non-configurable properties, that were changed, can't be redefined and are skipped.
+        for property in state.properties.iter() {
+            let key = v8::Local::new(scope, &property.key);
+            match &property.value {
+                PropertyValue::Data { value, cloned } => {
+                    let mut value = v8::Local::new(scope, value);
+
+                    // The snapshot must stay untouched, so that it can be restored multiple times.
+                    if *cloned {
+                        value = value.seal().deep_clone(scope.seal()).ok()?.unseal();
+                    }
+
+                    object.define_own_property(scope, key, value, property.attributes)?;
+                }
+                PropertyValue::Accessor { getter, setter } => {
+                    let getter = getter.as_ref().map(|getter| v8::Local::new(scope, getter));
+                    let setter = setter.as_ref().map(|setter| v8::Local::new(scope, setter));
+                    object.set_accessor_property(key, getter, setter, property.attributes);
+                }
+            }
+        }
+    }
+
+    Some(())
+}
@@ -0,0 +1,133 @@
+//! Implements the host extension.
+
+use crate::{runtime::RuntimeData, Extension, StaticFunction};
+
+/// Namespace of the host extension.
+const NAMESPACE: &str = "host";
+
+/// Provides primitives to scripts, that hand the control back to the host.
+///
+/// | Function       | Description                                                          |
+/// |----------------|----------------------------------------------------------------------|
+/// | `host.yield()` | Returns a promise, that is resolved once the host resumes the script. |
+///
+/// The engine can't suspend a running script, so scripts yield by awaiting `host.yield()`
+/// inside an async function. The execution returns to the host, which resumes the yielded
+/// scripts with [`crate::Runtime::resume()`]. This enables coroutine-style scripts, for example
+/// NPCs that act over multiple frames, without async host functions.
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::{initialize_with_defaults, HostExtension, Runtime, RuntimeOptions};
+///
+/// initialize_with_defaults();
+///
+/// let mut runtime = Runtime::new(
+///     RuntimeOptions {
+///         extensions: vec![HostExtension::create()],
+///         ..Default::default()
+///     },
+///     (),
+/// )
+/// .expect("Can't create runtime");
+///
+/// runtime
+///     .execute::<(), _>(
+///         "var steps = 0; \
+///          (async () => { while (steps < 3) { steps++; await host.yield(); } })();",
+///     )
+///     .expect("Can't execute code");
+///
+/// while runtime.is_suspended() {
+///     runtime.resume();
+/// }
+///
+/// let steps: i32 = runtime.execute("steps").expect("Can't execute code");
+/// assert_eq!(steps, 3);
+/// ```
+pub struct HostExtension;
+
+impl HostExtension {
+    /// Creates the host extension.
+    pub fn create<STATE>() -> Extension<STATE> {
+        let mut extension = Extension::new(Some(NAMESPACE));
+        extension.add_static_function("yield", HostYield);
+        extension
+    }
+}
+
+/// The `host.yield()` function.
+struct HostYield;
+
+impl StaticFunction for HostYield {
+    fn callback() -> v8::FunctionCallback {
+        use v8::MapFnTo;
+        host_yield.map_fn_to()
+    }
+}
+
+/// Returns a promise, that is resolved once the host resumes the yielded scripts.
+fn host_yield(
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+) {
+    let Some(resolver) = v8::PromiseResolver::new(scope) else {
+        return;
+    };
+
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    runtime_data
+        .yielded
+        .borrow_mut()
+        .push(v8::Global::new(scope, resolver));
+
+    rv.set(resolver.get_promise(scope).into());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{initialize_with_defaults, HostExtension, Runtime, RuntimeOptions};
+
+    #[test]
+    fn yield_and_resume() {
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![HostExtension::create()],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        assert!(!runtime.is_suspended());
+        assert_eq!(runtime.resume(), 0);
+
+        runtime
+            .execute::<(), _>(
+                "var log = []; \
+                 async function npc(name) { \
+                     for (let i = 0; i < 2; i++) { log.push(name + i); await host.yield(); } \
+                 } \
+                 npc('a'); npc('b');",
+            )
+            .expect("Can't execute code");
+
+        assert!(runtime.is_suspended());
+        let val: String = runtime.execute("log.join()").expect("Can't execute code");
+        assert_eq!(val, "a0,b0");
+
+        assert_eq!(runtime.resume(), 2);
+        let val: String = runtime.execute("log.join()").expect("Can't execute code");
+        assert_eq!(val, "a0,b0,a1,b1");
+
+        assert_eq!(runtime.resume(), 2);
+        assert!(!runtime.is_suspended());
+    }
+}
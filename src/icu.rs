@@ -0,0 +1,195 @@
+//! Implements the loading of the ICU data, that is used for internationalization, and the
+//! hiding of `Intl` when the ICU data is never loaded.
+
+// The ICU data is never loaded with the `hide-intl` feature.
+#![cfg_attr(feature = "hide-intl", allow(dead_code))]
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "hide-intl")]
+use crate::value::{new_string, NewStringType};
+use crate::{prepare_icu_data, Aligned16};
+
+#[cfg(target_endian = "little")]
+const ICU_FILE_NAME: &str = "icudt71l.dat";
+
+#[cfg(target_endian = "big")]
+const ICU_FILE_NAME: &str = "icudt71b.dat";
+
+/// The environment variable that can point to the ICU file or to the folder containing it.
+const ICU_PATH_VARIABLE: &str = "KOPI_ICU_PATH";
+
+/// The source of the ICU data (icudt71*.dat) used for internationalization.
+#[derive(Clone)]
+pub enum IcuSource {
+    /// ICU data that is embedded into the application. Use [`prepare_icu_data`] to properly
+    /// align the data.
+    Embedded(&'static [Aligned16]),
+    /// The ICU file is searched in the given paths, which can point to the file or to the folder
+    /// containing it. Afterwards the path of the `KOPI_ICU_PATH` environment variable, the folder
+    /// of the executable and the working folder are searched.
+    File(Vec<PathBuf>),
+    /// No ICU data is loaded.
+    None,
+}
+
+impl Default for IcuSource {
+    fn default() -> Self {
+        Self::File(Vec::new())
+    }
+}
+
+/// Returns the path of the ICU file, if the path points to a folder.
+fn icu_file_path(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join(ICU_FILE_NAME)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Returns the paths in which the ICU file is searched, in the order of their priority.
+fn search_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut search_paths: Vec<PathBuf> = paths.iter().map(|path| icu_file_path(path)).collect();
+
+    if let Some(path) = std::env::var_os(ICU_PATH_VARIABLE) {
+        search_paths.push(icu_file_path(Path::new(&path)));
+    }
+
+    if let Some(folder) = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    {
+        search_paths.push(folder.join(ICU_FILE_NAME));
+    }
+
+    search_paths.push(PathBuf::from(ICU_FILE_NAME));
+    search_paths
+}
+
+/// Loads the ICU data of the source.
+pub(crate) fn load_icu(source: &IcuSource) {
+    let icu_data = match source {
+        IcuSource::Embedded(icu_data) => Some(*icu_data),
+        IcuSource::File(paths) => search_paths(paths).into_iter().find_map(|path| {
+            let icu_data = std::fs::read(&path).ok()?;
+            log::debug!("Loading the ICU data from '{}'", path.display());
+            Some(prepare_icu_data(icu_data).expect("Invalid ICU data"))
+        }),
+        IcuSource::None => None,
+    };
+
+    let Some(icu_data) = icu_data else {
+        log::debug!("No ICU data was loaded");
+        return;
+    };
+
+    assert_eq!(
+        icu_data.as_ptr() as usize % 16,
+        0,
+        "ICU data is not aligned to 16 bytes"
+    );
+
+    // SAFETY: We know that `Aligned16` is a multiple of byte alignment.
+    let byte_data = unsafe {
+        std::slice::from_raw_parts(
+            icu_data.as_ptr() as *const u8,
+            std::mem::size_of_val(icu_data),
+        )
+    };
+
+    if let Err(err_code) = v8::icu::set_common_data_71(byte_data) {
+        panic!("ICU could not be initialized: {}", err_code)
+    }
+}
+
+/// The locale-sensitive methods of the built-in prototypes, that need the ICU data.
+#[cfg(feature = "hide-intl")]
+const LOCALE_METHODS: &[(&str, &[&str])] = &[
+    (
+        "String",
+        &[
+            "localeCompare",
+            "normalize",
+            "toLocaleLowerCase",
+            "toLocaleUpperCase",
+        ],
+    ),
+    ("Number", &["toLocaleString"]),
+    ("BigInt", &["toLocaleString"]),
+    (
+        "Date",
+        &["toLocaleString", "toLocaleDateString", "toLocaleTimeString"],
+    ),
+];
+
+/// Removes the `Intl` object of the context, so that scripts can detect its absence, and
+/// replaces the locale-sensitive methods with methods that throw a `TypeError`, since they
+/// can't work without the ICU data.
+#[cfg(feature = "hide-intl")]
+pub(crate) fn hide_intl(scope: &mut v8::HandleScope, global: v8::Local<v8::Object>) {
+    let name = new_string(scope, "Intl", NewStringType::Internalized);
+    global.delete(scope, name.into());
+
+    let Some(unavailable) = v8::Function::new(scope, locale_method_unavailable) else {
+        return;
+    };
+
+    for (constructor, methods) in LOCALE_METHODS {
+        let Some(prototype) = get_object(scope, global, constructor)
+            .and_then(|constructor| get_object(scope, constructor, "prototype"))
+        else {
+            continue;
+        };
+
+        for method in methods.iter() {
+            let name = new_string(scope, method, NewStringType::Internalized);
+            prototype.set(scope, name.into(), unavailable.into());
+        }
+    }
+}
+
+/// Returns the property of the object, if it is an object.
+#[cfg(feature = "hide-intl")]
+fn get_object<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    object: v8::Local<'scope, v8::Object>,
+    name: &str,
+) -> Option<v8::Local<'scope, v8::Object>> {
+    let name = new_string(scope, name, NewStringType::Internalized);
+    object
+        .get(scope, name.into())
+        .and_then(|value| v8::Local::<v8::Object>::try_from(value).ok())
+}
+
+/// The callback of the locale-sensitive methods, when the `hide-intl` feature is enabled.
+#[cfg(feature = "hide-intl")]
+fn locale_method_unavailable(
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    _rv: v8::ReturnValue,
+) {
+    let msg = new_string(
+        scope,
+        "Locale-sensitive methods are not available without Intl",
+        NewStringType::Normal,
+    );
+    let error = v8::Exception::type_error(scope, msg);
+    scope.throw_exception(error);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn icu_search_paths() {
+        let folder = std::env::temp_dir();
+        let file = PathBuf::from("data/icu.dat");
+
+        let paths = search_paths(&[folder.clone(), file.clone()]);
+        assert_eq!(paths[0], folder.join(ICU_FILE_NAME));
+        assert_eq!(paths[1], file);
+        assert_eq!(paths.last(), Some(&PathBuf::from(ICU_FILE_NAME)));
+    }
+}
@@ -0,0 +1,64 @@
+//! Implements the global properties, that are computed on their first access.
+
+use crate::{
+    error::TypeError,
+    extension::new_type_error,
+    runtime::RuntimeData,
+    traits::Serialize,
+    value::{Seal, Unseal, Value, ValueScope},
+};
+
+type ComputeFn =
+    Box<dyn for<'scope> Fn(&mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError>>;
+
+/// A global property, that is computed on the first access inside a context.
+///
+/// Created with [`crate::RuntimeOptions::lazy_global()`].
+pub struct LazyGlobal {
+    pub(crate) name: String,
+    pub(crate) compute: ComputeFn,
+}
+
+impl LazyGlobal {
+    /// Creates a new global property with the given name. The value is computed by the given
+    /// function.
+    pub(crate) fn new<T>(name: &str, compute: fn() -> T) -> Self
+    where
+        T: Serialize,
+    {
+        Self {
+            name: name.to_string(),
+            compute: Box::new(move |scope| compute().serialize(scope)),
+        }
+    }
+}
+
+/// Is called by the engine on the first access of a lazy global. The computed value replaces the
+/// accessor, so that the value is only computed once per context.
+pub(crate) fn lazy_global_getter<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    key: v8::Local<'scope, v8::Name>,
+    args: v8::PropertyCallbackArguments<'scope>,
+    mut rv: v8::ReturnValue,
+) {
+    let Some(data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    let name = key.to_rust_string_lossy(scope);
+    let Some(lazy_global) = data.lazy_globals.iter().find(|global| global.name == name) else {
+        return;
+    };
+
+    let value = match (lazy_global.compute)(scope.seal()) {
+        Ok(value) => value.unseal(),
+        Err(err) => {
+            let error = new_type_error(scope.seal(), err);
+            scope.throw_exception(error.unseal());
+            return;
+        }
+    };
+
+    args.this().create_data_property(scope, key, value);
+    rv.set(value);
+}
@@ -57,49 +57,117 @@ pub mod _macros {
     };
 
     pub use crate::{
-        extension::{get_argument, set_result},
+        extension::{get_argument, set_result, HostCallGuard},
         runtime::STATE_DATA_SLOT,
+        traits::FromFastcallValue,
     };
 }
 
+mod async_tasks;
+mod binding_interceptor;
+mod breadcrumbs;
+mod bundle;
+mod class;
+mod code_verifier;
+mod console;
+mod diagnostic;
 pub mod error;
+mod event_loop;
+mod execution_timing;
 mod extension;
+mod global_statistics;
+mod globals_snapshot;
 mod heap_statistics;
+mod host_extension;
+mod icu;
+mod lazy_global;
+mod memory_extension;
+mod microtasks;
+mod module_loader;
+mod performance_extension;
+pub mod prelude;
+mod rate_limit;
+mod realm;
 mod runtime;
+mod runtime_handle;
+#[cfg(feature = "metrics")]
+mod runtime_metrics;
+mod script_cache;
+mod script_origin;
 mod serialization;
+mod sliced_execution;
+mod stack_sampler;
+mod std_extension;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+mod timers;
 mod traits;
 pub mod value;
+mod wasm;
 
 #[cfg(target_pointer_width = "16")]
 compile_error!("16 bit systems are not supported");
 
 use std::{
+    collections::hash_map::DefaultHasher,
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
     num::NonZeroU32,
+    path::PathBuf,
+    sync::Mutex,
 };
 
 pub use self::{
+    binding_interceptor::BindingOperation,
+    bundle::Bundle,
+    class::Class,
+    code_verifier::{ScriptKind, ScriptSource, VerifyError},
+    console::{ConsoleLevel, ConsoleSink, LogConsoleSink, StdoutConsoleSink},
+    diagnostic::Diagnostic,
+    event_loop::ScriptEventLoop,
+    execution_timing::ExecutionTiming,
     extension::{
-        Extension, FastcallFunction, FunctionArguments, FunctionWithStateArguments, StaticFunction,
+        Extension, ExtensionFootprint, FastcallFunction, FunctionArguments, FunctionAsyncArguments,
+        FunctionMutArguments, FunctionWithDefaultsArguments, FunctionWithStateArguments, Namespace,
+        StaticFunction,
     },
+    global_statistics::{global_statistics, GlobalStatistics},
+    globals_snapshot::GlobalsSnapshot,
     heap_statistics::HeapStatistics,
-    runtime::{Runtime, RuntimeOptions},
+    host_extension::HostExtension,
+    icu::IcuSource,
+    lazy_global::LazyGlobal,
+    memory_extension::MemoryExtension,
+    microtasks::MicrotasksPolicy,
+    module_loader::ModuleLoader,
+    performance_extension::PerformanceExtension,
+    rate_limit::RateLimit,
+    realm::Realm,
+    runtime::{
+        FunctionHandle, NamespaceHandle, PauseGuard, Runtime, RuntimeCounters, RuntimeOptions,
+    },
+    runtime_handle::RuntimeHandle,
+    script_cache::ScriptCacheStatistics,
+    script_origin::ScriptOrigin,
     serialization::*,
-    traits::{Deserialize, FastcallArgument, FastcallReturnValue, Serialize},
+    sliced_execution::SlicedExecution,
+    stack_sampler::{SlowScriptReport, StackSample, StackSampling},
+    std_extension::StdExtension,
+    timers::TimerExtension,
+    traits::{Deserialize, DeserializeOwned, FastcallArgument, FastcallReturnValue, Serialize},
+    wasm::{WasmInstance, WasmValue},
 };
 
-const DEFAULT_V8_FLAGS: &str = "--turbo_fast_api_calls";
-
-#[cfg(target_endian = "little")]
-const ICU_FILE_NAME: &str = "icudt71l.dat";
-
-#[cfg(target_endian = "big")]
-const ICU_FILE_NAME: &'static str = "icudt71b.dat";
+const DEFAULT_V8_FLAGS: &str = "--turbo_fast_api_calls --harmony-import-assertions";
 
 static V8_INITIALIZATION: std::sync::Once = std::sync::Once::new();
 
+/// The configuration the V8 engine was initialized with.
+static INITIALIZATION_CONFIG: Mutex<Option<InitializationConfig>> = Mutex::new(None);
+
 /// Represents the version number of the V8 engine.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct Version {
     /// The major milestone version (based on the chromium release).
     pub milestone_major: u32,
@@ -156,34 +224,172 @@ pub fn version_v8() -> Version {
         .expect("V8 version string is not of the expected format")
 }
 
+/// Describes how the V8 engine was build and for which platform.
+#[derive(Copy, Clone, Debug)]
+pub struct BuildInfo {
+    /// The version of the V8 engine.
+    pub v8_version: Version,
+    /// The target architecture (e.g. `x86_64` or `aarch64`).
+    pub target_arch: &'static str,
+    /// The target operating system (e.g. `linux` or `windows`).
+    pub target_os: &'static str,
+}
+
+/// Returns information about the build of the V8 engine and the target platform.
+///
+/// The V8 crate doesn't expose the flags it was build with (like pointer compression or the
+/// sandbox), so only the information that can be queried is reported.
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        v8_version: version_v8(),
+        target_arch: std::env::consts::ARCH,
+        target_os: std::env::consts::OS,
+    }
+}
+
 /// Configures the initialization of the V8 engine.
 pub struct InitializationOptions {
     /// Configures if the V8 engine should run single threaded or multi threaded mode.
     pub execution_model: ExecutionModel,
-    /// Optional ICU data used for internationalization (icudt71*.dat).
-    /// Use [`prepare_icu_data`] to properly align the data.
+    /// The source of the ICU data used for internationalization.
+    ///
+    /// By default the ICU file is searched in the path of the `KOPI_ICU_PATH` environment
+    /// variable, the folder of the executable and the working folder.
     ///
-    /// If no data is given, we try to load the file from the work folder.
-    pub icu_data: Option<&'static [Aligned16]>,
+    /// Is ignored with the `hide-intl` feature.
+    pub icu_source: IcuSource,
     /// The default locale used for internationalization.
     ///
-    /// Must be a valid locale based on ECMA402.
+    /// Must be a valid locale based on ECMA402. Is ignored with the `hide-intl` feature.
     pub default_locale: String,
+    /// Configures how strings are deserialized into Rust strings.
+    pub string_policy: StringPolicy,
+    /// Configures how NaN, ±Infinity and -0 are handled by the numeric conversions.
+    pub number_policy: NumberPolicy,
+}
+
+impl InitializationOptions {
+    /// Returns a builder, that starts with the default options.
+    pub fn builder() -> InitializationOptionsBuilder {
+        InitializationOptionsBuilder(Self::default())
+    }
 }
 
 impl Default for InitializationOptions {
     fn default() -> Self {
         Self {
             execution_model: ExecutionModel::MultiThreaded(None),
-            icu_data: None,
+            icu_source: IcuSource::default(),
             default_locale: "en-US".to_string(),
+            string_policy: StringPolicy::default(),
+            number_policy: NumberPolicy::default(),
         }
     }
 }
 
+/// Builder for the [`InitializationOptions`].
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::{ExecutionModel, InitializationOptions};
+///
+/// let options = InitializationOptions::builder()
+///     .execution_model(ExecutionModel::SingleThreaded)
+///     .icu_search_path("assets/icu")
+///     .default_locale("de-DE")
+///     .build();
+/// ```
+pub struct InitializationOptionsBuilder(InitializationOptions);
+
+impl InitializationOptionsBuilder {
+    /// Sets the execution model of the engine.
+    pub fn execution_model(mut self, execution_model: ExecutionModel) -> Self {
+        self.0.execution_model = execution_model;
+        self
+    }
+
+    /// Sets the source of the ICU data.
+    pub fn icu_source(mut self, icu_source: IcuSource) -> Self {
+        self.0.icu_source = icu_source;
+        self
+    }
+
+    /// Adds a path, in which the ICU file is searched. The path can point to the file or to the
+    /// folder containing it. Paths are searched in the order they were added.
+    ///
+    /// Replaces embedded ICU data or a disabled ICU source.
+    pub fn icu_search_path<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        match &mut self.0.icu_source {
+            IcuSource::File(paths) => paths.push(path.into()),
+            icu_source => *icu_source = IcuSource::File(vec![path.into()]),
+        }
+        self
+    }
+
+    /// Sets the default locale used for internationalization.
+    pub fn default_locale<S>(mut self, default_locale: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.0.default_locale = default_locale.into();
+        self
+    }
+
+    /// Sets the string policy.
+    pub fn string_policy(mut self, string_policy: StringPolicy) -> Self {
+        self.0.string_policy = string_policy;
+        self
+    }
+
+    /// Sets the number policy.
+    pub fn number_policy(mut self, number_policy: NumberPolicy) -> Self {
+        self.0.number_policy = number_policy;
+        self
+    }
+
+    /// Returns the configured options.
+    pub fn build(self) -> InitializationOptions {
+        self.0
+    }
+}
+
+/// Configures how strings are deserialized into Rust strings.
+///
+/// ECMAScript strings are UTF-16 and can contain unpaired surrogates, which can't be represented
+/// in UTF-8. Use [`StrictString`] to deserialize a string strictly regardless of the policy and
+/// [`WString`] to access the raw UTF-16 data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StringPolicy {
+    /// Unpaired surrogates are replaced with the replacement character (U+FFFD).
+    #[default]
+    Lossy,
+    /// Unpaired surrogates result in a type error.
+    Strict,
+}
+
+/// Configures how NaN, ±Infinity and -0 are handled when deserializing into integer types and
+/// when serializing floats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum NumberPolicy {
+    /// Floats are serialized unchanged. Integers can't be created from these numbers and result
+    /// in a type error.
+    #[default]
+    PassThrough,
+    /// Serializing floats and deserializing integers results in a type error.
+    Error,
+    /// NaN and -0 are converted to `0`. ±Infinity is converted to the smallest or largest value
+    /// of the type.
+    Clamp,
+}
+
 /// Configures if the V8 engine should run in single threaded mode or with a thread pool for
 /// background tasks. Background tasks include internal tasks like the GC and code compilation.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ExecutionModel {
     /// The engine will be single threaded.
     SingleThreaded,
@@ -194,6 +400,63 @@ pub enum ExecutionModel {
     MultiThreaded(Option<NonZeroU32>),
 }
 
+/// The configuration the V8 engine was initialized with.
+///
+/// Libraries that embed kopi can compare it with the configuration they expect, since only the
+/// first initialization of a process configures the engine.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InitializationConfig {
+    /// The execution model of the engine.
+    pub execution_model: ExecutionModel,
+    /// `true` if ICU data was embedded with the [`InitializationOptions`].
+    pub icu_data: bool,
+    /// The default locale used for internationalization.
+    pub default_locale: String,
+    /// The string policy.
+    pub string_policy: StringPolicy,
+    /// The number policy.
+    pub number_policy: NumberPolicy,
+}
+
+impl InitializationConfig {
+    fn new(options: &InitializationOptions) -> Self {
+        Self {
+            execution_model: options.execution_model,
+            icu_data: !cfg!(feature = "hide-intl")
+                && matches!(options.icu_source, IcuSource::Embedded(_)),
+            default_locale: options.default_locale.clone(),
+            string_policy: options.string_policy,
+            number_policy: options.number_policy,
+        }
+    }
+
+    /// Returns the hash of the configuration, that can be used to cheaply compare
+    /// configurations. The hash is only stable inside the same build.
+    pub fn config_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Returns `true` if the V8 engine was initialized.
+pub fn is_initialized() -> bool {
+    V8_INITIALIZATION.is_completed()
+}
+
+/// Returns the configuration the V8 engine was initialized with, or `None` if the engine was
+/// not initialized yet.
+pub fn initialization_config() -> Option<InitializationConfig> {
+    lock_initialization_config().clone()
+}
+
+fn lock_initialization_config() -> std::sync::MutexGuard<'static, Option<InitializationConfig>> {
+    match INITIALIZATION_CONFIG.lock() {
+        Ok(config) => config,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
 /// Initialized the V8 engine with the default configuration.
 ///
 /// [`initialize()`] or [`initialize_with_defaults()`] need to be called once before creating
@@ -210,13 +473,20 @@ pub fn initialize_with_defaults() {
 /// Initialized the V8 engine.
 ///
 /// [`initialize()`] or [`initialize_with_defaults()`] need to be called once before creating
-/// a runtime. Subsequent calls will result in a NOP.
+/// a runtime. Subsequent calls will result in a NOP. A warning is logged if a subsequent call
+/// uses a different configuration. Use [`is_initialized()`] and [`initialization_config()`] to
+/// detect a prior initialization.
 ///
 /// # Panics
 ///
 /// Panics if the V8 engine could not be initialized.
 pub fn initialize(options: InitializationOptions) {
+    let config = InitializationConfig::new(&options);
+    let mut initialized = false;
+
     V8_INITIALIZATION.call_once(|| {
+        initialized = true;
+
         let (flags, platform) = match options.execution_model {
             ExecutionModel::SingleThreaded => {
                 let flags = format!("{} {}", DEFAULT_V8_FLAGS, "--single-threaded");
@@ -233,8 +503,20 @@ pub fn initialize(options: InitializationOptions) {
             }
         };
 
-        load_icu(&options);
+        #[cfg(not(feature = "hide-intl"))]
+        icu::load_icu(&options.icu_source);
 
+        serialization::STRICT_STRINGS.store(
+            options.string_policy == StringPolicy::Strict,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+
+        serialization::NUMBER_POLICY.store(
+            options.number_policy as u8,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+
+        #[cfg(not(feature = "hide-intl"))]
         v8::icu::set_default_locale(options.default_locale.as_ref());
 
         #[cfg(feature = "getrandom")]
@@ -252,40 +534,12 @@ pub fn initialize(options: InitializationOptions) {
 
         v8::V8::initialize_platform(platform.make_shared());
         v8::V8::initialize();
-    });
-}
-
-fn load_icu(options: &InitializationOptions) {
-    // Either use the provided ICU file, or try to load a local ICU file.
-    let icu_data = match options.icu_data {
-        Some(icu_data) => Some(icu_data),
-        None => match std::fs::read(ICU_FILE_NAME) {
-            Ok(icu_data) => {
-                let icu_data = prepare_icu_data(&icu_data).expect("Invalid ICU data");
-                Some(icu_data)
-            }
-            Err(_) => None,
-        },
-    };
 
-    if let Some(icu_data) = icu_data {
-        assert_eq!(
-            icu_data.as_ptr() as usize % 16,
-            0,
-            "ICU data is not aligned to 16 bytes"
-        );
-
-        // SAFETY: We know that `Aligned16` is a multiple of byte alignment.
-        let byte_data = unsafe {
-            std::slice::from_raw_parts(
-                icu_data.as_ptr() as *const u8,
-                std::mem::size_of_val(icu_data),
-            )
-        };
+        *lock_initialization_config() = Some(config.clone());
+    });
 
-        if let Err(err_code) = v8::icu::set_common_data_71(byte_data) {
-            panic!("ICU could not be initialized: {}", err_code)
-        }
+    if !initialized && lock_initialization_config().as_ref() != Some(&config) {
+        log::warn!("V8 engine is already initialized with a different configuration");
     }
 }
 
@@ -317,7 +571,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{initialize_with_defaults, version_v8, Runtime, RuntimeOptions};
+    use crate::{
+        build_info, initialization_config, initialize_with_defaults, is_initialized, version_v8,
+        InitializationConfig, InitializationOptions, Runtime, RuntimeOptions,
+    };
 
     #[test]
     fn test_version_v8() {
@@ -325,7 +582,29 @@ mod test {
         assert!(version.milestone_major >= 10);
     }
 
+    #[test]
+    fn test_build_info() {
+        let build_info = build_info();
+        assert!(v8::V8::get_version().starts_with(&build_info.v8_version.to_string()));
+        assert_eq!(build_info.target_arch, std::env::consts::ARCH);
+        assert_eq!(build_info.target_os, std::env::consts::OS);
+    }
+
+    #[test]
+    fn test_initialization_config() {
+        initialize_with_defaults();
+
+        assert!(is_initialized());
+        let config = initialization_config().expect("No initialization config");
+        assert_eq!(config.default_locale, "en-US");
+        assert_eq!(
+            config.config_hash(),
+            InitializationConfig::new(&InitializationOptions::default()).config_hash()
+        );
+    }
+
     // For this test to run we need an ICU file in the root folder.
+    #[cfg(not(feature = "hide-intl"))]
     #[test]
     fn test_icu() {
         initialize_with_defaults();
@@ -194,6 +194,11 @@ macro_rules! static_function {
                 args: $crate::_macros::FunctionCallbackArguments<'scope>,
                 mut rv: $crate::_macros::ReturnValue,
             ) {
+                let name = stringify!($function_name);
+                let Some(_guard) = $crate::_macros::HostCallGuard::enter_static(scope, name) else {
+                    return;
+                };
+
                 // SAFETY: This is safe since we know that the state is stored in that slot
                 //         and the data is bound to the lifetime of this runtime.
                 let $state_name = unsafe { &*(scope.get_data($crate::_macros::STATE_DATA_SLOT) as *const std::cell::RefCell<$state_type>) };
@@ -232,6 +237,11 @@ macro_rules! static_function {
                 args: $crate::_macros::FunctionCallbackArguments<'scope>,
                 mut rv: $crate::_macros::ReturnValue,
             ) {
+                let name = stringify!($function_name);
+                let Some(_guard) = $crate::_macros::HostCallGuard::enter_static(scope, name) else {
+                    return;
+                };
+
                 // SAFETY: This is safe since we know that the state is stored in that slot
                 //         and the data is bound to the lifetime of this runtime.
                 let $state_name = unsafe { &*(scope.get_data($crate::_macros::STATE_DATA_SLOT) as *const std::cell::RefCell<$state_type>) };
@@ -271,6 +281,11 @@ macro_rules! static_function {
                 args: $crate::_macros::FunctionCallbackArguments<'scope>,
                 mut rv: $crate::_macros::ReturnValue,
             ) {
+                let name = stringify!($function_name);
+                let Some(_guard) = $crate::_macros::HostCallGuard::enter_static(scope, name) else {
+                    return;
+                };
+
                 let counter_value = 0; 
                 let Some($first_arg_name) = $crate::_macros::get_argument::<$first_arg_type>(scope, &args, &mut rv, counter_value) else {
                     return;
@@ -307,6 +322,11 @@ macro_rules! static_function {
                 args: $crate::_macros::FunctionCallbackArguments<'scope>,
                 mut rv: $crate::_macros::ReturnValue,
             ) {
+                let name = stringify!($function_name);
+                let Some(_guard) = $crate::_macros::HostCallGuard::enter_static(scope, name) else {
+                    return;
+                };
+
                 let counter_value = 0; 
                 let Some($first_arg_name) = $crate::_macros::get_argument::<$first_arg_type>(scope, &args, &mut rv, counter_value) else {
                     return;
@@ -334,7 +354,7 @@ macro_rules! static_function {
 /// When given as the first argument, the function can also mutate the runtime state.
 ///
 /// Function arguments need to implement the [`crate::FastcallArgument`] trait.
-/// Currently supported are: bool, i32, u32, f32, f64. 
+/// Currently supported are: bool, i32, u32, f32, f64 and [`crate::OneByteString`].
 /// 
 /// Return parameter need to implement the [`crate::FastcallReturnValue`] trait.
 /// Currently supported are: bool, i32, u32, f32, f64.
@@ -345,11 +365,14 @@ macro_rules! static_function {
 /// u64 and i64 are supported by V8, but their values get truncated and they are not
 /// converted to bigints. They will get supported, once V8 implements the bigint
 /// conversion in the `fastapi` API.
+///
+/// Strings are passed as [`crate::OneByteString`]. V8 only takes the fast path for sequential
+/// one-byte (Latin-1) strings and uses the regular callback for all other strings.
 /// 
 /// # Example
 /// 
 /// ```rust
-/// use kopi::fastcall_function;
+/// use kopi::{fastcall_function, OneByteString};
 ///
 /// struct State;
 ///
@@ -384,6 +407,10 @@ macro_rules! static_function {
 /// fastcall_function! {
 ///     fn static_function_7() {}
 /// }
+///
+/// fastcall_function! {
+///     fn static_function_8(key: OneByteString) -> u32 { key.as_bytes().len() as u32 }
+/// }
 /// ```
 #[macro_export]
 #[rustfmt::skip]
@@ -642,7 +669,7 @@ macro_rules! fastcall_function {
         impl $function_name {
             fn fast_call(
                 _recv: $crate::_macros::Local<$crate::_macros::Object>,
-                $($arg_name: $arg_type,)*
+                $($arg_name: <$arg_type as $crate::FastcallArgument>::Value,)*
                 fast_api_callback_options: *mut $crate::_macros::FastApiCallbackOptions,
             ) {
                 // SAFETY: We know that the pointer point to these structs as defined by rusty_v8.
@@ -656,6 +683,7 @@ macro_rules! fastcall_function {
                         .value() as *const std::cell::RefCell<$state_type>)
                 };
                 let mut borrow = $state_name.borrow_mut();
+                $(let $arg_name = <$arg_type as $crate::_macros::FromFastcallValue>::from_value(&$arg_name);)*
                 
                 Self::call(&mut borrow $(,$arg_name)*);
             }
@@ -725,7 +753,7 @@ macro_rules! fastcall_function {
         impl $function_name {
             fn fast_call(
                 _recv: $crate::_macros::Local<$crate::_macros::Object>,
-                $($arg_name: $arg_type,)*
+                $($arg_name: <$arg_type as $crate::FastcallArgument>::Value,)*
                 fast_api_callback_options: *mut $crate::_macros::FastApiCallbackOptions,
             ) -> $return_type {
                 // SAFETY: We know that the pointer point to these structs as defined by rusty_v8.
@@ -739,6 +767,7 @@ macro_rules! fastcall_function {
                         .value() as *const std::cell::RefCell<$state_type>)
                 };
                 let mut borrow = $state_name.borrow_mut();
+                $(let $arg_name = <$arg_type as $crate::_macros::FromFastcallValue>::from_value(&$arg_name);)*
                 
                 Self::call(&mut borrow $(,$arg_name)*)
             }
@@ -804,9 +833,11 @@ macro_rules! fastcall_function {
         impl $function_name {
             fn fast_call(
                 _recv: $crate::_macros::Local<$crate::_macros::Object>,
-                $first_arg_name: $first_arg_type,
-                $($arg_name: $arg_type,)*
+                $first_arg_name: <$first_arg_type as $crate::FastcallArgument>::Value,
+                $($arg_name: <$arg_type as $crate::FastcallArgument>::Value,)*
             ) {
+                let $first_arg_name = <$first_arg_type as $crate::_macros::FromFastcallValue>::from_value(&$first_arg_name);
+                $(let $arg_name = <$arg_type as $crate::_macros::FromFastcallValue>::from_value(&$arg_name);)*
                 Self::call($first_arg_name $(,$arg_name)*)
             }
 
@@ -871,9 +902,11 @@ macro_rules! fastcall_function {
         impl $function_name {
             fn fast_call(
                 _recv: $crate::_macros::Local<$crate::_macros::Object>,
-                $first_arg_name: $first_arg_type,
-                $($arg_name: $arg_type,)*
+                $first_arg_name: <$first_arg_type as $crate::FastcallArgument>::Value,
+                $($arg_name: <$arg_type as $crate::FastcallArgument>::Value,)*
             ) -> $return_type {
+                let $first_arg_name = <$first_arg_type as $crate::_macros::FromFastcallValue>::from_value(&$first_arg_name);
+                $(let $arg_name = <$arg_type as $crate::_macros::FromFastcallValue>::from_value(&$arg_name);)*
                 Self::call($first_arg_name $(,$arg_name)*)
             }
 
@@ -0,0 +1,110 @@
+//! Implements the memory extension.
+
+use crate::{
+    error::TypeError,
+    value::{NameCache, Object, Unseal, Value, ValueScope},
+    Extension, HeapStatistics, Serialize,
+};
+
+/// Namespace of the memory extension.
+const NAMESPACE: &str = "host";
+
+/// Provides scripts a safe subset of the heap statistics of their runtime under the `host`
+/// namespace, so that well-behaved scripts can throttle themselves before they hit the heap
+/// limit.
+///
+/// | Function        | Description                                                      |
+/// |-----------------|------------------------------------------------------------------|
+/// | `host.memory()` | Returns `{ used, limit, percent }` of the heap of the runtime.   |
+///
+/// `used` and `limit` are given in bytes, `percent` is the used part of the limit in the
+/// range `[0, 100]`.
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::{initialize_with_defaults, MemoryExtension, Runtime, RuntimeOptions};
+///
+/// initialize_with_defaults();
+///
+/// let mut runtime = Runtime::new(
+///     RuntimeOptions {
+///         extensions: vec![MemoryExtension::create()],
+///         ..Default::default()
+///     },
+///     (),
+/// )
+/// .expect("Can't create runtime");
+///
+/// let val: bool = runtime
+///     .execute("host.memory().percent < 90")
+///     .expect("Can't execute code");
+///
+/// assert!(val);
+/// ```
+pub struct MemoryExtension;
+
+impl MemoryExtension {
+    /// Creates the memory extension.
+    pub fn create<STATE>() -> Extension<STATE> {
+        let mut extension = Extension::new(Some(NAMESPACE));
+        extension.add_function("memory", |()| MemoryUsage);
+        extension
+    }
+}
+
+/// Serializes the current heap usage of the runtime.
+struct MemoryUsage;
+
+impl Serialize for MemoryUsage {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        let statistics = HeapStatistics::new(scope.unseal());
+        let used = statistics.used_heap_size();
+        let limit = statistics.heap_size_limit();
+        let percent = if limit == 0 {
+            0.0
+        } else {
+            (used as f64 / limit as f64 * 100.0).min(100.0)
+        };
+
+        let object = Object::new(scope);
+        for (name, value) in [
+            ("used", used as f64),
+            ("limit", limit as f64),
+            ("percent", percent),
+        ] {
+            let key = NameCache::get(scope, name);
+            let value = value.serialize(scope)?;
+            object.set(scope, key.into(), value);
+        }
+
+        Ok(object.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{initialize_with_defaults, MemoryExtension, Runtime, RuntimeOptions};
+
+    #[test]
+    fn memory() {
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![MemoryExtension::create()],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: bool = runtime
+            .execute(
+                "const m = host.memory(); \
+                m.used > 0 && m.limit >= m.used && m.percent > 0 && m.percent <= 100",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+    }
+}
@@ -0,0 +1,69 @@
+//! Implements the policy of the microtask queue and the tracking of unhandled promise
+//! rejections.
+
+use crate::runtime::RuntimeData;
+
+/// Decides when the microtasks (e.g. promise reactions) of a runtime are run.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MicrotasksPolicy {
+    /// The microtasks are only run at checkpoints. The runtime performs a checkpoint after every
+    /// execution and [`crate::Runtime::perform_microtask_checkpoint()`] performs one on demand.
+    #[default]
+    Explicit,
+    /// The engine additionally runs the microtasks, once the depth of script calls drops to
+    /// zero. The reactions of a script then already ran, when its evaluated value is converted.
+    Auto,
+}
+
+impl From<MicrotasksPolicy> for v8::MicrotasksPolicy {
+    fn from(policy: MicrotasksPolicy) -> Self {
+        match policy {
+            MicrotasksPolicy::Explicit => v8::MicrotasksPolicy::Explicit,
+            MicrotasksPolicy::Auto => v8::MicrotasksPolicy::Auto,
+        }
+    }
+}
+
+/// The callback of the engine, that tracks the rejected promises without a handler. A promise
+/// is only reported as unhandled, if it still has no handler after the next microtask
+/// checkpoint.
+pub(crate) extern "C" fn promise_reject_callback(message: v8::PromiseRejectMessage) {
+    // SAFETY: The engine calls the callback with a valid message from inside the isolate.
+    let scope = &mut unsafe { v8::CallbackScope::new(&message) };
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    let promise = message.get_promise();
+    match message.get_event() {
+        v8::PromiseRejectEvent::PromiseRejectWithNoHandler => {
+            let reason = message
+                .get_value()
+                .unwrap_or_else(|| v8::undefined(scope).into());
+            let rejection = (
+                v8::Global::new(scope, promise),
+                v8::Global::new(scope, reason),
+            );
+            runtime_data
+                .unhandled_rejections
+                .borrow_mut()
+                .push(rejection);
+        }
+        v8::PromiseRejectEvent::PromiseHandlerAddedAfterReject => {
+            forget_rejection(scope, promise);
+        }
+        _ => {}
+    }
+}
+
+/// Removes the tracked rejection of the promise, since its rejection was handled.
+pub(crate) fn forget_rejection(scope: &mut v8::HandleScope, promise: v8::Local<v8::Promise>) {
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    runtime_data
+        .unhandled_rejections
+        .borrow_mut()
+        .retain(|(rejected, _)| v8::Local::new(scope, rejected) != promise);
+}
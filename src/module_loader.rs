@@ -0,0 +1,356 @@
+//! Implements the loading of ES modules.
+
+use std::{collections::HashMap, num::NonZeroI32};
+
+use crate::{
+    code_verifier::{verify_source, ScriptKind, ScriptSource},
+    runtime::RuntimeData,
+    value::{new_string, NewStringType},
+};
+
+/// Resolves and loads the ES modules, that are imported by other modules.
+///
+/// Imports with the assertion `assert { type: "json" }` load a JSON module, whose default export
+/// is the parsed source. Imports with the assertion `assert { type: "bytes" }` load a bytes
+/// module, whose default export is an `Uint8Array` with the content returned by
+/// [`ModuleLoader::load_bytes()`]. The engine doesn't support the newer `with { type: "json" }`
+/// syntax yet.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use kopi::ModuleLoader;
+///
+/// struct MemoryLoader(HashMap<String, String>);
+///
+/// impl ModuleLoader for MemoryLoader {
+///     fn load(&self, name: &str) -> Result<String, String> {
+///         self.0
+///             .get(name)
+///             .cloned()
+///             .ok_or_else(|| format!("Can't find module '{}'", name))
+///     }
+/// }
+/// ```
+pub trait ModuleLoader {
+    /// Resolves the specifier of an import inside the module with the name `referrer` and
+    /// returns the name of the imported module. Modules with the same name are only loaded once.
+    ///
+    /// The default implementation uses the specifier as the name.
+    fn resolve(&self, specifier: &str, _referrer: &str) -> Result<String, String> {
+        Ok(specifier.to_string())
+    }
+
+    /// Loads the source of the module with the given name.
+    fn load(&self, name: &str) -> Result<String, String>;
+
+    /// Loads the content of the bytes module with the given name.
+    ///
+    /// The default implementation rejects all bytes modules.
+    fn load_bytes(&self, name: &str) -> Result<Vec<u8>, String> {
+        Err(format!("Can't import '{}' as bytes", name))
+    }
+}
+
+/// The type of an imported module, that is given by the `type` import assertion.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ModuleType {
+    JavaScript,
+    Json,
+    Bytes,
+}
+
+impl ModuleType {
+    /// Parses the import assertions, which are given as triplets of key, value and source
+    /// offset.
+    fn from_assertions(
+        scope: &mut v8::HandleScope,
+        import_assertions: v8::Local<v8::FixedArray>,
+    ) -> Result<Self, String> {
+        for index in (0..import_assertions.length()).step_by(3) {
+            let key = import_assertions
+                .get(scope, index)
+                .and_then(|key| v8::Local::<v8::String>::try_from(key).ok())
+                .map(|key| key.to_rust_string_lossy(scope));
+            if key.as_deref() != Some("type") {
+                continue;
+            }
+
+            let value = import_assertions
+                .get(scope, index + 1)
+                .and_then(|value| v8::Local::<v8::String>::try_from(value).ok())
+                .map(|value| value.to_rust_string_lossy(scope))
+                .unwrap_or_default();
+
+            return match value.as_str() {
+                "json" => Ok(ModuleType::Json),
+                "bytes" => Ok(ModuleType::Bytes),
+                _ => Err(format!("Unsupported module type '{}'", value)),
+            };
+        }
+
+        Ok(ModuleType::JavaScript)
+    }
+
+    /// Returns the name of the module inside the registry. Modules of other types than
+    /// JavaScript are registered under a separate name, so that a module can be imported with
+    /// different types.
+    fn registry_name(self, name: &str) -> String {
+        match self {
+            ModuleType::JavaScript => name.to_string(),
+            ModuleType::Json => format!("json:{}", name),
+            ModuleType::Bytes => format!("bytes:{}", name),
+        }
+    }
+}
+
+/// The compiled modules of a runtime.
+#[derive(Default)]
+pub(crate) struct ModuleRegistry {
+    /// The modules keyed by their name.
+    modules: HashMap<String, v8::Global<v8::Module>>,
+    /// The names of the modules keyed by the identity hash of the module.
+    names: HashMap<NonZeroI32, String>,
+    /// The default exports of the synthetic modules, that were not evaluated yet, keyed by the
+    /// identity hash of the module.
+    synthetic_exports: HashMap<NonZeroI32, v8::Global<v8::Value>>,
+}
+
+impl ModuleRegistry {
+    /// Returns the module with the given name.
+    pub(crate) fn get(&self, name: &str) -> Option<&v8::Global<v8::Module>> {
+        self.modules.get(name)
+    }
+
+    /// Registers the module under the given name.
+    pub(crate) fn insert(
+        &mut self,
+        scope: &mut v8::HandleScope,
+        name: &str,
+        module: v8::Local<v8::Module>,
+    ) {
+        self.names
+            .insert(module.get_identity_hash(), name.to_string());
+        self.modules
+            .insert(name.to_string(), v8::Global::new(scope, module));
+    }
+
+    /// Removes all modules. Must be called while the isolate is still alive.
+    pub(crate) fn clear(&mut self) {
+        self.modules.clear();
+        self.names.clear();
+        self.synthetic_exports.clear();
+    }
+}
+
+/// Compiles the source as an ES module with the given name. The code cache is consumed if
+/// given. A code cache that doesn't match the source or the engine is rejected by the engine,
+/// which then compiles the source without it.
+pub(crate) fn compile_module<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    name: &str,
+    source: &str,
+    code_cache: Option<&[u8]>,
+) -> Option<v8::Local<'scope, v8::Module>> {
+    let name = new_string(scope, name, NewStringType::Normal);
+    let source_map_url = v8::undefined(scope);
+    let origin = v8::ScriptOrigin::new(
+        scope,
+        name.into(),
+        0,
+        0,
+        false,
+        0,
+        source_map_url.into(),
+        false,
+        false,
+        true,
+    );
+
+    let source = new_string(scope, source, NewStringType::Normal);
+    match code_cache {
+        Some(code_cache) => {
+            let cached_data = v8::CachedData::new(code_cache);
+            let source = v8::script_compiler::Source::new_with_cached_data(
+                source,
+                Some(&origin),
+                cached_data,
+            );
+            v8::script_compiler::compile_module2(
+                scope,
+                source,
+                v8::script_compiler::CompileOptions::ConsumeCodeCache,
+                v8::script_compiler::NoCacheReason::NoReason,
+            )
+        }
+        None => {
+            let source = v8::script_compiler::Source::new(source, Some(&origin));
+            v8::script_compiler::compile_module(scope, source)
+        }
+    }
+}
+
+/// Creates a synthetic module with the given value as its default export.
+fn create_synthetic_module<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    data: &RuntimeData,
+    name: &str,
+    value: v8::Local<v8::Value>,
+) -> v8::Local<'scope, v8::Module> {
+    let name = new_string(scope, name, NewStringType::Normal);
+    let export_name = new_string(scope, "default", NewStringType::Internalized);
+    let module = v8::Module::create_synthetic_module(
+        scope,
+        name,
+        &[export_name],
+        synthetic_module_evaluation_steps,
+    );
+
+    let value = v8::Global::new(scope, value);
+    data.modules
+        .borrow_mut()
+        .synthetic_exports
+        .insert(module.get_identity_hash(), value);
+
+    module
+}
+
+/// Is called by the engine to evaluate a synthetic module. Sets the default export of the
+/// module.
+fn synthetic_module_evaluation_steps<'scope>(
+    context: v8::Local<'scope, v8::Context>,
+    module: v8::Local<'scope, v8::Module>,
+) -> Option<v8::Local<'scope, v8::Value>> {
+    // SAFETY: This is safe since the callback is called by the engine from inside the context.
+    let scope = &mut unsafe { v8::CallbackScope::new(context) };
+
+    let Some(data) = RuntimeData::from_isolate(scope) else {
+        return throw_error(scope, "Modules can only be evaluated inside a runtime");
+    };
+
+    let value = data
+        .modules
+        .borrow_mut()
+        .synthetic_exports
+        .remove(&module.get_identity_hash());
+    let Some(value) = value else {
+        return throw_error(scope, "The synthetic module was already evaluated");
+    };
+
+    let value = v8::Local::new(scope, value);
+    let export_name = new_string(scope, "default", NewStringType::Internalized);
+    module.set_synthetic_module_export(scope, export_name, value)?;
+
+    Some(v8::undefined(scope).into())
+}
+
+/// Is called by the engine to resolve the imports of a module.
+pub(crate) fn resolve_module_callback<'scope>(
+    context: v8::Local<'scope, v8::Context>,
+    specifier: v8::Local<'scope, v8::String>,
+    import_assertions: v8::Local<'scope, v8::FixedArray>,
+    referrer: v8::Local<'scope, v8::Module>,
+) -> Option<v8::Local<'scope, v8::Module>> {
+    // SAFETY: This is safe since the callback is called by the engine from inside the context.
+    let scope = &mut unsafe { v8::CallbackScope::new(context) };
+    let specifier = specifier.to_rust_string_lossy(scope);
+
+    let module_type = match ModuleType::from_assertions(scope, import_assertions) {
+        Ok(module_type) => module_type,
+        Err(msg) => return throw_error(scope, &msg),
+    };
+
+    let Some(data) = RuntimeData::from_isolate(scope) else {
+        return throw_error(scope, "Modules can only be imported inside a runtime");
+    };
+
+    let loader = data.module_loader.as_ref();
+
+    let name = match loader {
+        Some(loader) => {
+            let referrer = data
+                .modules
+                .borrow()
+                .names
+                .get(&referrer.get_identity_hash())
+                .cloned()
+                .unwrap_or_default();
+
+            match loader.resolve(&specifier, &referrer) {
+                Ok(name) => name,
+                Err(msg) => return throw_error(scope, &msg),
+            }
+        }
+        None => specifier,
+    };
+
+    let registry_name = module_type.registry_name(&name);
+    if let Some(module) = data.modules.borrow().get(&registry_name) {
+        return Some(v8::Local::new(scope, module));
+    }
+
+    let Some(loader) = loader else {
+        let msg = format!("Can't import '{}' without a module loader", name);
+        return throw_error(scope, &msg);
+    };
+
+    if module_type == ModuleType::Bytes {
+        let bytes = match loader.load_bytes(&name) {
+            Ok(bytes) => bytes,
+            Err(msg) => return throw_error(scope, &msg),
+        };
+
+        let length = bytes.len();
+        let backing_store = v8::ArrayBuffer::new_backing_store_from_vec(bytes).make_shared();
+        let buffer = v8::ArrayBuffer::with_backing_store(scope, &backing_store);
+        let array = v8::Uint8Array::new(scope, buffer, 0, length)?;
+
+        let module = create_synthetic_module(scope, data, &name, array.into());
+        data.modules
+            .borrow_mut()
+            .insert(scope, &registry_name, module);
+        return Some(module);
+    }
+
+    let source = match loader.load(&name) {
+        Ok(source) => source,
+        Err(msg) => return throw_error(scope, &msg),
+    };
+
+    let script = ScriptSource {
+        kind: ScriptKind::Module,
+        name: Some(&name),
+        source: &source,
+    };
+    if let Err(err) = verify_source(data, &script) {
+        return throw_error(scope, &err.to_string());
+    }
+
+    if module_type == ModuleType::Json {
+        let source = new_string(scope, &source, NewStringType::Normal);
+        // A failed parse leaves the exception pending, which is then reported by the engine.
+        let value = v8::json::parse(scope, source)?;
+
+        let module = create_synthetic_module(scope, data, &name, value);
+        data.modules
+            .borrow_mut()
+            .insert(scope, &registry_name, module);
+        return Some(module);
+    }
+
+    // A failed compilation leaves the exception pending, which is then reported by the engine.
+    let module = compile_module(scope, &name, &source, None)?;
+    data.modules.borrow_mut().insert(scope, &name, module);
+
+    Some(module)
+}
+
+/// Throws an error with the given message.
+fn throw_error<T>(scope: &mut v8::HandleScope, msg: &str) -> Option<T> {
+    let msg = new_string(scope, msg, NewStringType::Normal);
+    let exception = v8::Exception::error(scope, msg);
+    scope.throw_exception(exception);
+    None
+}
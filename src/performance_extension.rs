@@ -0,0 +1,215 @@
+//! Implements the performance extension.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::Extension;
+
+/// Namespace of the performance extension.
+const NAMESPACE: &str = "performance";
+
+/// Provides a minimal subset of the `performance` API to scripts, with a time source that is
+/// controlled by the host.
+///
+/// | Function                                   | Description                                             |
+/// |--------------------------------------------|---------------------------------------------------------|
+/// | `performance.now()`                        | Returns the current time of the clock.                  |
+/// | `performance.mark(name)`                   | Stores the current time under the name and returns it.  |
+/// | `performance.measure(name, start?, end?)`  | Returns the milliseconds between two marks.             |
+/// | `performance.clearMarks(name?)`            | Removes the given mark or all marks.                    |
+///
+/// All times are given in milliseconds. If no start mark is given, `measure` measures from the
+/// origin of the clock. If no end mark is given, it measures until now. Unknown marks result
+/// in `NaN`.
+///
+/// Providing a deterministic clock makes the time measurements inside scripts reproducible,
+/// for example when replaying a recorded game session.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::{
+///     atomic::{AtomicU64, Ordering},
+///     Arc,
+/// };
+///
+/// use kopi::{initialize_with_defaults, PerformanceExtension, Runtime, RuntimeOptions};
+///
+/// initialize_with_defaults();
+///
+/// let frame = Arc::new(AtomicU64::new(0));
+/// let clock_frame = frame.clone();
+///
+/// let mut runtime = Runtime::new(
+///     RuntimeOptions {
+///         extensions: vec![PerformanceExtension::create(move || {
+///             clock_frame.load(Ordering::Relaxed) as f64 * 16.0
+///         })],
+///         ..Default::default()
+///     },
+///     (),
+/// )
+/// .expect("Can't create runtime");
+///
+/// runtime
+///     .execute::<(), _>("performance.mark('start')")
+///     .expect("Can't execute code");
+///
+/// frame.store(2, Ordering::Relaxed);
+///
+/// let val: f64 = runtime
+///     .execute("performance.measure('frame', 'start')")
+///     .expect("Can't execute code");
+///
+/// assert_eq!(val, 32.0);
+/// ```
+pub struct PerformanceExtension;
+
+impl PerformanceExtension {
+    /// Creates the performance extension with the given clock. The clock returns the current
+    /// time in milliseconds.
+    pub fn create<STATE, F>(clock: F) -> Extension<STATE>
+    where
+        F: 'static + Send + Sync + Fn() -> f64,
+    {
+        let clock = Arc::new(clock);
+        let marks = Arc::new(Mutex::new(HashMap::<String, f64>::new()));
+
+        let mut extension = Extension::new(Some(NAMESPACE));
+
+        let now_clock = clock.clone();
+        extension.add_function("now", move |()| now_clock());
+
+        let mark_clock = clock.clone();
+        let mark_marks = marks.clone();
+        extension.add_function("mark", move |(name,): (String,)| {
+            let time = mark_clock();
+            lock(&mark_marks).insert(name, time);
+            time
+        });
+
+        let measure_marks = marks.clone();
+        extension.add_function(
+            "measure",
+            move |(_name, start, end): (String, Option<String>, Option<String>)| {
+                let marks = lock(&measure_marks);
+                let start = match start {
+                    Some(start) => marks.get(&start).copied().unwrap_or(f64::NAN),
+                    None => 0.0,
+                };
+                let end = match end {
+                    Some(end) => marks.get(&end).copied().unwrap_or(f64::NAN),
+                    None => clock(),
+                };
+                end - start
+            },
+        );
+
+        extension.add_function("clearMarks", move |(name,): (Option<String>,)| {
+            let mut marks = lock(&marks);
+            match name {
+                Some(name) => {
+                    marks.remove(&name);
+                }
+                None => marks.clear(),
+            }
+        });
+
+        extension
+    }
+
+    /// Creates the performance extension with a monotonic system clock, that starts at the
+    /// creation of the extension.
+    pub fn create_with_system_clock<STATE>() -> Extension<STATE> {
+        let origin = Instant::now();
+        Self::create(move || origin.elapsed().as_secs_f64() * 1000.0)
+    }
+}
+
+/// Locks the marks, even if the mutex was poisoned.
+fn lock(marks: &Mutex<HashMap<String, f64>>) -> std::sync::MutexGuard<'_, HashMap<String, f64>> {
+    match marks.lock() {
+        Ok(marks) => marks,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use crate::{initialize_with_defaults, PerformanceExtension, Runtime, RuntimeOptions};
+
+    #[test]
+    fn deterministic_clock() {
+        initialize_with_defaults();
+
+        let time = Arc::new(AtomicU32::new(100));
+        let clock_time = time.clone();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![PerformanceExtension::create(move || {
+                    clock_time.load(Ordering::Relaxed) as f64
+                })],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: f64 = runtime
+            .execute("performance.now()")
+            .expect("Can't execute code");
+        assert_eq!(val, 100.0);
+
+        runtime
+            .execute::<(), _>("performance.mark('a')")
+            .expect("Can't execute code");
+        time.store(150, Ordering::Relaxed);
+        runtime
+            .execute::<(), _>("performance.mark('b')")
+            .expect("Can't execute code");
+        time.store(175, Ordering::Relaxed);
+
+        let val: f64 = runtime
+            .execute("performance.measure('ab', 'a', 'b')")
+            .expect("Can't execute code");
+        assert_eq!(val, 50.0);
+
+        let val: f64 = runtime
+            .execute("performance.measure('a_now', 'a')")
+            .expect("Can't execute code");
+        assert_eq!(val, 75.0);
+
+        let val: bool = runtime
+            .execute("performance.clearMarks('a'); isNaN(performance.measure('ab', 'a', 'b'))")
+            .expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn system_clock() {
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![PerformanceExtension::create_with_system_clock()],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: bool = runtime
+            .execute("const a = performance.now(); const b = performance.now(); a >= 0 && b >= a")
+            .expect("Can't execute code");
+        assert!(val);
+    }
+}
@@ -0,0 +1,33 @@
+//! Re-exports the types, traits and macros that are commonly needed to embed a runtime.
+//!
+//! Also contains the common value types of the [`crate::value::prelude`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use kopi::prelude::*;
+//!
+//! initialize_with_defaults();
+//!
+//! let mut extension = Extension::new(None);
+//! extension.add_function("add", |(x, y): (i32, i32)| x + y);
+//!
+//! let mut runtime = Runtime::new(
+//!     RuntimeOptions {
+//!         extensions: vec![extension],
+//!         ..Default::default()
+//!     },
+//!     (),
+//! )
+//! .expect("Can't create runtime");
+//!
+//! let result: i32 = runtime.execute("add(1, 2)").expect("Can't execute code");
+//! assert_eq!(result, 3);
+//! ```
+
+pub use crate::{
+    error::{Error, ScriptError},
+    fastcall_function, initialize, initialize_with_defaults, static_function,
+    value::prelude::*,
+    Extension, InitializationOptions, Runtime, RuntimeOptions,
+};
@@ -0,0 +1,103 @@
+//! Implements the rate limiting of host functions.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Limits how often a host function can be called by scripts.
+///
+/// When a limit is exceeded, a `RangeError` is thrown into the script and the optional
+/// `on_exceeded` callback is called with the name of the function.
+#[derive(Clone, Default)]
+pub struct RateLimit {
+    /// Maximal number of calls during a single execution of a script.
+    pub calls_per_execute: Option<u32>,
+    /// Maximal number of calls per second.
+    pub calls_per_second: Option<u32>,
+    /// Notifies the host with the name of the function when the rate limit was exceeded.
+    pub on_exceeded: Option<Arc<dyn Fn(&str) + Send + Sync>>,
+}
+
+struct RateLimiterState {
+    execute_calls: u32,
+    second_start: Instant,
+    second_calls: u32,
+}
+
+/// Tracks the calls of a rate limited host function.
+pub(crate) struct RateLimiter {
+    name: String,
+    limit: RateLimit,
+    state: Mutex<RateLimiterState>,
+}
+
+impl RateLimiter {
+    /// Creates a new [`RateLimiter`] for the function with the given name.
+    pub(crate) fn new(name: String, limit: RateLimit) -> Self {
+        Self {
+            name,
+            limit,
+            state: Mutex::new(RateLimiterState {
+                execute_calls: 0,
+                second_start: Instant::now(),
+                second_calls: 0,
+            }),
+        }
+    }
+
+    /// Returns the name of the function as seen from inside a script.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Registers a call. Returns `false` if the call exceeds the rate limit.
+    pub(crate) fn try_call(&self) -> bool {
+        let allowed = {
+            let mut state = match self.state.lock() {
+                Ok(state) => state,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+
+            let now = Instant::now();
+            if now.duration_since(state.second_start) >= Duration::from_secs(1) {
+                state.second_start = now;
+                state.second_calls = 0;
+            }
+
+            let execute_exceeded = self
+                .limit
+                .calls_per_execute
+                .map_or(false, |limit| state.execute_calls >= limit);
+            let second_exceeded = self
+                .limit
+                .calls_per_second
+                .map_or(false, |limit| state.second_calls >= limit);
+
+            if execute_exceeded || second_exceeded {
+                false
+            } else {
+                state.execute_calls += 1;
+                state.second_calls += 1;
+                true
+            }
+        };
+
+        if !allowed {
+            if let Some(on_exceeded) = &self.limit.on_exceeded {
+                on_exceeded(&self.name);
+            }
+        }
+
+        allowed
+    }
+
+    /// Resets the calls that were counted for the last execution of a script.
+    pub(crate) fn reset_execute(&self) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        state.execute_calls = 0;
+    }
+}
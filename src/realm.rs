@@ -0,0 +1,15 @@
+//! Implements the handle of a realm, an additional context inside a runtime.
+
+/// A handle to a realm of a runtime. Created by [`crate::Runtime::create_realm()`].
+///
+/// A realm has its own global object and its own builtins, but shares the heap and the state
+/// with all other realms of the runtime. Objects of one realm can't be accessed from another
+/// realm, which makes realms a cheaper alternative to a runtime per tenant or plugin.
+///
+/// A realm can only be used with the runtime that created it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Realm {
+    /// The id of the runtime, that created the realm.
+    pub(crate) runtime: u64,
+    pub(crate) index: usize,
+}
@@ -1,25 +1,164 @@
 //! Implements the ECMAScript runtime.
 
-use std::{any::Any, cell::RefCell, ffi::c_void, rc::Rc, sync::Arc};
+use std::{
+    any::Any,
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ffi::c_void,
+    io::Write,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 // Needs to be public for the `static_function` macro.
 /// Slot inside the runtime in which we save a `Rc<RefCell<S>>` to the state `S`.
 #[doc(hidden)]
 pub const STATE_DATA_SLOT: u32 = 0;
 
+/// Slot inside the runtime in which we save a pointer to the [`RuntimeData`].
+const RUNTIME_DATA_SLOT: u32 = 1;
+
+/// Data of the runtime that host function callbacks need access to.
+pub(crate) struct RuntimeData {
+    /// The current depth of nested host function calls.
+    pub(crate) host_call_depth: Cell<u32>,
+    /// The maximal depth of nested host function calls.
+    pub(crate) max_host_call_depth: Option<u32>,
+    /// The internalized strings of the property names used by the [`crate::value::NameCache`].
+    pub(crate) name_cache: RefCell<HashMap<&'static str, v8::Global<v8::String>>>,
+    /// The number of failed argument conversions per host function, keyed by the pointer of
+    /// the closure of the host function.
+    pub(crate) conversion_errors: RefCell<HashMap<*const c_void, u64>>,
+    /// Scratch buffer that is re-used when converting strings.
+    pub(crate) scratch: RefCell<Vec<u16>>,
+    /// The capacity of the scratch buffer, that is retained between string conversions.
+    pub(crate) scratch_capacity: Cell<usize>,
+    /// The private data of extensions, keyed by the name of the extension.
+    pub(crate) extension_data: RefCell<HashMap<String, Box<dyn Any>>>,
+    /// The counters of the runtime.
+    pub(crate) counters: Cell<RuntimeCounters>,
+    /// Only BigInts are accepted for 64 bit integer types and numbers for smaller integer types.
+    pub(crate) strict_bigints: bool,
+    /// The loader of the modules, that are imported by other modules.
+    pub(crate) module_loader: Option<Box<dyn ModuleLoader>>,
+    /// The compiled modules.
+    pub(crate) modules: RefCell<ModuleRegistry>,
+    /// The global properties, that are computed on their first access.
+    pub(crate) lazy_globals: Vec<LazyGlobal>,
+    /// Is called when the heap is close to its limit and returns the new heap limit.
+    pub(crate) near_heap_limit: RefCell<Option<Box<dyn FnMut(usize) -> usize>>>,
+    /// Is set when a script was terminated, because the heap reached its limit.
+    pub(crate) heap_limit_reached: Cell<bool>,
+    /// Verifies the scripts before they are compiled.
+    pub(crate) code_verifier: Option<CodeVerifier>,
+    /// The pending tasks of async host functions.
+    pub(crate) async_tasks: RefCell<AsyncTasks>,
+    /// The pending timers of scripts.
+    pub(crate) timers: RefCell<Timers>,
+    /// Collects the timings of the current execution, if it's timed.
+    pub(crate) timing: Cell<Option<TimingCollector>>,
+    /// The resolvers of the promises returned by `host.yield()`, that wait to be resumed.
+    pub(crate) yielded: RefCell<Vec<v8::Global<v8::PromiseResolver>>>,
+    /// The sink of the `console` API.
+    pub(crate) console: RefCell<Option<Box<dyn ConsoleSink>>>,
+    /// The native data of the living instances of native classes.
+    pub(crate) class_instances: ClassInstances,
+    /// The most recent host function calls, that are attached to script errors.
+    pub(crate) breadcrumbs: RefCell<Breadcrumbs>,
+    /// The rejected promises without a handler and their reasons, that are reported after the
+    /// next microtask checkpoint.
+    pub(crate) unhandled_rejections: RefCell<Vec<(v8::Global<v8::Promise>, v8::Global<v8::Value>)>>,
+}
+
+impl RuntimeData {
+    /// Returns the data of the runtime, that owns the isolate. Returns `None` if the isolate
+    /// doesn't belong to a runtime.
+    ///
+    /// The data is not bound to the borrow of the isolate, so that the isolate can still be used
+    /// while the data is accessed. It must not be kept beyond the current callback or call.
+    #[inline(always)]
+    pub(crate) fn from_isolate<'data>(isolate: &v8::Isolate) -> Option<&'data RuntimeData> {
+        let data = isolate.get_data(RUNTIME_DATA_SLOT) as *const RuntimeData;
+
+        // SAFETY: This is safe since we know that the runtime data is stored in that slot
+        //         and the data is bound to the lifetime of this runtime.
+        unsafe { data.as_ref() }
+    }
+
+    /// Updates the counters of the runtime.
+    #[inline(always)]
+    pub(crate) fn count<F: FnOnce(&mut RuntimeCounters)>(&self, f: F) {
+        let mut counters = self.counters.get();
+        f(&mut counters);
+
+        #[cfg(feature = "metrics")]
+        crate::runtime_metrics::record_counters(&self.counters.get(), &counters);
+
+        self.counters.set(counters);
+    }
+}
+
+/// Lightweight counters of a runtime, that are always collected.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RuntimeCounters {
+    /// Number of compiled scripts. Scripts taken from the script cache are not compiled again.
+    pub scripts_compiled: u64,
+    /// Number of executed scripts.
+    pub executions: u64,
+    /// Number of exceptions, that were thrown by scripts and not caught.
+    pub exceptions_thrown: u64,
+    /// Number of calls of host functions.
+    pub host_calls: u64,
+    /// Number of microtask checkpoints the runtime performed.
+    pub microtask_checkpoints: u64,
+}
+
 use crate::{
-    error::{create_error_from_exception, Error},
-    extension::FunctionDeclaration,
-    traits::DeserializeOwned,
-    value::{new_string, NewStringType, Seal},
-    Extension, HeapStatistics, V8_INITIALIZATION,
+    async_tasks::{drive_until_settled, poll_tasks, AsyncTasks},
+    binding_interceptor::{install_interceptor, BindingInterceptor},
+    breadcrumbs::Breadcrumbs,
+    bundle::Bundle,
+    class::ClassInstances,
+    code_verifier::{verify_source, CodeVerifier, ScriptKind, ScriptSource, VerifyError},
+    console::{install_console, ConsoleSink},
+    diagnostic::Diagnostic,
+    error::{
+        create_error_from_exception, create_type_error, Error, ErrorCode, ScriptError, TypeError,
+    },
+    event_loop::ScriptEventLoop,
+    execution_timing::{thread_cpu_time, ExecutionTiming, TimingCollector},
+    extension::{ExtensionFootprint, FunctionDeclaration},
+    global_statistics::Registration,
+    globals_snapshot::{self, GlobalsSnapshot},
+    lazy_global::{lazy_global_getter, LazyGlobal},
+    microtasks::{forget_rejection, promise_reject_callback, MicrotasksPolicy},
+    module_loader::{compile_module, resolve_module_callback, ModuleLoader, ModuleRegistry},
+    rate_limit::RateLimiter,
+    realm::Realm,
+    runtime_handle::RuntimeHandle,
+    script_cache::ScriptCache,
+    script_origin::ScriptOrigin,
+    serialization::deserialize_string_into,
+    sliced_execution::{SliceState, SlicedExecution, Slicer},
+    stack_sampler::{SlowScriptReport, StackSampling, Watchdog},
+    timers::Timers,
+    traits::{Deserialize, DeserializeOwned, Serialize},
+    value::{
+        new_string, ArrayBuffer, ArrayBufferView, NewStringType, Object, Seal, Unseal, Value,
+        ValueScope, WeakRefIntrinsics,
+    },
+    wasm::{WasmInstance, WasmValue},
+    Extension, HeapStatistics, ScriptCacheStatistics, V8_INITIALIZATION,
 };
 
 /// Configures a ECMAScript runtime.
 pub struct RuntimeOptions<STATE> {
-    /// Sets the initial size of the heap.
+    /// Sets the initial size of the heap. Must not be larger than the maximum size of the heap.
     pub initial_heap_size: usize,
-    /// Sets the maximum size of the heap.
+    /// Sets the maximum size of the heap. Must be at least 1 MiB.
     pub max_heap_size: usize,
     /// Enables the capturing of the current stack trace when an uncaught exception
     /// occurs and report it to the message listeners. Sets the limit of how many
@@ -27,6 +166,78 @@ pub struct RuntimeOptions<STATE> {
     pub capture_stack_trace_for_uncaught_exceptions: Option<i32>,
     /// Extensions add build-in functionality to a runtime.
     pub extensions: Vec<Extension<STATE>>,
+    /// Runs [`Runtime::reduce_memory()`] each time the runtime is marked as idle with
+    /// [`Runtime::set_idle()`].
+    ///
+    /// Useful for servers that host many runtimes that are idle most of the time.
+    pub memory_reducer: bool,
+    /// Limits how deep host functions can be nested (host → script → host). Exceeding the limit
+    /// throws a `RangeError` inside the script instead of risking to exhaust the native stack.
+    pub max_host_call_depth: Option<u32>,
+    /// Evaluates all classic scripts in strict mode, so that scripts can't rely on sloppy mode
+    /// behavior like implicitly creating globals (`x = 5`). Legacy scripts can opt out by using
+    /// [`Runtime::execute_sloppy()`].
+    ///
+    /// Column numbers of diagnostics in the first line of a script are shifted by the length of
+    /// the injected `"use strict"` directive. Script errors report the original position.
+    pub force_strict_mode: bool,
+    /// Sets how many compiled scripts are cached, so that the repeated execution of identical
+    /// scripts skips the compilation. Scripts are identified by the hash of their source and the
+    /// least recently used script is evicted first. A capacity of `0` disables the cache.
+    pub script_cache_capacity: usize,
+    /// Only accepts BigInts for 64 bit integer types (`i64` and `u64`) and numbers for smaller
+    /// integer types, mirroring the WebIDL semantics. By default numbers and BigInts are accepted
+    /// for all integer types, as long as the value is inside the range of the type.
+    ///
+    /// Applies to all host functions of the runtime, it can't be selected per function.
+    pub strict_bigints: bool,
+    /// Resolves and loads the modules, that are imported by other modules. Without a loader,
+    /// modules can only import modules that were compiled with [`Runtime::compile_module()`].
+    pub module_loader: Option<Box<dyn ModuleLoader>>,
+    /// Global properties, that are computed on their first access. Add them with
+    /// [`RuntimeOptions::lazy_global()`].
+    pub lazy_globals: Vec<LazyGlobal>,
+    /// Is called with the current heap limit when the heap is close to its limit and returns
+    /// the new heap limit. Returning a limit that is not larger than the current limit
+    /// terminates the running script, which then returns [`Error::HeapLimit`].
+    ///
+    /// Without a callback, the engine aborts the process once the heap limit is reached.
+    pub on_near_heap_limit: Option<Box<dyn FnMut(usize) -> usize>>,
+    /// Is called with the source of every classic script and ES module before it's compiled,
+    /// including the modules of a module loader and bundles. Returning an error rejects the
+    /// script with [`Error::Verification`], for example if its signature is invalid.
+    pub code_verifier: Option<Box<dyn Fn(&ScriptSource) -> Result<(), VerifyError>>>,
+    /// Samples the stack of scripts, that are executed with [`Runtime::execute_with_timeout()`]
+    /// and run longer than the threshold of the sampling. The samples are reported with
+    /// [`Runtime::slow_script_report()`].
+    pub stack_sampling: Option<StackSampling>,
+    /// Provides the `console` API (`log`, `debug`, `info`, `warn` and `error`) to scripts and
+    /// writes the formatted messages to the sink. Use [`crate::LogConsoleSink`] to write to the
+    /// `log` crate or [`crate::StdoutConsoleSink`] to write to stdout.
+    ///
+    /// Without a sink, the runtime doesn't provide the `console` API.
+    pub console: Option<Box<dyn ConsoleSink>>,
+    /// Registers fastcall functions only with their regular callback, so that the engine never
+    /// calls the fast path.
+    ///
+    /// Useful to bisect whether a crash or a wrong result originates from the fast path, for
+    /// example by running the test suite in both modes.
+    pub disable_fastcalls: bool,
+    /// Sets how many of the most recent host function calls are remembered and attached to
+    /// script errors as [`crate::error::ScriptError::host_calls`]. A capacity of `0` disables
+    /// the breadcrumbs.
+    pub host_call_breadcrumbs: usize,
+    /// Is called with the state after an execution was terminated, because it exceeded its
+    /// timeout, was terminated with [`RuntimeHandle::terminate_execution()`] or reached the heap
+    /// limit.
+    ///
+    /// A termination can interrupt a script between two host function calls, that must be
+    /// called together. The hook can restore the invariants of the state before the runtime is
+    /// used again.
+    pub on_terminated: Option<Box<dyn FnMut(&mut STATE)>>,
+    /// Decides when the microtasks of the runtime are run. Defaults to
+    /// [`MicrotasksPolicy::Explicit`].
+    pub microtasks_policy: MicrotasksPolicy,
 }
 
 impl<STATE> Default for RuntimeOptions<STATE> {
@@ -36,25 +247,829 @@ impl<STATE> Default for RuntimeOptions<STATE> {
             max_heap_size: 512 * 1024 * 1024, // 512 MiB
             capture_stack_trace_for_uncaught_exceptions: None,
             extensions: vec![],
+            memory_reducer: false,
+            max_host_call_depth: Some(128),
+            force_strict_mode: false,
+            script_cache_capacity: 0,
+            strict_bigints: false,
+            module_loader: None,
+            lazy_globals: vec![],
+            on_near_heap_limit: None,
+            code_verifier: None,
+            stack_sampling: None,
+            console: None,
+            disable_fastcalls: false,
+            host_call_breadcrumbs: 8,
+            on_terminated: None,
+            microtasks_policy: MicrotasksPolicy::Explicit,
         }
     }
 }
 
+impl<STATE> RuntimeOptions<STATE> {
+    /// Adds a global property, that is computed by the given function on the first access inside
+    /// a context. Useful for expensive constants, that shouldn't slow down the creation of every
+    /// runtime.
+    ///
+    /// The property is replaced by the computed value, so the function is only called once per
+    /// context.
+    pub fn lazy_global<T>(&mut self, name: &str, compute: fn() -> T) -> &mut Self
+    where
+        T: Serialize,
+    {
+        self.lazy_globals.push(LazyGlobal::new(name, compute));
+        self
+    }
+}
+
 /// The runtime that runs ECMAScript code inside the V8 engine.
 pub struct Runtime<STATE> {
     isolate: v8::OwnedIsolate,
     main_context: v8::Global<v8::Context>,
-    _closures: Box<[Arc<dyn Any>]>,
-    _state: Rc<RefCell<STATE>>,
+    realms: Vec<v8::Global<v8::Context>>,
+    /// The realm, that scripts are executed in. `None` executes scripts in the main context.
+    active_realm: Option<usize>,
+    /// The origin of the executed script. `None` executes anonymous scripts.
+    active_origin: Option<ScriptOrigin>,
+    closures: Vec<(String, Arc<dyn Any>)>,
+    rate_limiters: Vec<Arc<RateLimiter>>,
+    extension_footprints: Vec<ExtensionFootprint>,
+    state: Rc<RefCell<STATE>>,
+    runtime_data: Box<RuntimeData>,
+    memory_reducer: bool,
+    force_strict_mode: bool,
+    disable_fastcalls: bool,
+    script_cache: ScriptCache,
+    idle: bool,
+    registration: Registration,
+    stack_sampling: Option<StackSampling>,
+    slow_script_report: Option<SlowScriptReport>,
+    on_terminated: Option<Box<dyn FnMut(&mut STATE)>>,
+    promise_reject_callback: Option<Box<dyn FnMut(&ScriptError)>>,
 }
 
 impl<STATE> Drop for Runtime<STATE> {
     fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        for (name, references) in self.leaked_closures() {
+            log::warn!(
+                "Host function '{}' is still referenced {} time(s) after the runtime was dropped",
+                name,
+                references
+            );
+        }
+
         // We want to make sure that nothing will run inside the isolate, since
         // the pointer to the state inside the isolate and closures would be invalid
-        // after the drop (stored in slot STATE_DATA_SLOT).
+        // after the drop (stored in slot STATE_DATA_SLOT and RUNTIME_DATA_SLOT).
         self.isolate.terminate_execution();
+
+        // The global handles of the caches need to be released while the isolate is alive.
+        self.runtime_data.name_cache.borrow_mut().clear();
+        self.runtime_data.modules.borrow_mut().clear();
+        self.runtime_data.async_tasks.borrow_mut().clear();
+        self.runtime_data.timers.borrow_mut().clear();
+        self.runtime_data.yielded.borrow_mut().clear();
+        self.runtime_data.class_instances.borrow_mut().clear();
+        self.runtime_data.unhandled_rejections.borrow_mut().clear();
+        self.script_cache.clear();
+        self.realms.clear();
+    }
+}
+
+/// Deserializes the evaluated value of a script. Returns [`Error::UndefinedResult`] if the
+/// value is `undefined` and `T` can't be created from `undefined`.
+fn deserialize_result<'scope, T>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Result<T, Error>
+where
+    T: Deserialize<'scope>,
+{
+    T::deserialize(scope, value).map_err(|err| {
+        if value.is_undefined() {
+            Error::UndefinedResult
+        } else {
+            Error::Type(err)
+        }
+    })
+}
+
+/// Is called by the engine when the heap is close to its limit. Terminates the running script,
+/// if the callback of the runtime doesn't raise the limit.
+extern "C" fn near_heap_limit_callback(
+    data: *mut c_void,
+    current_heap_limit: usize,
+    _initial_heap_limit: usize,
+) -> usize {
+    // SAFETY: The data is the pointer of the isolate, that registered the callback.
+    let isolate = unsafe { &mut *(data as *mut v8::Isolate) };
+    let Some(runtime_data) = RuntimeData::from_isolate(isolate) else {
+        return current_heap_limit;
+    };
+
+    let heap_limit = match runtime_data.near_heap_limit.borrow_mut().as_mut() {
+        Some(callback) => callback(current_heap_limit),
+        None => current_heap_limit,
+    };
+
+    if heap_limit > current_heap_limit {
+        return heap_limit;
+    }
+
+    runtime_data.heap_limit_reached.set(true);
+    isolate.terminate_execution();
+
+    // The engine needs some headroom to unwind the terminated script.
+    current_heap_limit.saturating_mul(2)
+}
+
+/// Validates the heap limits, since the engine would abort the process on invalid limits.
+fn validate_heap_limits(initial_heap_size: usize, max_heap_size: usize) -> Result<(), Error> {
+    if max_heap_size < MIN_HEAP_SIZE {
+        return Err(Error::IsolateCreation(format!(
+            "The maximal heap size of {} bytes is smaller than the minimal heap size of {} bytes",
+            max_heap_size, MIN_HEAP_SIZE
+        )));
+    }
+
+    if initial_heap_size > max_heap_size {
+        return Err(Error::IsolateCreation(format!(
+            "The initial heap size of {} bytes is larger than the maximal heap size of {} bytes",
+            initial_heap_size, max_heap_size
+        )));
+    }
+
+    Ok(())
+}
+
+/// Evaluates the expression of the prototype of a namespace object. The prototype must be an
+/// object or `null`.
+fn evaluate_prototype<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    namespace: &str,
+    expression: &str,
+) -> Result<v8::Local<'scope, v8::Value>, Error> {
+    let try_catch_scope = &mut v8::TryCatch::new(scope);
+    let source = new_string(try_catch_scope, expression, NewStringType::Normal);
+
+    let Some(prototype) = v8::Script::compile(try_catch_scope, source, None)
+        .and_then(|script| script.run(try_catch_scope))
+    else {
+        let exception = try_catch_scope.exception();
+        let error = create_error_from_exception(try_catch_scope, exception);
+        return Err(Error::Extension(format!(
+            "Can't evaluate the prototype of namespace '{}': {}",
+            namespace, error
+        )));
+    };
+
+    if !prototype.is_object() && !prototype.is_null() {
+        return Err(Error::Extension(format!(
+            "The prototype of namespace '{}' must be an object or null",
+            namespace
+        )));
+    }
+
+    Ok(prototype)
+}
+
+/// Returns the object template of a nested namespace (e.g. `a.b`) inside the global template.
+/// Missing object templates are created.
+fn namespace_template<'scope>(
+    scope: &mut v8::HandleScope<'scope, ()>,
+    global_template: v8::Local<'scope, v8::ObjectTemplate>,
+    templates: &mut HashMap<String, v8::Local<'scope, v8::ObjectTemplate>>,
+    path: &str,
+) -> v8::Local<'scope, v8::ObjectTemplate> {
+    let mut template = global_template;
+    let mut end = 0;
+
+    for segment in path.split('.') {
+        end += segment.len();
+        let key = &path[..end];
+        end += 1;
+
+        template = match templates.get(key) {
+            Some(child) => *child,
+            None => {
+                let child = v8::ObjectTemplate::new(scope);
+                let name = new_string(scope, segment, NewStringType::Normal);
+                template.set(name.into(), child.into());
+                templates.insert(key.to_string(), child);
+                child
+            }
+        };
+    }
+
+    template
+}
+
+/// Returns the nested object (e.g. `a.b`) inside the parent object. Missing objects are created.
+fn nested_object<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    parent: v8::Local<'scope, v8::Object>,
+    path: &str,
+) -> v8::Local<'scope, v8::Object> {
+    let mut object = parent;
+
+    for segment in path.split('.') {
+        let key = new_string(scope, segment, NewStringType::Normal);
+        object = match object
+            .get(scope, key.into())
+            .and_then(|value| v8::Local::<v8::Object>::try_from(value).ok())
+        {
+            Some(child) => child,
+            None => {
+                let child = v8::Object::new(scope);
+                object.set(scope, key.into(), child.into());
+                child
+            }
+        };
+    }
+
+    object
+}
+
+/// Records the footprints of the extensions, before they are registered.
+fn extension_footprints<STATE>(extensions: &[Extension<STATE>]) -> Vec<ExtensionFootprint> {
+    // The object templates of nested namespaces are shared between the global extensions and
+    // are attributed to the first extension that uses them.
+    let mut namespace_templates = HashSet::new();
+
+    extensions
+        .iter()
+        .map(|extension| {
+            let templates = match extension.namespace {
+                None => {
+                    let mut templates = extension.declarations.len();
+                    for path in extension.declarations.keys() {
+                        for (index, _) in path.match_indices('.') {
+                            if namespace_templates.insert(path[..index].to_string()) {
+                                templates += 1;
+                            }
+                        }
+                    }
+                    templates
+                }
+                Some(_) => extension
+                    .declarations
+                    .values()
+                    .filter(|declaration| {
+                        matches!(
+                            declaration,
+                            FunctionDeclaration::Fastcall { .. }
+                                | FunctionDeclaration::Class { .. }
+                        )
+                    })
+                    .count(),
+            };
+
+            ExtensionFootprint {
+                name: extension
+                    .namespace
+                    .clone()
+                    .unwrap_or_else(|| "global".to_string()),
+                functions: extension.declarations.len(),
+                closures: extension.closures.len(),
+                closure_bytes: extension
+                    .closures
+                    .iter()
+                    .map(|(_, closure)| std::mem::size_of_val(&**closure))
+                    .sum(),
+                templates,
+                script_bytes: extension.prototype.as_ref().map_or(0, |p| p.len()),
+            }
+        })
+        .collect()
+}
+
+/// Orders the extensions, so that every extension is registered after the extensions it
+/// depends on. Extensions without dependencies keep their relative order.
+fn order_extensions<STATE>(
+    extensions: Vec<Extension<STATE>>,
+) -> Result<Vec<Extension<STATE>>, Error> {
+    fn extension_name<STATE>(extension: &Extension<STATE>) -> &str {
+        extension.namespace.as_deref().unwrap_or("global")
+    }
+
+    for extension in extensions.iter() {
+        for required in extension.requires.iter() {
+            if !extensions
+                .iter()
+                .any(|e| e.namespace.as_deref() == Some(required))
+            {
+                return Err(Error::Extension(format!(
+                    "Extension '{}' requires the missing extension '{}'",
+                    extension_name(extension),
+                    required
+                )));
+            }
+        }
+    }
+
+    let mut pending: Vec<Option<Extension<STATE>>> = extensions.into_iter().map(Some).collect();
+    let mut ordered = Vec::with_capacity(pending.len());
+
+    while ordered.len() < pending.len() {
+        let ready = pending.iter().position(|extension| {
+            extension.as_ref().map_or(false, |extension| {
+                extension.requires.iter().all(|required| {
+                    !pending.iter().flatten().any(|e| {
+                        e.namespace.as_deref() == Some(required) && !std::ptr::eq(e, extension)
+                    })
+                })
+            })
+        });
+
+        let Some(index) = ready else {
+            let names: Vec<&str> = pending.iter().flatten().map(extension_name).collect();
+            return Err(Error::Extension(format!(
+                "Extensions have cyclic dependencies: {}",
+                names.join(", ")
+            )));
+        };
+
+        if let Some(extension) = pending[index].take() {
+            ordered.push(extension);
+        }
+    }
+
+    Ok(ordered)
+}
+
+/// The smallest maximal heap size that is accepted when creating a runtime.
+const MIN_HEAP_SIZE: usize = 1024 * 1024; // 1 MiB
+
+/// The capacity of the scratch buffer in UTF-16 code units, that is retained between string
+/// conversions, if none was configured with [`Runtime::with_scratch()`].
+const DEFAULT_SCRATCH_CAPACITY: usize = 4 * 1024;
+
+/// Prefix that is injected into scripts to evaluate them in strict mode. The `void 0` ends the
+/// directive prologue, so that the directive doesn't become the completion value of the script.
+pub(crate) const STRICT_MODE_PREFIX: &str = "'use strict';void 0;";
+
+/// Returns the source without a leading UTF-8 BOM and shebang line and with the strict mode
+/// directive injected, if requested.
+///
+/// The shebang line itself is removed, but its line break is kept, so that the line numbers of
+/// errors still match the original source.
+fn prepare_source(source: &str, strict: bool) -> Cow<'_, str> {
+    let source = source.strip_prefix('\u{FEFF}').unwrap_or(source);
+    let source = match source.starts_with("#!") {
+        true => &source[source.find('\n').unwrap_or(source.len())..],
+        false => source,
+    };
+
+    if strict {
+        Cow::Owned(format!("{}{}", STRICT_MODE_PREFIX, source))
+    } else {
+        Cow::Borrowed(source)
+    }
+}
+
+/// The number of UTF-16 code units that are converted at once when writing a string result.
+const WRITE_CHUNK_LENGTH: usize = 64 * 1024;
+
+/// Writes a string, array buffer or array buffer view directly to the writer and returns the
+/// number of bytes written. Strings are written as UTF-8 in chunks, unpaired surrogates are
+/// replaced with the replacement character (U+FFFD).
+fn write_value<'scope, W>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+    writer: &mut W,
+) -> Result<usize, Error>
+where
+    W: Write + ?Sized,
+{
+    if let Ok(buffer) = ArrayBuffer::try_from(value) {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        writer.write_all(buffer.as_ref()).map_err(Error::Io)?;
+        return Ok(buffer.len());
+    }
+
+    if let Ok(view) = ArrayBufferView::try_from(value) {
+        let Some(buffer) = view.buffer(scope).filter(|_| view.byte_length() > 0) else {
+            return Ok(0);
+        };
+        let start = view.byte_offset();
+        let end = start + view.byte_length();
+        writer
+            .write_all(&buffer.as_ref()[start..end])
+            .map_err(Error::Io)?;
+        return Ok(end - start);
+    }
+
+    let Ok(string) = v8::Local::<v8::String>::try_from(value.unseal()) else {
+        return Err(Error::Type(create_type_error(
+            ErrorCode::ArgumentType,
+            "Value is not a string or a typed array",
+            scope,
+            &value,
+        )));
+    };
+
+    let scope = scope.unseal();
+    let length = string.length();
+    let mut units = vec![0u16; WRITE_CHUNK_LENGTH.min(length)];
+    let mut bytes = Vec::with_capacity(units.len() * 3);
+    let mut written = 0;
+    let mut start = 0;
+
+    while start < length {
+        let mut count = string.write(
+            scope,
+            &mut units,
+            start,
+            v8::WriteOptions::NO_NULL_TERMINATION,
+        );
+        if count == 0 {
+            break;
+        }
+
+        // Don't split surrogate pairs between two chunks.
+        if start + count < length && count > 1 && (0xD800..0xDC00).contains(&units[count - 1]) {
+            count -= 1;
+        }
+
+        bytes.clear();
+        for c in char::decode_utf16(units[..count].iter().copied()) {
+            let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+            let mut buffer = [0u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+        }
+        writer.write_all(&bytes).map_err(Error::Io)?;
+
+        written += bytes.len();
+        start += count;
+    }
+
+    Ok(written)
+}
+
+/// Resolves an existing object by its path (e.g. `a.b.c`), starting from the global object.
+fn resolve_object<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    path: &str,
+) -> Result<v8::Local<'scope, v8::Object>, Error> {
+    let context = scope.get_current_context();
+    let mut object = context.global(scope);
+
+    for name in path.split('.') {
+        let key = new_string(scope, name, NewStringType::Normal);
+        object = object
+            .get(scope, key.into())
+            .and_then(|value| v8::Local::<v8::Object>::try_from(value).ok())
+            .ok_or_else(|| Error::Extension(format!("Can't find the object '{}'", path)))?;
+    }
+
+    Ok(object)
+}
+
+/// Returns the export with the given name of a WebAssembly instance, if it has the expected
+/// type.
+fn resolve_export<'scope, T>(
+    scope: &mut v8::HandleScope<'scope>,
+    object: v8::Local<'scope, v8::Object>,
+    name: &str,
+) -> Result<v8::Local<'scope, T>, Error>
+where
+    v8::Local<'scope, T>: TryFrom<v8::Local<'scope, v8::Value>>,
+{
+    let key = new_string(scope, name, NewStringType::Normal);
+    object
+        .get(scope, key.into())
+        .and_then(|value| v8::Local::<T>::try_from(value).ok())
+        .ok_or_else(|| {
+            Error::Type(TypeError {
+                code: ErrorCode::ArgumentValue,
+                msg: format!(
+                    "The WebAssembly instance has no export '{}' of the expected type",
+                    name
+                ),
+            })
+        })
+}
+
+/// Creates a new context with the functions of the extensions. The closures and rate limiters
+/// of the extensions are moved into the given vectors, since they must be kept alive as long as
+/// the runtime exists.
+fn create_context<'scope, STATE>(
+    scope: &mut v8::HandleScope<'scope, ()>,
+    extensions: &mut [Extension<STATE>],
+    state_ptr: *mut c_void,
+    runtime_data: &RuntimeData,
+    disable_fastcalls: bool,
+    closures: &mut Vec<(String, Arc<dyn Any>)>,
+    rate_limiters: &mut Vec<Arc<RateLimiter>>,
+) -> Result<v8::Local<'scope, v8::Context>, Error> {
+    let global_template = v8::ObjectTemplate::new(scope);
+    let mut namespace_templates = HashMap::new();
+    let mut global_properties = Vec::new();
+
+    // Set the global functions.
+    for Extension {
+        declarations,
+        closures: extension_closures,
+        rate_limiters: extension_rate_limiters,
+        ..
+    } in extensions.iter_mut().filter(|e| e.namespace.is_none())
+    {
+        for (function_path, function_declaration) in declarations.drain() {
+            let (template, function_name) = match function_path.rsplit_once('.') {
+                Some((path, function_name)) => (
+                    namespace_template(scope, global_template, &mut namespace_templates, path),
+                    function_name,
+                ),
+                None => (global_template, function_path.as_str()),
+            };
+            let function_name = new_string(scope, function_name, NewStringType::Normal);
+
+            let function = match function_declaration {
+                FunctionDeclaration::Closure {
+                    cb_data,
+                    function_callback,
+                } => {
+                    let external = v8::External::new(scope, cb_data);
+                    v8::FunctionTemplate::builder_raw(function_callback)
+                        .data(external.into())
+                        .build(scope)
+                }
+                FunctionDeclaration::Static(function_callback) => {
+                    v8::FunctionTemplate::builder_raw(function_callback).build(scope)
+                }
+                FunctionDeclaration::Fastcall {
+                    fastcall,
+                    function_callback,
+                } => {
+                    let external = v8::External::new(scope, state_ptr);
+                    let builder =
+                        v8::FunctionTemplate::builder_raw(function_callback).data(external.into());
+                    if disable_fastcalls {
+                        builder.build(scope)
+                    } else {
+                        builder.build_fast(scope, &*fastcall, None)
+                    }
+                }
+                FunctionDeclaration::Class { cb_data, template } => template(scope, cb_data),
+                FunctionDeclaration::Property { getter, setter } => {
+                    // The functions of the accessors need the data of the closures, which
+                    // templates of accessors can't store, so they are defined on the context.
+                    global_properties.push((function_path, getter, setter));
+                    continue;
+                }
+            };
+
+            template.set(function_name.into(), function.into());
+        }
+
+        closures.append(extension_closures);
+        rate_limiters.append(extension_rate_limiters);
+    }
+
+    for lazy_global in runtime_data.lazy_globals.iter() {
+        let name = new_string(scope, &lazy_global.name, NewStringType::Normal);
+        global_template.set_accessor(name.into(), lazy_global_getter);
+    }
+
+    let global_context = v8::Context::new_from_template(scope, global_template);
+    let mut interceptors = Vec::new();
+    let global_context_scope = &mut v8::ContextScope::new(scope, global_context);
+    WeakRefIntrinsics::capture(global_context_scope);
+
+    if runtime_data.console.borrow().is_some() {
+        let global = global_context.global(global_context_scope);
+        install_console(global_context_scope, global);
+    }
+
+    #[cfg(feature = "hide-intl")]
+    {
+        let global = global_context.global(global_context_scope);
+        crate::icu::hide_intl(global_context_scope, global);
+    }
+
+    for (property_path, getter, setter) in global_properties {
+        let global = global_context.global(global_context_scope);
+        let (object, property_name) = match property_path.rsplit_once('.') {
+            Some((path, property_name)) => (
+                nested_object(global_context_scope, global, path),
+                property_name,
+            ),
+            None => (global, property_path.as_str()),
+        };
+        let property_name = new_string(global_context_scope, property_name, NewStringType::Normal);
+        install_property(global_context_scope, object, property_name, getter, setter)?;
+    }
+
+    // Set the global functions that are inside a namespace object.
+    for Extension {
+        namespace,
+        extending,
+        prototype,
+        interceptor,
+        declarations,
+        closures: extension_closures,
+        rate_limiters: extension_rate_limiters,
+        ..
+    } in extensions.iter_mut().filter(|e| e.namespace.is_some())
+    {
+        if let Some(namespace) = namespace {
+            // Namespaces are merged with the existing objects at their path, so that
+            // multiple extensions can share a namespace and nested namespaces like
+            // `a.b.c` don't overwrite `a` or `a.b`.
+            let namespace_object = match (*extending, prototype) {
+                (true, None) => resolve_object(global_context_scope, namespace)?,
+                (true, Some(_)) => {
+                    return Err(Error::Extension(format!(
+                        "The prototype of the extended object '{}' can't be changed",
+                        namespace
+                    )));
+                }
+                (false, None) => {
+                    let global = global_context.global(global_context_scope);
+                    nested_object(global_context_scope, global, namespace)
+                }
+                (false, Some(prototype)) => {
+                    let prototype = evaluate_prototype(global_context_scope, namespace, prototype)?;
+                    let global = global_context.global(global_context_scope);
+                    let object = nested_object(global_context_scope, global, namespace);
+                    object.set_prototype(global_context_scope, prototype);
+                    object
+                }
+            };
+
+            for (function_path, function_declaration) in declarations.drain() {
+                let (target_object, function_name) = match function_path.rsplit_once('.') {
+                    Some((path, function_name)) => (
+                        nested_object(global_context_scope, namespace_object, path),
+                        function_name,
+                    ),
+                    None => (namespace_object, function_path.as_str()),
+                };
+                let function_name =
+                    new_string(global_context_scope, function_name, NewStringType::Normal);
+
+                // Functions of extended objects must not shadow inherited properties.
+                let exists = if *extending {
+                    target_object.has(global_context_scope, function_name.into())
+                } else {
+                    target_object.has_own_property(global_context_scope, function_name.into())
+                };
+
+                if exists.unwrap_or(false) {
+                    return Err(Error::Extension(format!(
+                        "Property '{}.{}' already exists",
+                        namespace, function_path
+                    )));
+                }
+
+                let function = match function_declaration {
+                    FunctionDeclaration::Closure {
+                        cb_data,
+                        function_callback,
+                    } => {
+                        let external = v8::External::new(global_context_scope, cb_data);
+                        v8::Function::builder_raw(function_callback)
+                            .data(external.into())
+                            .build(global_context_scope)
+                            .ok_or_else(|| Error::Internal("Can't build function".to_string()))?
+                    }
+                    FunctionDeclaration::Static(function_callback) => {
+                        v8::Function::builder_raw(function_callback)
+                            .build(global_context_scope)
+                            .ok_or_else(|| Error::Internal("Can't build function".to_string()))?
+                    }
+                    FunctionDeclaration::Fastcall {
+                        fastcall,
+                        function_callback,
+                    } => {
+                        let external = v8::External::new(global_context_scope, state_ptr);
+                        let builder = v8::FunctionTemplate::builder_raw(function_callback)
+                            .data(external.into());
+                        let template = if disable_fastcalls {
+                            builder.build(global_context_scope)
+                        } else {
+                            builder.build_fast(global_context_scope, &*fastcall, None)
+                        };
+                        template
+                            .get_function(global_context_scope)
+                            .ok_or_else(|| Error::Internal("Can't build function".to_string()))?
+                    }
+                    FunctionDeclaration::Class { cb_data, template } => {
+                        template(global_context_scope, cb_data)
+                            .get_function(global_context_scope)
+                            .ok_or_else(|| Error::Internal("Can't build class".to_string()))?
+                    }
+                    FunctionDeclaration::Property { getter, setter } => {
+                        install_property(
+                            global_context_scope,
+                            target_object,
+                            function_name,
+                            getter,
+                            setter,
+                        )?;
+                        continue;
+                    }
+                };
+
+                target_object.set(global_context_scope, function_name.into(), function.into());
+            }
+
+            if let Some(callback) = interceptor.take() {
+                if interceptors.iter().any(|(n, _)| *n == *namespace) {
+                    return Err(Error::Extension(format!(
+                        "The namespace '{}' already has an interceptor",
+                        namespace
+                    )));
+                }
+                interceptors.push((namespace.clone(), callback));
+            }
+        }
+
+        closures.append(extension_closures);
+        rate_limiters.append(extension_rate_limiters);
+    }
+
+    // Nested namespaces are wrapped first, so that their parents are still the plain
+    // namespace objects and not proxies.
+    interceptors.sort_by_key(|(namespace, _)| std::cmp::Reverse(namespace.matches('.').count()));
+    for (namespace, callback) in interceptors {
+        let global = global_context.global(global_context_scope);
+        let (parent, name) = match namespace.rsplit_once('.') {
+            Some((path, name)) => (nested_object(global_context_scope, global, path), name),
+            None => (global, namespace.as_str()),
+        };
+        let object = resolve_object(global_context_scope, &namespace)?;
+
+        let interceptor = Arc::new(BindingInterceptor {
+            namespace: namespace.clone(),
+            callback,
+        });
+        install_interceptor(
+            global_context_scope,
+            parent,
+            name,
+            object,
+            Arc::as_ptr(&interceptor),
+        )?;
+        closures.push((namespace, interceptor as Arc<dyn Any>));
+    }
+
+    let mut breadcrumbs = runtime_data.breadcrumbs.borrow_mut();
+    for (name, closure) in closures.iter() {
+        breadcrumbs.register(Arc::as_ptr(closure) as *const c_void, name);
+    }
+
+    Ok(global_context)
+}
+
+/// Defines an accessor property on the object, whose getter and setter call the closures of a
+/// property of an extension.
+fn install_property<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    object: v8::Local<'scope, v8::Object>,
+    name: v8::Local<'scope, v8::String>,
+    getter: (*mut c_void, v8::FunctionCallback),
+    setter: Option<(*mut c_void, v8::FunctionCallback)>,
+) -> Result<(), Error> {
+    let mut build = |(cb_data, function_callback): (*mut c_void, v8::FunctionCallback)| {
+        let external = v8::External::new(scope, cb_data);
+        v8::Function::builder_raw(function_callback)
+            .data(external.into())
+            .build(scope)
+            .ok_or_else(|| Error::Internal("Can't build function".to_string()))
+    };
+
+    let getter = build(getter)?;
+    let setter = setter.map(build).transpose()?;
+    object.set_accessor_property(name.into(), Some(getter), setter, v8::NONE);
+
+    Ok(())
+}
+
+/// Validates the options of the extensions, that can't be applied to global extensions.
+fn validate_extensions<STATE>(extensions: &[Extension<STATE>]) -> Result<(), Error> {
+    if extensions
+        .iter()
+        .any(|e| e.namespace.is_none() && e.prototype.is_some())
+    {
+        return Err(Error::Extension(
+            "A global extension can't have a prototype".to_string(),
+        ));
+    }
+
+    if extensions
+        .iter()
+        .any(|e| e.namespace.is_none() && e.interceptor.is_some())
+    {
+        return Err(Error::Extension(
+            "A global extension can't intercept modifications".to_string(),
+        ));
     }
+
+    Ok(())
 }
 
 impl<STATE> Runtime<STATE> {
@@ -66,350 +1081,4293 @@ impl<STATE> Runtime<STATE> {
             return Err(Error::V8NotInitialized);
         }
 
+        options.extensions = order_extensions(std::mem::take(&mut options.extensions))?;
+        let extension_footprints = extension_footprints(&options.extensions);
+
+        validate_heap_limits(options.initial_heap_size, options.max_heap_size)?;
+
         let mut config = v8::CreateParams::default();
         config = config.heap_limits(options.initial_heap_size, options.max_heap_size);
 
         let mut runtime_closures = Vec::default();
+        let mut runtime_rate_limiters = Vec::default();
         let state = Rc::new(RefCell::new(state));
         let state_ptr = Rc::as_ptr(&state) as *const RefCell<STATE> as *mut c_void;
+        let runtime_data = Box::new(RuntimeData {
+            host_call_depth: Cell::new(0),
+            max_host_call_depth: options.max_host_call_depth,
+            name_cache: RefCell::new(HashMap::new()),
+            conversion_errors: RefCell::new(HashMap::new()),
+            scratch: RefCell::new(Vec::new()),
+            scratch_capacity: Cell::new(DEFAULT_SCRATCH_CAPACITY),
+            extension_data: RefCell::new(HashMap::new()),
+            counters: Cell::new(RuntimeCounters::default()),
+            strict_bigints: options.strict_bigints,
+            module_loader: options.module_loader.take(),
+            modules: RefCell::new(ModuleRegistry::default()),
+            lazy_globals: std::mem::take(&mut options.lazy_globals),
+            near_heap_limit: RefCell::new(options.on_near_heap_limit.take()),
+            heap_limit_reached: Cell::new(false),
+            code_verifier: options.code_verifier.take(),
+            async_tasks: RefCell::new(AsyncTasks::default()),
+            timers: RefCell::new(Timers::default()),
+            timing: Cell::new(None),
+            yielded: RefCell::new(Vec::new()),
+            console: RefCell::new(options.console.take()),
+            class_instances: ClassInstances::default(),
+            breadcrumbs: RefCell::new(Breadcrumbs::new(options.host_call_breadcrumbs)),
+            unhandled_rejections: RefCell::new(Vec::new()),
+        });
+        let runtime_data_ptr = &*runtime_data as *const RuntimeData as *mut c_void;
 
-        let mut isolate = v8::Isolate::new(config);
+        // The engine aborts the process if it can't reserve the memory of the heap. We can only
+        // catch the failures that are reported by the V8 crate.
+        let mut isolate =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| v8::Isolate::new(config)))
+                .map_err(|err| {
+                    let reason = err
+                        .downcast_ref::<&str>()
+                        .map(|reason| reason.to_string())
+                        .or_else(|| err.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "Unknown reason".to_string());
+                    Error::IsolateCreation(reason)
+                })?;
+
+        if runtime_data.near_heap_limit.borrow().is_some() {
+            let isolate_ptr = &mut *isolate as *mut v8::Isolate as *mut c_void;
+            isolate.add_near_heap_limit_callback(near_heap_limit_callback, isolate_ptr);
+        }
 
         if let Some(frame_limit) = options.capture_stack_trace_for_uncaught_exceptions {
             isolate.set_capture_stack_trace_for_uncaught_exceptions(true, frame_limit.max(0))
         }
 
-        // TODO Test how namespaces are overwritten. Also support "nested" namespaces like "a.b.c".
+        isolate.set_microtasks_policy(options.microtasks_policy.into());
+
+        validate_extensions(&options.extensions)?;
+
+        let disable_fastcalls = options.disable_fastcalls;
         let main_context = {
             let isolate_scope = &mut v8::HandleScope::new(&mut isolate);
             isolate_scope.set_data(STATE_DATA_SLOT, state_ptr);
+            isolate_scope.set_data(RUNTIME_DATA_SLOT, runtime_data_ptr);
 
-            let global_template = v8::ObjectTemplate::new(isolate_scope);
-
-            // Set the global functions.
-            for Extension {
-                declarations,
-                closures,
-                ..
-            } in options
-                .extensions
-                .iter_mut()
-                .filter(|e| e.namespace.is_none())
-            {
-                for (function_name, function_declaration) in declarations.drain() {
-                    let function_name =
-                        new_string(isolate_scope, function_name, NewStringType::Normal);
-
-                    let function = match function_declaration {
-                        FunctionDeclaration::Closure {
-                            cb_data,
-                            function_callback,
-                        } => {
-                            let external = v8::External::new(isolate_scope, cb_data);
-                            v8::FunctionTemplate::builder_raw(function_callback)
-                                .data(external.into())
-                                .build(isolate_scope)
-                        }
-                        FunctionDeclaration::Static(function_callback) => {
-                            v8::FunctionTemplate::builder_raw(function_callback)
-                                .build(isolate_scope)
-                        }
-                        FunctionDeclaration::Fastcall {
-                            fastcall,
-                            function_callback,
-                        } => {
-                            let external = v8::External::new(isolate_scope, state_ptr);
-                            v8::FunctionTemplate::builder_raw(function_callback)
-                                .data(external.into())
-                                .build_fast(isolate_scope, &*fastcall, None)
-                        }
-                    };
-
-                    global_template.set(function_name.into(), function.into());
-                }
-
-                runtime_closures.append(closures);
-            }
-
-            let global_context = v8::Context::new_from_template(isolate_scope, global_template);
-            let global_context_scope = &mut v8::ContextScope::new(isolate_scope, global_context);
-
-            // Set the global functions that are inside a namespace object.
-            for Extension {
-                namespace,
-                declarations,
-                closures,
-                ..
-            } in options
-                .extensions
-                .iter_mut()
-                .filter(|e| e.namespace.is_some())
-            {
-                if let Some(namespace) = namespace {
-                    let namespace_name =
-                        new_string(global_context_scope, namespace, NewStringType::Normal);
-                    let namespace_object = v8::Object::new(global_context_scope);
-
-                    for (function_name, function_declaration) in declarations.drain() {
-                        let function_name =
-                            new_string(global_context_scope, function_name, NewStringType::Normal);
-
-                        let function = match function_declaration {
-                            FunctionDeclaration::Closure {
-                                cb_data,
-                                function_callback,
-                            } => {
-                                let external = v8::External::new(global_context_scope, cb_data);
-                                v8::Function::builder_raw(function_callback)
-                                    .data(external.into())
-                                    .build(global_context_scope)
-                                    .ok_or_else(|| {
-                                        Error::Internal("Can't build function".to_string())
-                                    })?
-                            }
-                            FunctionDeclaration::Static(function_callback) => {
-                                v8::Function::builder_raw(function_callback)
-                                    .build(global_context_scope)
-                                    .ok_or_else(|| {
-                                        Error::Internal("Can't build function".to_string())
-                                    })?
-                            }
-                            FunctionDeclaration::Fastcall {
-                                fastcall,
-                                function_callback,
-                            } => {
-                                let external = v8::External::new(global_context_scope, state_ptr);
-                                v8::FunctionTemplate::builder_raw(function_callback)
-                                    .data(external.into())
-                                    .build_fast(global_context_scope, &*fastcall, None)
-                                    .get_function(global_context_scope)
-                                    .ok_or_else(|| {
-                                        Error::Internal("Can't build function".to_string())
-                                    })?
-                            }
-                        };
-
-                        namespace_object.set(
-                            global_context_scope,
-                            function_name.into(),
-                            function.into(),
-                        );
-                    }
-
-                    global_context.global(global_context_scope).set(
-                        global_context_scope,
-                        namespace_name.into(),
-                        namespace_object.into(),
-                    );
-                }
-
-                runtime_closures.append(closures);
-            }
-
-            v8::Global::new(global_context_scope, global_context)
+            let context = create_context(
+                isolate_scope,
+                &mut options.extensions,
+                state_ptr,
+                &runtime_data,
+                disable_fastcalls,
+                &mut runtime_closures,
+                &mut runtime_rate_limiters,
+            )?;
+            v8::Global::new(isolate_scope, context)
         };
 
-        let runtime = Self {
+        let mut runtime = Self {
             isolate,
             main_context,
-            _closures: runtime_closures.into_boxed_slice(),
-            _state: state,
+            realms: Vec::new(),
+            active_realm: None,
+            active_origin: None,
+            closures: runtime_closures,
+            rate_limiters: runtime_rate_limiters,
+            extension_footprints,
+            state,
+            runtime_data,
+            memory_reducer: options.memory_reducer,
+            force_strict_mode: options.force_strict_mode,
+            disable_fastcalls,
+            script_cache: ScriptCache::new(options.script_cache_capacity),
+            idle: false,
+            registration: Registration::new(),
+            stack_sampling: options.stack_sampling,
+            slow_script_report: None,
+            on_terminated: options.on_terminated.take(),
+            promise_reject_callback: None,
         };
+        runtime.heap_statistics();
 
         Ok(runtime)
     }
 
-    // TODO add support for compiling modules.
     // TODO add support for creating a new runtime from a snapshot
 
     /// Executes the ECMAScript as a classic script inside the runtime and returns the evaluated value.
+    ///
+    /// Returns [`Error::UndefinedResult`] if the script evaluates to `undefined` and `T` can't be
+    /// created from `undefined`. Use `Option<T>` or [`Runtime::execute_or_default()`] if the
+    /// script is allowed to not return a value.
+    ///
+    /// Returns [`Error::Terminated`] if the script was terminated with
+    /// [`RuntimeHandle::terminate_execution()`].
     pub fn execute<T, SOURCE>(&mut self, source: SOURCE) -> Result<T, Error>
     where
         T: DeserializeOwned,
         SOURCE: AsRef<str>,
     {
-        let source = source.as_ref();
+        self.execute_with(source, self.force_strict_mode, |scope, value| {
+            deserialize_result(scope, value)
+        })
+    }
 
-        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
-        let source = new_string(scope, source, NewStringType::Normal);
+    /// Executes the ECMAScript as a classic script inside the runtime and returns the evaluated
+    /// value together with the timing of the call.
+    ///
+    /// Unlike measuring the call from the outside, the timing separates the compilation from the
+    /// execution and reports the time spent inside host functions.
+    pub fn execute_timed<T, SOURCE>(
+        &mut self,
+        source: SOURCE,
+    ) -> (Result<T, Error>, ExecutionTiming)
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        self.runtime_data
+            .timing
+            .set(Some(TimingCollector::default()));
+        let cpu_start = thread_cpu_time();
+        let start = Instant::now();
 
-        let try_catch_scope = &mut v8::TryCatch::new(scope);
+        let result = self.execute(source);
 
-        let Some(script) = v8::Script::compile(try_catch_scope, source, None) else {
-            let exception = try_catch_scope.exception();
-            return Err(create_error_from_exception(try_catch_scope, exception));
-        };
+        let wall = start.elapsed();
+        let cpu = cpu_start
+            .zip(thread_cpu_time())
+            .map(|(start, end)| end.saturating_sub(start));
+        let timing = self.runtime_data.timing.take().unwrap_or_default();
 
-        let Some(v8_value) = script.run(try_catch_scope) else {
-            let exception = try_catch_scope.exception();
-            return Err(create_error_from_exception(try_catch_scope, exception));
+        let timing = ExecutionTiming {
+            wall,
+            compile: timing.compile,
+            execute: wall.saturating_sub(timing.compile),
+            host_calls: timing.host_calls,
+            cpu,
         };
 
-        T::deserialize(try_catch_scope.seal(), v8_value.seal()).map_err(Error::Type)
-    }
-
-    /// Returns a collection of information about the heap of the engine.
-    pub fn heap_statistics(&mut self) -> HeapStatistics {
-        HeapStatistics::new(&mut self.isolate)
+        (result, timing)
     }
-}
+
+    /// Executes the ECMAScript as a classic script inside the runtime and returns the settled value,
+    /// if the script evaluates to a promise (e.g. the result of an async function).
+    ///
+    /// The microtask queue is drained and the futures of async host functions are driven until
+    /// the promise is settled. A rejected promise returns the rejection reason as a
+    /// [`Error::Script`]. Returns [`Error::PendingPromise`] if the promise is still pending
+    /// afterwards, because it waits for work that is driven by the host.
+    /// Values that are not a promise are returned like [`Runtime::execute()`] does.
+    ///
+    /// Top-level `await` is only supported inside modules.
+    pub fn execute_async<T, SOURCE>(&mut self, source: SOURCE) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        self.execute_with(source, self.force_strict_mode, |scope, value| {
+            let Ok(promise) = v8::Local::<v8::Promise>::try_from(value.unseal()) else {
+                return deserialize_result(scope, value);
+            };
+
+            scope.unseal().perform_microtask_checkpoint();
+            drive_until_settled(scope.unseal(), promise);
+
+            match promise.state() {
+                v8::PromiseState::Pending => Err(Error::PendingPromise),
+                v8::PromiseState::Rejected => {
+                    // The rejection is returned as the error, so it's not reported as unhandled.
+                    forget_rejection(scope.unseal(), promise);
+                    let reason = promise.result(scope.unseal());
+                    Err(create_error_from_exception(scope.unseal(), Some(reason)))
+                }
+                v8::PromiseState::Fulfilled => {
+                    let result = promise.result(scope.unseal());
+                    deserialize_result(scope, result.seal())
+                }
+            }
+        })
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime and returns the evaluated
+    /// value. The execution is terminated if it takes longer than the given timeout, which
+    /// returns [`Error::Terminated`].
+    ///
+    /// A watchdog thread terminates the execution, so that even endless loops of untrusted
+    /// scripts are stopped. The runtime stays usable after a termination.
+    ///
+    /// If [`RuntimeOptions::stack_sampling`] is configured, the watchdog also samples the stack
+    /// of the script once it runs longer than the threshold. The samples are reported with
+    /// [`Runtime::slow_script_report()`].
+    pub fn execute_with_timeout<T, SOURCE>(
+        &mut self,
+        source: SOURCE,
+        timeout: Duration,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        let watchdog = Watchdog::start(
+            self.isolate.thread_safe_handle(),
+            timeout,
+            self.stack_sampling,
+        );
+
+        let result = self.execute_with(source, self.force_strict_mode, |scope, value| {
+            deserialize_result(scope, value)
+        });
+
+        let watchdog = watchdog.finish()?;
+        self.slow_script_report = watchdog.report;
+
+        if watchdog.terminated {
+            // The termination could be requested after the script finished, so it needs to be
+            // canceled in any case to not terminate the next execution.
+            self.isolate.cancel_terminate_execution();
+            return Err(Error::Terminated);
+        }
+
+        result
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime in slices and returns the
+    /// evaluated value. Once the script ran for the budget of a slice, it's interrupted and the
+    /// callback is called with the progress of the execution. The script continues with the next
+    /// slice once the callback returns.
+    ///
+    /// Lets a single threaded host spread a heavy script over multiple frames, for example by
+    /// rendering a frame inside the callback. The callback runs on the stack of the script, so
+    /// the runtime itself can't be used inside it. The time spent inside the callback doesn't
+    /// count towards the budget of the next slice.
+    ///
+    /// Calling [`SlicedExecution::terminate()`] inside the callback terminates the execution,
+    /// which returns [`Error::Terminated`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use kopi::{initialize_with_defaults, Runtime, RuntimeOptions};
+    ///
+    /// initialize_with_defaults();
+    ///
+    /// let mut runtime = Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+    ///
+    /// let mut frames = 0;
+    /// let val: f64 = runtime
+    ///     .execute_sliced(
+    ///         "let x = 0; for (let i = 0; i < 1e7; i++) { x += Math.sqrt(i); } x",
+    ///         Duration::from_millis(2),
+    ///         |_| frames += 1,
+    ///     )
+    ///     .expect("Can't execute code");
+    ///
+    /// assert!(val > 0.0);
+    /// ```
+    pub fn execute_sliced<T, SOURCE, F>(
+        &mut self,
+        source: SOURCE,
+        budget_per_slice: Duration,
+        mut between_slices: F,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+        F: FnMut(&mut SlicedExecution),
+    {
+        let mut state = SliceState::new(&mut between_slices);
+        let slicer = Slicer::start(
+            self.isolate.thread_safe_handle(),
+            budget_per_slice,
+            &mut state,
+        );
+
+        let result = self.execute_with(source, self.force_strict_mode, |scope, value| {
+            deserialize_result(scope, value)
+        });
+
+        slicer.finish()?;
+        result
+    }
+
+    /// Runs all pending microtasks (e.g. promise reactions) and reports the unhandled promise
+    /// rejections afterwards.
+    ///
+    /// The runtime performs a checkpoint after every execution, so explicit checkpoints are only
+    /// needed for promises, that were settled outside of an execution.
+    pub fn perform_microtask_checkpoint(&mut self) {
+        self.isolate.perform_microtask_checkpoint();
+        self.runtime_data.count(|c| c.microtask_checkpoints += 1);
+        self.report_unhandled_rejections();
+    }
+
+    /// Sets the callback, that is called with the error of every promise, that was rejected
+    /// without a handler.
+    ///
+    /// A rejection is only reported, if the promise still has no handler after the next
+    /// microtask checkpoint, so that handlers attached in the same tick are honored. Rejections,
+    /// that are already returned as the error of [`Runtime::execute_async()`] or
+    /// [`Runtime::execute_module()`], are not reported.
+    pub fn set_promise_reject_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(&ScriptError) + 'static,
+    {
+        self.isolate
+            .set_promise_reject_callback(promise_reject_callback);
+        self.promise_reject_callback = Some(Box::new(callback));
+    }
+
+    /// Calls the promise reject callback with the rejections, that are still unhandled.
+    fn report_unhandled_rejections(&mut self) {
+        let rejections = std::mem::take(&mut *self.runtime_data.unhandled_rejections.borrow_mut());
+        let Some(callback) = self.promise_reject_callback.as_mut() else {
+            return;
+        };
+        if rejections.is_empty() {
+            return;
+        }
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        for (_, reason) in rejections {
+            let reason = v8::Local::new(scope, reason);
+            if let Error::Script(script_error) = create_error_from_exception(scope, Some(reason)) {
+                callback(&script_error);
+            }
+        }
+    }
+
+    /// Polls the futures of async host functions once without blocking, settles the promises
+    /// of the ready futures and runs the microtasks afterwards. Returns `true` if there are still
+    /// pending futures.
+    ///
+    /// Useful to integrate the runtime into the loop of the host, for example a game loop.
+    pub fn poll_event_loop(&mut self) -> bool {
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        poll_tasks(scope, &self.runtime_data);
+
+        scope.perform_microtask_checkpoint();
+        self.runtime_data.count(|c| c.microtask_checkpoints += 1);
+        self.report_unhandled_rejections();
+
+        !self.runtime_data.async_tasks.borrow().is_empty()
+    }
+
+    /// Drives the futures of async host functions until all of them are ready and runs the
+    /// microtasks of the settled promises. Blocks the current thread while no future can make
+    /// progress.
+    pub fn run_event_loop(&mut self) {
+        while self.poll_event_loop() {
+            self.runtime_data.async_tasks.borrow().wait();
+        }
+    }
+
+    /// Returns the report of the last execution with [`Runtime::execute_with_timeout()`], if it
+    /// ran longer than the threshold of [`RuntimeOptions::stack_sampling`].
+    pub fn slow_script_report(&self) -> Option<&SlowScriptReport> {
+        self.slow_script_report.as_ref()
+    }
+
+    /// Compiles the ECMAScript as an ES module with the given name, so that it can be imported
+    /// by other modules. The module is evaluated, once it's imported the first time.
+    pub fn compile_module<SOURCE>(&mut self, name: &str, source: SOURCE) -> Result<(), Error>
+    where
+        SOURCE: AsRef<str>,
+    {
+        self.compile_module_with_cache(name, source.as_ref(), None)
+    }
+
+    /// Compiles the ECMAScript as an ES module and consumes the code cache, if one is given.
+    fn compile_module_with_cache(
+        &mut self,
+        name: &str,
+        source: &str,
+        code_cache: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        verify_source(
+            &self.runtime_data,
+            &ScriptSource {
+                kind: ScriptKind::Module,
+                name: Some(name),
+                source,
+            },
+        )?;
+
+        let source = prepare_source(source, false);
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        self.runtime_data.count(|c| c.scripts_compiled += 1);
+        let Some(module) = compile_module(try_catch_scope, name, &source, code_cache) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        self.runtime_data
+            .modules
+            .borrow_mut()
+            .insert(try_catch_scope, name, module);
+
+        Ok(())
+    }
+
+    /// Executes the ECMAScript as an ES module with the given name and returns its namespace
+    /// object (the exports of the module).
+    ///
+    /// Imports are resolved with the modules that were compiled with
+    /// [`Runtime::compile_module()`] and with the [`crate::ModuleLoader`] of the
+    /// [`RuntimeOptions`]. Top-level `await` is supported, as long as the awaited promises settle
+    /// while the microtask queue is drained.
+    pub fn execute_module<T, SOURCE>(&mut self, name: &str, source: SOURCE) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        self.execute_module_with_cache(name, source.as_ref(), None)
+    }
+
+    /// Executes the ECMAScript as an ES module and consumes the code cache, if one is given.
+    fn execute_module_with_cache<T>(
+        &mut self,
+        name: &str,
+        source: &str,
+        code_cache: Option<&[u8]>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let result = self.run_module(name, source, code_cache);
+        let result = self.check_heap_limit(result);
+        let result = self.notify_terminated(result);
+
+        self.report_unhandled_rejections();
+        result
+    }
+
+    /// Compiles, instantiates and evaluates the ECMAScript as an ES module.
+    fn run_module<T>(
+        &mut self,
+        name: &str,
+        source: &str,
+        code_cache: Option<&[u8]>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        verify_source(
+            &self.runtime_data,
+            &ScriptSource {
+                kind: ScriptKind::Module,
+                name: Some(name),
+                source,
+            },
+        )?;
+
+        let source = prepare_source(source, false);
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        self.runtime_data.count(|c| c.scripts_compiled += 1);
+        let Some(module) = compile_module(try_catch_scope, name, &source, code_cache) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        self.runtime_data
+            .modules
+            .borrow_mut()
+            .insert(try_catch_scope, name, module);
+
+        if module.instantiate_module(try_catch_scope, resolve_module_callback) != Some(true) {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        }
+
+        self.runtime_data.count(|c| c.executions += 1);
+        let Some(result) = module.evaluate(try_catch_scope) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        try_catch_scope.perform_microtask_checkpoint();
+        self.runtime_data.count(|c| c.microtask_checkpoints += 1);
+
+        // Evaluating a module returns a promise, that settles once the top-level await finished.
+        if let Ok(promise) = v8::Local::<v8::Promise>::try_from(result) {
+            drive_until_settled(try_catch_scope, promise);
+
+            match promise.state() {
+                v8::PromiseState::Pending => return Err(Error::PendingPromise),
+                v8::PromiseState::Rejected => {
+                    self.runtime_data.count(|c| c.exceptions_thrown += 1);
+                    forget_rejection(try_catch_scope, promise);
+                    let reason = promise.result(try_catch_scope);
+                    return Err(create_error_from_exception(try_catch_scope, Some(reason)));
+                }
+                v8::PromiseState::Fulfilled => {}
+            }
+        }
+
+        let namespace = module.get_module_namespace();
+        deserialize_result(try_catch_scope.seal(), namespace.seal())
+    }
+
+    /// Loads the modules of the bundle and executes its entry module. Returns the namespace
+    /// object of the entry module (see [`Runtime::execute_module()`]).
+    ///
+    /// The modules of the bundle can import each other by their name. The code caches of the
+    /// bundle are consumed while the modules are compiled. Code caches that were created for a
+    /// different source or engine version are rejected by the engine and the module is compiled
+    /// without them.
+    ///
+    /// Bundles with snapshot data are rejected with [`Error::Bundle`], since a snapshot can only
+    /// be used while a runtime is created.
+    pub fn load_bundle<T>(&mut self, bundle: &Bundle) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        if bundle.snapshot().is_some() {
+            return Err(Error::Bundle(
+                "The bundle contains snapshot data, which can't be loaded into a runtime"
+                    .to_string(),
+            ));
+        }
+
+        let mut entry_source = None;
+        for (name, source) in bundle.modules() {
+            if name == bundle.entry() {
+                entry_source = Some(source);
+            } else {
+                self.compile_module_with_cache(name, source, bundle.code_cache(name))?;
+            }
+        }
+
+        let Some(entry_source) = entry_source else {
+            return Err(Error::Bundle("The entry module is missing".to_string()));
+        };
+
+        self.execute_module_with_cache(
+            bundle.entry(),
+            entry_source,
+            bundle.code_cache(bundle.entry()),
+        )
+    }
+
+    /// Reads and validates the bundle at the given path and loads it. See
+    /// [`Runtime::load_bundle()`].
+    pub fn load_bundle_file<T, P>(&mut self, path: P) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let bundle = Bundle::read(path)?;
+        self.load_bundle(&bundle)
+    }
+
+    /// Validates the given bundle data and loads it. See [`Runtime::load_bundle()`].
+    pub fn load_bundle_bytes<T>(&mut self, data: &[u8]) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let bundle = Bundle::from_bytes(data)?;
+        self.load_bundle(&bundle)
+    }
+
+    /// Captures the state of the global object, so that it can be restored with
+    /// [`Runtime::restore_globals()`]. See [`GlobalsSnapshot`] for what is captured.
+    ///
+    /// Useful to run many untrusted scripts against an identical global state, without
+    /// creating a new runtime for each script.
+    pub fn capture_globals(&mut self) -> Result<GlobalsSnapshot, Error> {
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+        let context = v8::Local::new(try_catch_scope, &self.main_context);
+        let global = context.global(try_catch_scope);
+
+        globals_snapshot::capture(try_catch_scope, global).ok_or_else(|| {
+            let exception = try_catch_scope.exception();
+            create_error_from_exception(try_catch_scope, exception)
+        })
+    }
+
+    /// Restores the state of the global object, that was captured with
+    /// [`Runtime::capture_globals()`]. Global properties that were added afterwards are removed.
+    pub fn restore_globals(&mut self, snapshot: &GlobalsSnapshot) -> Result<(), Error> {
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        globals_snapshot::restore(try_catch_scope, snapshot).ok_or_else(|| {
+            let exception = try_catch_scope.exception();
+            create_error_from_exception(try_catch_scope, exception)
+        })
+    }
+
+    /// Reserves a scratch buffer for the given number of UTF-16 code units, that is re-used when
+    /// converting strings, instead of allocating a new buffer for every conversion.
+    ///
+    /// Conversions of longer strings temporarily grow the buffer, which is shrunk back to the
+    /// given capacity afterwards. Useful for workloads that transfer large strings repeatedly,
+    /// for example templating. Combine it with [`Runtime::execute_into()`] to also re-use the
+    /// allocation of the result.
+    pub fn with_scratch(self, capacity: usize) -> Self {
+        self.runtime_data.scratch_capacity.set(capacity);
+
+        let mut scratch = self.runtime_data.scratch.borrow_mut();
+        scratch.clear();
+        scratch.shrink_to(capacity);
+        scratch.reserve_exact(capacity);
+        drop(scratch);
+
+        self
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime and writes the string
+    /// representation of the evaluated value into the target string, re-using its allocation.
+    pub fn execute_into<SOURCE>(&mut self, source: SOURCE, target: &mut String) -> Result<(), Error>
+    where
+        SOURCE: AsRef<str>,
+    {
+        self.execute_with(source, self.force_strict_mode, |scope, value| {
+            deserialize_string_into(scope, value, target).map_err(Error::Type)
+        })
+    }
+
+    /// Executes the ECMAScript as a classic script in sloppy mode inside the runtime and returns
+    /// the evaluated value, even if [`RuntimeOptions::force_strict_mode`] is enabled.
+    ///
+    /// Should only be used for legacy scripts that rely on sloppy mode.
+    pub fn execute_sloppy<T, SOURCE>(&mut self, source: SOURCE) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        self.execute_with(source, false, |scope, value| {
+            deserialize_result(scope, value)
+        })
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime and returns the evaluated value.
+    ///
+    /// Returns the default value of `T` if the script evaluates to `undefined`.
+    pub fn execute_or_default<T, SOURCE>(&mut self, source: SOURCE) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Default,
+        SOURCE: AsRef<str>,
+    {
+        self.execute_with(source, self.force_strict_mode, |scope, value| {
+            if value.is_undefined() {
+                Ok(T::default())
+            } else {
+                deserialize_result(scope, value)
+            }
+        })
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime and writes the evaluated
+    /// value directly into the writer. Returns the number of bytes written.
+    ///
+    /// The script must evaluate to a string, an `ArrayBuffer` or an `ArrayBufferView`
+    /// (e.g. a `Uint8Array`). Strings are written as UTF-8 in chunks, so that large results
+    /// don't need to be copied into an intermediate Rust string. Unpaired surrogates are
+    /// replaced with the replacement character (U+FFFD).
+    pub fn execute_to_writer<W, SOURCE>(
+        &mut self,
+        source: SOURCE,
+        writer: &mut W,
+    ) -> Result<usize, Error>
+    where
+        W: Write + ?Sized,
+        SOURCE: AsRef<str>,
+    {
+        self.execute_with(source, self.force_strict_mode, |scope, value| {
+            write_value(scope, value, writer)
+        })
+    }
+
+    /// Executes the ECMAScript as a classic script and converts the evaluated value with the
+    /// given function. Updates the statistics of the runtime registration afterwards.
+    fn execute_with<T, SOURCE, F>(
+        &mut self,
+        source: SOURCE,
+        strict: bool,
+        convert: F,
+    ) -> Result<T, Error>
+    where
+        SOURCE: AsRef<str>,
+        F: for<'scope> FnOnce(&mut ValueScope<'scope>, Value<'scope>) -> Result<T, Error>,
+    {
+        #[cfg(feature = "metrics")]
+        let start = Instant::now();
+
+        let result = self.run_script(source, strict, convert);
+        let result = self.check_heap_limit(result);
+        let result = self.notify_terminated(result);
+
+        #[cfg(feature = "metrics")]
+        crate::runtime_metrics::record_execution(start.elapsed());
+
+        self.isolate.perform_microtask_checkpoint();
+        self.runtime_data.count(|c| {
+            c.executions += 1;
+            c.microtask_checkpoints += 1;
+        });
+        self.report_unhandled_rejections();
+
+        self.heap_statistics();
+        result
+    }
+
+    /// Returns [`Error::HeapLimit`] if the execution was terminated because the heap reached its
+    /// limit. The termination is canceled, so that the runtime can be used again.
+    fn check_heap_limit<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if self.runtime_data.heap_limit_reached.replace(false) {
+            self.isolate.cancel_terminate_execution();
+            return Err(Error::HeapLimit);
+        }
+        result
+    }
+
+    /// Calls [`RuntimeOptions::on_terminated`] with the state, if the execution was terminated.
+    fn notify_terminated<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if !matches!(result, Err(Error::Terminated | Error::HeapLimit)) {
+            return result;
+        }
+
+        if let Some(on_terminated) = self.on_terminated.as_mut() {
+            match self.state.try_borrow_mut() {
+                Ok(mut state) => on_terminated(&mut state),
+                Err(_) => log::warn!("Can't call the termination hook, the state is borrowed"),
+            }
+        }
+        result
+    }
+
+    /// Executes the ECMAScript as a classic script and converts the evaluated value with the
+    /// given function.
+    fn run_script<T, SOURCE, F>(
+        &mut self,
+        source: SOURCE,
+        strict: bool,
+        convert: F,
+    ) -> Result<T, Error>
+    where
+        SOURCE: AsRef<str>,
+        F: for<'scope> FnOnce(&mut ValueScope<'scope>, Value<'scope>) -> Result<T, Error>,
+    {
+        verify_source(
+            &self.runtime_data,
+            &ScriptSource {
+                kind: ScriptKind::Script,
+                name: self
+                    .active_origin
+                    .as_ref()
+                    .map(|origin| origin.name.as_str()),
+                source: source.as_ref(),
+            },
+        )?;
+
+        let source = prepare_source(source.as_ref(), strict);
+
+        for rate_limiter in self.rate_limiters.iter() {
+            rate_limiter.reset_execute();
+        }
+
+        let context = match self.active_realm {
+            Some(index) => &self.realms[index],
+            None => &self.main_context,
+        };
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        // The compiled script keeps its origin, so scripts with an origin bypass the cache.
+        let use_cache = self.script_cache.is_enabled() && self.active_origin.is_none();
+        let hash = ScriptCache::hash(&source);
+        let cached = if use_cache {
+            self.script_cache.get(hash, &source)
+        } else {
+            None
+        };
+
+        let script = match cached {
+            Some(unbound_script) => {
+                let unbound_script = v8::Local::new(try_catch_scope, unbound_script);
+                unbound_script.bind_to_current_context(try_catch_scope)
+            }
+            None => {
+                let compile_start = Instant::now();
+                let source_string = new_string(try_catch_scope, &source, NewStringType::Normal);
+                self.runtime_data.count(|c| c.scripts_compiled += 1);
+                let origin = self
+                    .active_origin
+                    .as_ref()
+                    .map(|origin| origin.to_v8(try_catch_scope));
+                let script = v8::Script::compile(try_catch_scope, source_string, origin.as_ref());
+
+                if let Some(mut timing) = self.runtime_data.timing.get() {
+                    timing.compile += compile_start.elapsed();
+                    self.runtime_data.timing.set(Some(timing));
+                }
+
+                let Some(script) = script else {
+                    self.runtime_data.count(|c| c.exceptions_thrown += 1);
+                    let exception = try_catch_scope.exception();
+                    return Err(create_error_from_exception(try_catch_scope, exception));
+                };
+
+                if use_cache {
+                    let unbound_script = script.get_unbound_script(try_catch_scope);
+                    let unbound_script = v8::Global::new(try_catch_scope, unbound_script);
+                    self.script_cache
+                        .insert(hash, source.into_owned(), unbound_script);
+                }
+
+                script
+            }
+        };
+
+        let Some(v8_value) = script.run(try_catch_scope) else {
+            if try_catch_scope.has_terminated() {
+                try_catch_scope.cancel_terminate_execution();
+                return Err(Error::Terminated);
+            }
+
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        convert(try_catch_scope.seal(), v8_value.seal())
+    }
+
+    /// Executes the ECMAScript as a classic script with the given origin inside the runtime and
+    /// returns the evaluated value. See [`Runtime::execute()`].
+    ///
+    /// Script errors and stack traces report the name and the offsets of the origin instead of
+    /// `<anonymous>`. Scripts with an origin are not stored in the script cache.
+    pub fn execute_with_origin<T, SOURCE>(
+        &mut self,
+        source: SOURCE,
+        origin: &ScriptOrigin,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        self.active_origin = Some(origin.clone());
+        let result = self.execute(source);
+        self.active_origin = None;
+
+        result
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime and returns the evaluated value.
+    ///
+    /// The label is attached to the script errors of the execution and is logged, so that
+    /// failures can be attributed to the right entity (e.g. `"ai:goblin#42"`).
+    pub fn execute_labeled<T, SOURCE>(&mut self, label: &str, source: SOURCE) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        log::debug!("Executing script labeled '{}'", label);
+
+        self.execute(source).map_err(|mut err| {
+            if let Error::Script(script_error) = &mut err {
+                log::debug!("Script labeled '{}' failed: {}", label, script_error);
+                script_error.label = Some(label.to_string());
+            }
+            err
+        })
+    }
+
+    /// Compiles the ECMAScript as a classic script without executing it and returns the
+    /// diagnostics reported by the engine.
+    ///
+    /// An empty list means that the script compiled successfully. The engine stops parsing at
+    /// the first early error and doesn't report warnings while compiling, so the list currently
+    /// contains at most one diagnostic.
+    pub fn check<SOURCE>(&mut self, source: SOURCE) -> Vec<Diagnostic>
+    where
+        SOURCE: AsRef<str>,
+    {
+        self.check_source(source.as_ref(), None)
+    }
+
+    /// Compiles the ECMAScript as a classic script with the given origin without executing it
+    /// and returns the diagnostics reported by the engine. See [`Runtime::check()`].
+    ///
+    /// The lines and columns of the diagnostics include the offsets of the origin.
+    pub fn check_with_origin<SOURCE>(
+        &mut self,
+        source: SOURCE,
+        origin: &ScriptOrigin,
+    ) -> Vec<Diagnostic>
+    where
+        SOURCE: AsRef<str>,
+    {
+        self.check_source(source.as_ref(), Some(origin))
+    }
+
+    fn check_source(&mut self, source: &str, origin: Option<&ScriptOrigin>) -> Vec<Diagnostic> {
+        let strict = self.force_strict_mode;
+        let source = prepare_source(source, strict);
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let source = new_string(scope, source, NewStringType::Normal);
+
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+        let v8_origin = origin.map(|origin| origin.to_v8(try_catch_scope));
+
+        if v8::Script::compile(try_catch_scope, source, v8_origin.as_ref()).is_some() {
+            return Vec::new();
+        }
+
+        let Some(message) = try_catch_scope.message() else {
+            return Vec::new();
+        };
+        let mut diagnostic = Diagnostic::from_message(try_catch_scope, message);
+
+        // Hide the injected strict mode directive, so that the position matches the original
+        // source. The directive is injected into the first line of the script.
+        if strict {
+            let prefix_length = STRICT_MODE_PREFIX.len();
+            diagnostic.start_position = diagnostic.start_position.saturating_sub(prefix_length);
+            diagnostic.end_position = diagnostic.end_position.saturating_sub(prefix_length);
+
+            let first_line = origin.map_or(0, |origin| origin.line_offset.max(0) as usize) + 1;
+            if diagnostic.line == first_line {
+                diagnostic.start_column = diagnostic.start_column.saturating_sub(prefix_length);
+                diagnostic.end_column = diagnostic.end_column.saturating_sub(prefix_length);
+            }
+        }
+
+        vec![diagnostic]
+    }
+
+    /// Runs a final garbage collection and returns the names of the host functions, whose
+    /// closures are still referenced outside of the runtime, together with the number of the
+    /// outside references.
+    ///
+    /// The runtime is the only owner of the closures, so every other reference outlives the
+    /// runtime and points to a lifetime bug of the extension code.
+    #[cfg(debug_assertions)]
+    fn leaked_closures(&mut self) -> Vec<(String, usize)> {
+        // A full garbage collection runs the weak callbacks. Finalizers are posted as tasks to
+        // the platform, so all pending tasks need to run as well.
+        self.isolate.low_memory_notification();
+        let platform = v8::V8::get_current_platform();
+        while v8::Platform::pump_message_loop(&platform, &mut self.isolate, false) {}
+
+        self.closures
+            .iter()
+            .filter_map(|(name, closure)| {
+                let references = Arc::strong_count(closure) - 1;
+                (references > 0).then(|| (name.clone(), references))
+            })
+            .collect()
+    }
+
+    /// Returns the number of failed argument conversions per host function, keyed by the name
+    /// of the function as seen from inside a script.
+    ///
+    /// Only functions added as closures are tracked. Functions without any failed conversion
+    /// are not included.
+    pub fn conversion_error_stats(&self) -> HashMap<String, u64> {
+        let conversion_errors = self.runtime_data.conversion_errors.borrow();
+        self.closures
+            .iter()
+            .filter_map(|(name, closure)| {
+                let ptr = Arc::as_ptr(closure) as *const c_void;
+                conversion_errors
+                    .get(&ptr)
+                    .map(|&count| (name.clone(), count))
+            })
+            .collect()
+    }
+
+    /// Resets the counters of the failed argument conversions.
+    pub fn reset_conversion_error_stats(&mut self) {
+        self.runtime_data.conversion_errors.borrow_mut().clear();
+    }
+
+    /// Pauses the runtime until the returned guard is dropped.
+    ///
+    /// While the guard exists, no script, microtask, timer or async host function can run inside
+    /// the runtime, so the host can consistently read the state of the runtime and its globals
+    /// (for example to take a snapshot of a save game). The delays of the timers of the
+    /// [`crate::TimerExtension`] stop at the last poll before the pause and continue with the
+    /// first poll after it, so that timers don't expire because of the pause.
+    pub fn pause(&mut self) -> PauseGuard<'_, STATE> {
+        self.runtime_data.timers.borrow_mut().suspend();
+        PauseGuard { runtime: self }
+    }
+
+    /// Returns a collection of information about the heap of the engine.
+    pub fn heap_statistics(&mut self) -> HeapStatistics {
+        let statistics = HeapStatistics::new(&mut self.isolate);
+        self.registration.update(&statistics);
+
+        #[cfg(feature = "metrics")]
+        crate::runtime_metrics::record_heap_statistics(&statistics);
+
+        statistics
+    }
+
+    /// Runs the closure inside a value scope of the main context and returns its result.
+    ///
+    /// The closure receives the global object, so that values can be created with the
+    /// [`crate::value`] API, set as globals or read directly, without executing source code.
+    /// Values can't escape the closure, [`crate::value::PersistentValue`] can be used to keep
+    /// them alive. Exceptions thrown inside the closure are discarded and the pending microtasks
+    /// are run after the closure returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use kopi::{value::*, *};
+    ///
+    /// initialize_with_defaults();
+    ///
+    /// let mut runtime = Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+    ///
+    /// runtime.scope(|scope, global| {
+    ///     let key = String::new(scope, "answer", NewStringType::Internalized);
+    ///     let value = Integer::new_from_i32(scope, 42);
+    ///     global.set(scope, key.into(), value.into());
+    /// });
+    ///
+    /// let answer: i32 = runtime.execute("answer").expect("Can't execute code");
+    /// assert_eq!(answer, 42);
+    /// ```
+    pub fn scope<F, R>(&mut self, f: F) -> R
+    where
+        F: for<'scope> FnOnce(&mut ValueScope<'scope>, Object<'scope>) -> R,
+    {
+        let result = {
+            let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+            let try_catch_scope = &mut v8::TryCatch::new(scope);
+            let global = try_catch_scope
+                .get_current_context()
+                .global(try_catch_scope);
+            f(try_catch_scope.seal(), global.seal())
+        };
+
+        self.isolate.perform_microtask_checkpoint();
+        self.runtime_data.count(|c| c.microtask_checkpoints += 1);
+        self.report_unhandled_rejections();
+
+        result
+    }
+
+    /// Resumes the scripts, that yielded with `host.yield()` of the [`crate::HostExtension`], and
+    /// runs them until they yield again or finish. Returns the number of resumed yields.
+    pub fn resume(&mut self) -> usize {
+        let yielded = std::mem::take(&mut *self.runtime_data.yielded.borrow_mut());
+        if yielded.is_empty() {
+            return 0;
+        }
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        for resolver in yielded.iter() {
+            let resolver = v8::Local::new(scope, resolver);
+            let undefined = v8::undefined(scope);
+            resolver.resolve(scope, undefined.into());
+        }
+
+        scope.perform_microtask_checkpoint();
+        self.runtime_data.count(|c| c.microtask_checkpoints += 1);
+        self.report_unhandled_rejections();
+
+        yielded.len()
+    }
+
+    /// Returns `true` if scripts yielded with `host.yield()` and wait to be resumed.
+    pub fn is_suspended(&self) -> bool {
+        !self.runtime_data.yielded.borrow().is_empty()
+    }
+
+    /// Executes the ECMAScript as a classic script inside the runtime and serializes the evaluated
+    /// value into bytes using the structured clone algorithm.
+    ///
+    /// The value can be moved to another runtime or persisted and is restored with
+    /// [`Runtime::deserialize_value()`]. See [`Value::to_structured_bytes()`] for the supported
+    /// values.
+    pub fn serialize_value<SOURCE>(&mut self, source: SOURCE) -> Result<Vec<u8>, Error>
+    where
+        SOURCE: AsRef<str>,
+    {
+        self.execute_with(source, self.force_strict_mode, |scope, value| {
+            value.to_structured_bytes(scope).map_err(Error::Type)
+        })
+    }
+
+    /// Deserializes a value, that was serialized with [`Runtime::serialize_value()`], and sets
+    /// it as the global property with the given name.
+    pub fn deserialize_value(&mut self, name: &str, data: &[u8]) -> Result<(), Error> {
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let value =
+            Value::from_structured_bytes(try_catch_scope.seal(), data).map_err(Error::Type)?;
+        let key = new_string(try_catch_scope, name, NewStringType::Internalized);
+        let global = try_catch_scope
+            .get_current_context()
+            .global(try_catch_scope);
+
+        if global
+            .set(try_catch_scope, key.into(), value.unseal())
+            .is_none()
+        {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new realm with the given extensions.
+    ///
+    /// The realm has its own global object, that only contains the builtins and the functions
+    /// of the given extensions. Functions with state access the state of the runtime. Scripts
+    /// are executed inside the realm with [`Runtime::execute_in()`].
+    pub fn create_realm(&mut self, extensions: Vec<Extension<STATE>>) -> Result<Realm, Error> {
+        let mut extensions = order_extensions(extensions)?;
+        validate_extensions(&extensions)?;
+        let footprints = extension_footprints(&extensions);
+
+        let state_ptr = Rc::as_ptr(&self.state) as *const RefCell<STATE> as *mut c_void;
+        let scope = &mut v8::HandleScope::new(&mut self.isolate);
+        let context = create_context(
+            scope,
+            &mut extensions,
+            state_ptr,
+            &self.runtime_data,
+            self.disable_fastcalls,
+            &mut self.closures,
+            &mut self.rate_limiters,
+        )?;
+
+        self.realms.push(v8::Global::new(scope, context));
+        self.extension_footprints.extend(footprints);
+
+        Ok(Realm {
+            runtime: self.registration.id(),
+            index: self.realms.len() - 1,
+        })
+    }
+
+    /// Executes the ECMAScript as a classic script inside the realm and returns the evaluated
+    /// value. See [`Runtime::execute()`].
+    pub fn execute_in<T, SOURCE>(&mut self, realm: &Realm, source: SOURCE) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+        SOURCE: AsRef<str>,
+    {
+        self.check_realm(realm)?;
+
+        self.active_realm = Some(realm.index);
+        let result = self.execute(source);
+        self.active_realm = None;
+
+        result
+    }
+
+    /// Sets the security token of the realm, or of the main context if `realm` is `None`.
+    ///
+    /// Scripts can only access the global object of another realm, if both realms have the same
+    /// security token. Every realm starts with its own token, so realms can't access each other
+    /// by default.
+    pub fn set_security_token(&mut self, realm: Option<&Realm>, token: &str) -> Result<(), Error> {
+        let context = match realm {
+            Some(realm) => {
+                self.check_realm(realm)?;
+                &self.realms[realm.index]
+            }
+            None => &self.main_context,
+        };
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, context);
+
+        // Tokens are compared by identity, internalized strings with the same content are the
+        // same object.
+        let token = new_string(scope, token, NewStringType::Internalized);
+        scope.get_current_context().set_security_token(token.into());
+        Ok(())
+    }
+
+    /// Restores the default security token of the realm, or of the main context if `realm` is
+    /// `None`. See [`Runtime::set_security_token()`].
+    pub fn use_default_security_token(&mut self, realm: Option<&Realm>) -> Result<(), Error> {
+        let context = match realm {
+            Some(realm) => {
+                self.check_realm(realm)?;
+                &self.realms[realm.index]
+            }
+            None => &self.main_context,
+        };
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, context);
+        scope.get_current_context().use_default_security_token();
+        Ok(())
+    }
+
+    /// Returns an error, if the realm was not created by this runtime.
+    fn check_realm(&self, realm: &Realm) -> Result<(), Error> {
+        if realm.runtime != self.registration.id() || realm.index >= self.realms.len() {
+            return Err(Error::Internal(
+                "The realm doesn't belong to this runtime".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compiles and instantiates the given WebAssembly binary.
+    ///
+    /// `imports` is an ECMAScript expression, that evaluates to the import object of the module
+    /// (e.g. `({ env: { log: console.log } })`). An empty expression passes no import object.
+    /// The expression is verified by the code verifier as [`ScriptKind::WasmImports`].
+    pub fn instantiate_wasm(&mut self, bytes: &[u8], imports: &str) -> Result<WasmInstance, Error> {
+        let has_imports = !imports.trim().is_empty();
+        if has_imports {
+            verify_source(
+                &self.runtime_data,
+                &ScriptSource {
+                    kind: ScriptKind::WasmImports,
+                    name: None,
+                    source: imports,
+                },
+            )?;
+        }
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let Some(module) = v8::WasmModuleObject::compile(try_catch_scope, bytes) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        let mut arguments: Vec<v8::Local<v8::Value>> = vec![module.into()];
+        if has_imports {
+            let source = new_string(try_catch_scope, imports, NewStringType::Normal);
+            let Some(imports) = v8::Script::compile(try_catch_scope, source, None)
+                .and_then(|script| script.run(try_catch_scope))
+            else {
+                self.runtime_data.count(|c| c.exceptions_thrown += 1);
+                let exception = try_catch_scope.exception();
+                return Err(create_error_from_exception(try_catch_scope, exception));
+            };
+            arguments.push(imports);
+        }
+
+        let constructor = resolve_object(try_catch_scope, "WebAssembly.Instance")?;
+        let constructor = v8::Local::<v8::Function>::try_from(constructor).map_err(|_| {
+            Error::Internal("WebAssembly.Instance is not a constructor".to_string())
+        })?;
+
+        let Some(instance) = constructor.new_instance(try_catch_scope, &arguments) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        let exports = resolve_export::<v8::Object>(try_catch_scope, instance, "exports")?;
+
+        Ok(WasmInstance {
+            runtime: self.registration.id(),
+            exports: v8::Global::new(try_catch_scope, exports),
+        })
+    }
+
+    /// Calls the exported function of the WebAssembly instance with the given arguments and
+    /// returns the deserialized result.
+    pub fn call_wasm<T>(
+        &mut self,
+        instance: &WasmInstance,
+        name: &str,
+        arguments: &[WasmValue],
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        self.check_wasm_instance(instance)?;
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let exports = v8::Local::new(try_catch_scope, &instance.exports);
+        let function = resolve_export::<v8::Function>(try_catch_scope, exports, name)?;
+
+        let arguments: Vec<v8::Local<v8::Value>> = arguments
+            .iter()
+            .map(|argument| argument.to_v8(try_catch_scope))
+            .collect();
+        let receiver = v8::undefined(try_catch_scope).into();
+
+        let Some(value) = function.call(try_catch_scope, receiver, &arguments) else {
+            if try_catch_scope.has_terminated() {
+                try_catch_scope.cancel_terminate_execution();
+                return Err(Error::Terminated);
+            }
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        deserialize_result(try_catch_scope.seal(), value.seal())
+    }
+
+    /// Calls the closure with the backing memory of the exported memory of the WebAssembly
+    /// instance.
+    ///
+    /// The slice can't outlive the closure, since growing the memory detaches it.
+    pub fn wasm_memory<F, R>(
+        &mut self,
+        instance: &WasmInstance,
+        name: &str,
+        f: F,
+    ) -> Result<R, Error>
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        self.check_wasm_instance(instance)?;
+
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let exports = v8::Local::new(try_catch_scope, &instance.exports);
+        let memory = resolve_export::<v8::WasmMemoryObject>(try_catch_scope, exports, name)?;
+
+        let Some(mut buffer) = memory.seal().buffer(try_catch_scope.seal()) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        Ok(f(buffer.as_mut()))
+    }
+
+    /// Returns an error, if the WebAssembly instance was created by another runtime. The handles
+    /// of the instance are only valid inside the isolate of the runtime, that created it.
+    fn check_wasm_instance(&self, instance: &WasmInstance) -> Result<(), Error> {
+        if instance.runtime != self.registration.id() {
+            return Err(Error::Internal(
+                "The WebAssembly instance doesn't belong to this runtime".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a handle to the runtime, that can be used to terminate or interrupt scripts from
+    /// other threads.
+    pub fn handle(&self) -> RuntimeHandle {
+        RuntimeHandle::new(self.isolate.thread_safe_handle())
+    }
+
+    /// Sets the global property with the given name to the serialized value.
+    ///
+    /// Can be used to inject configuration objects without executing a wrapper script.
+    pub fn set_global<T>(&mut self, name: &str, value: T) -> Result<(), Error>
+    where
+        T: Serialize,
+    {
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let value = value
+            .serialize(try_catch_scope.seal())
+            .map_err(Error::Type)?;
+        let key = new_string(try_catch_scope, name, NewStringType::Internalized);
+        let global = try_catch_scope
+            .get_current_context()
+            .global(try_catch_scope);
+
+        if global
+            .set(try_catch_scope, key.into(), value.unseal())
+            .is_none()
+        {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the deserialized value of the global property with the given name.
+    ///
+    /// Returns [`Error::UndefinedResult`] if the property doesn't exist and `T` can't be created
+    /// from `undefined`.
+    pub fn get_global<T>(&mut self, name: &str) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let key = new_string(try_catch_scope, name, NewStringType::Internalized);
+        let global = try_catch_scope
+            .get_current_context()
+            .global(try_catch_scope);
+
+        let Some(value) = global.get(try_catch_scope, key.into()) else {
+            self.runtime_data.count(|c| c.exceptions_thrown += 1);
+            let exception = try_catch_scope.exception();
+            return Err(create_error_from_exception(try_catch_scope, exception));
+        };
+
+        deserialize_result(try_catch_scope.seal(), value.seal())
+    }
+
+    /// Returns a handle to the namespace object at the given path (e.g. `game` or `game.math`),
+    /// if it exists.
+    ///
+    /// Can be used to verify at startup, that the functions a host expects are registered.
+    pub fn namespace(&mut self, path: &str) -> Option<NamespaceHandle<'_, STATE>> {
+        {
+            let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+            resolve_object(scope, path).ok()?;
+        }
+
+        Some(NamespaceHandle {
+            runtime: self,
+            path: path.to_string(),
+        })
+    }
+
+    /// Stores private data of an extension under the given key, replacing existing data.
+    ///
+    /// The data can be accessed with [`ValueScope::extension_data()`] inside custom
+    /// [`crate::Serialize`] and [`crate::Deserialize`] implementations. Using the name of the
+    /// extension as the key prevents collisions between independent extensions.
+    ///
+    /// [`ValueScope::extension_data()`]: crate::value::ValueScope::extension_data()
+    pub fn set_extension_data<T>(&mut self, key: &str, value: T)
+    where
+        T: 'static,
+    {
+        self.runtime_data
+            .extension_data
+            .borrow_mut()
+            .insert(key.to_string(), Box::new(value));
+    }
+
+    /// Returns the footprints of the extensions of the runtime, in the order they were
+    /// registered.
+    ///
+    /// Helps to find the extensions, that increase the creation cost and the memory of every
+    /// runtime the most.
+    pub fn extension_footprint(&self) -> &[ExtensionFootprint] {
+        &self.extension_footprints
+    }
+
+    /// Returns the counters of the runtime.
+    pub fn counters(&self) -> RuntimeCounters {
+        self.runtime_data.counters.get()
+    }
+
+    /// Returns the statistics of the script cache.
+    pub fn script_cache_statistics(&self) -> ScriptCacheStatistics {
+        self.script_cache.statistics()
+    }
+
+    /// Marks the runtime as idle or not.
+    ///
+    /// If `memory_reducer` of the [`RuntimeOptions`] is enabled, [`Runtime::reduce_memory()`]
+    /// is called when the runtime becomes idle. Otherwise only the flag is stored, the engine
+    /// itself is not notified.
+    pub fn set_idle(&mut self, idle: bool) {
+        if idle && !self.idle && self.memory_reducer {
+            self.reduce_memory();
+        }
+        self.idle = idle;
+    }
+
+    /// Returns `true` if the runtime was marked as idle.
+    #[inline(always)]
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Runs a full garbage collection, that collects all unreachable objects and releases as
+    /// much memory as possible (for example compiled code that can be recreated).
+    ///
+    /// The collection blocks the current thread. This is an expensive operation and should only
+    /// be called when the runtime is not expected to be used in the near future.
+    pub fn reduce_memory(&mut self) {
+        self.isolate.low_memory_notification();
+    }
+
+    /// Runs the pending tasks of the engine (e.g. garbage collection and compilation) for this
+    /// runtime until no task is left or the deadline is reached. Remaining time is used for idle
+    /// tasks. Returns the number of tasks that were run.
+    ///
+    /// Tasks of the engine are bound to an isolate, which is why the tasks are pumped per runtime.
+    /// When the engine was initialized with [`crate::ExecutionModel::SingleThreaded`], nothing
+    /// runs the tasks in the background, so embedders should call this function regularly inside
+    /// their own loop. Otherwise the work is done lazily during the execution of scripts.
+    pub fn pump_platform_tasks(&mut self, deadline: Instant) -> usize {
+        let platform = v8::V8::get_current_platform();
+
+        let mut tasks = 0;
+        while Instant::now() < deadline
+            && v8::Platform::pump_message_loop(&platform, &mut self.isolate, false)
+        {
+            tasks += 1;
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !remaining.is_zero() {
+            v8::Platform::run_idle_tasks(&platform, &mut self.isolate, remaining.as_secs_f64());
+        }
+
+        tasks
+    }
+}
+
+impl<STATE> ScriptEventLoop for Runtime<STATE> {
+    /// Runs the callbacks of the timers of the [`crate::TimerExtension`], that expired at or
+    /// before `now`. The microtasks are run after each callback.
+    fn poll_timers(&mut self, now: Instant) -> usize {
+        let expired = self.runtime_data.timers.borrow_mut().expire(now);
+        if expired.is_empty() {
+            return 0;
+        }
+
+        let mut count = 0;
+        let mut terminated = false;
+        {
+            let scope = &mut v8::HandleScope::with_context(&mut self.isolate, &self.main_context);
+            for id in expired {
+                // A callback can clear the timers, that expired together with it.
+                let Some(timer) = self.runtime_data.timers.borrow_mut().take(id, now) else {
+                    continue;
+                };
+
+                let try_catch_scope = &mut v8::TryCatch::new(scope);
+                let callback = v8::Local::new(try_catch_scope, &timer.callback);
+                let arguments: Vec<v8::Local<v8::Value>> = timer
+                    .arguments
+                    .iter()
+                    .map(|argument| v8::Local::new(try_catch_scope, argument))
+                    .collect();
+                let undefined = v8::undefined(try_catch_scope);
+
+                count += 1;
+                if callback
+                    .call(try_catch_scope, undefined.into(), &arguments)
+                    .is_none()
+                {
+                    // The remaining timers stay pending and run in the next poll.
+                    if try_catch_scope.has_terminated() {
+                        try_catch_scope.cancel_terminate_execution();
+                        terminated = true;
+                        break;
+                    }
+
+                    self.runtime_data.count(|c| c.exceptions_thrown += 1);
+                    let exception = try_catch_scope.exception();
+                    let err = create_error_from_exception(try_catch_scope, exception);
+                    log::warn!("Timer callback failed: {}", err);
+                }
+
+                try_catch_scope.perform_microtask_checkpoint();
+                self.runtime_data.count(|c| c.microtask_checkpoints += 1);
+            }
+        }
+
+        if terminated {
+            let _ = self.notify_terminated::<()>(Err(Error::Terminated));
+        }
+
+        self.report_unhandled_rejections();
+        count
+    }
+
+    fn drain_microtasks(&mut self) {
+        // Settling the promises of async host functions queues their reactions as microtasks.
+        self.poll_event_loop();
+    }
+
+    fn has_pending_work(&self) -> bool {
+        // Microtasks are drained after every execution, so only timers and async host functions
+        // can be pending.
+        !self.runtime_data.timers.borrow().is_empty()
+            || !self.runtime_data.async_tasks.borrow().is_empty()
+    }
+}
+
+/// Handle to a namespace object of a [`Runtime`].
+///
+/// Created by [`Runtime::namespace()`].
+pub struct NamespaceHandle<'runtime, STATE> {
+    runtime: &'runtime mut Runtime<STATE>,
+    path: String,
+}
+
+impl<'runtime, STATE> NamespaceHandle<'runtime, STATE> {
+    /// Returns the path of the namespace.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns a handle to the function with the given name inside the namespace, if it exists.
+    pub fn function(&mut self, name: &str) -> Option<FunctionHandle> {
+        let runtime = &mut *self.runtime;
+        let scope = &mut v8::HandleScope::with_context(&mut runtime.isolate, &runtime.main_context);
+
+        let namespace = resolve_object(scope, &self.path).ok()?;
+        let key = new_string(scope, name, NewStringType::Normal);
+        let value = namespace.get(scope, key.into())?;
+
+        if value.is_function() {
+            Some(FunctionHandle {
+                path: format!("{}.{}", self.path, name),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a handle to the nested namespace with the given name, if it exists.
+    pub fn namespace(self, name: &str) -> Option<NamespaceHandle<'runtime, STATE>> {
+        let path = format!("{}.{}", self.path, name);
+        self.runtime.namespace(&path)
+    }
+}
+
+/// Handle to a function of a [`Runtime`], that was verified to exist.
+///
+/// Created by [`NamespaceHandle::function()`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FunctionHandle {
+    path: String,
+}
+
+impl FunctionHandle {
+    /// Returns the path of the function as seen from inside a script (e.g. `game.spawn`).
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Guard of a paused [`Runtime`]. The runtime resumes once the guard is dropped.
+///
+/// Only gives access to operations, that don't run scripts. Created by [`Runtime::pause()`].
+pub struct PauseGuard<'runtime, STATE> {
+    runtime: &'runtime mut Runtime<STATE>,
+}
+
+impl<'runtime, STATE> PauseGuard<'runtime, STATE> {
+    /// Returns the state of the paused runtime.
+    pub fn state(&self) -> std::cell::Ref<'_, STATE> {
+        self.runtime.state.borrow()
+    }
+
+    /// Returns the mutable state of the paused runtime.
+    pub fn state_mut(&mut self) -> std::cell::RefMut<'_, STATE> {
+        self.runtime.state.borrow_mut()
+    }
+
+    /// Captures the state of the global object. See [`Runtime::capture_globals()`].
+    ///
+    /// Proxies are captured through their targets and accessor properties are captured without
+    /// calling their getters, so no script runs while capturing.
+    pub fn capture_globals(&mut self) -> Result<GlobalsSnapshot, Error> {
+        self.runtime.capture_globals()
+    }
+
+    /// Returns the counters of the runtime.
+    pub fn counters(&self) -> RuntimeCounters {
+        self.runtime.counters()
+    }
+
+    /// Returns a collection of information about the heap of the engine.
+    pub fn heap_statistics(&mut self) -> HeapStatistics {
+        self.runtime.heap_statistics()
+    }
+}
 
 #[cfg(test)]
 mod test {
     use std::{
         cell::RefCell,
+        collections::HashMap,
+        future::Future,
+        pin::Pin,
         rc::Rc,
         sync::{
-            atomic::{AtomicI32, Ordering},
-            Arc,
+            atomic::{AtomicI32, AtomicU32, AtomicUsize, Ordering},
+            Arc, Mutex,
         },
+        task::{Context, Poll},
         thread::JoinHandle,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        error::{Error, ErrorCode, ScriptError},
+        *,
     };
 
-    use crate::{error::Error, *};
+    #[test]
+    fn runtime_creation() {
+        initialize_with_defaults();
+
+        // Multiple runtimes can be created.
+        let runtime0 = Runtime::new(RuntimeOptions::default(), ());
+        assert!(runtime0.is_ok());
+
+        let runtime1 = Runtime::new(RuntimeOptions::default(), ());
+        assert!(runtime1.is_ok());
+    }
+
+    #[test]
+    fn runtime_creation_multiple_thread() {
+        initialize_with_defaults();
+
+        let handle0: JoinHandle<Result<(), Error>> = std::thread::spawn(|| {
+            let mut runtime0 = Runtime::new(RuntimeOptions::default(), ())?;
+            let val: i32 = runtime0.execute("var x = 30; x")?;
+            assert_eq!(val, 30);
+            Ok(())
+        });
+
+        let handle1: JoinHandle<Result<(), Error>> = std::thread::spawn(|| {
+            let mut runtime1 = Runtime::new(RuntimeOptions::default(), ())?;
+            let val: i32 = runtime1.execute("var x = 20; x")?;
+            assert_eq!(val, 20);
+            Ok(())
+        });
+
+        handle0.join().expect("thread 0 died").expect("error found");
+        handle1.join().expect("thread 1 died").expect("error found");
+    }
+
+    #[test]
+    fn global_statistics() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        runtime
+            .execute::<(), _>("var data = new Array(1000).fill(42);")
+            .expect("Can't execute code");
+
+        let used_heap_size = runtime.heap_statistics().used_heap_size();
+        let statistics = crate::global_statistics();
+        assert!(statistics.runtimes >= 1);
+        assert!(statistics.used_heap_size >= used_heap_size);
+    }
+
+    #[test]
+    fn invalid_heap_limits() {
+        initialize_with_defaults();
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                initial_heap_size: 8 * 1024 * 1024,
+                max_heap_size: 4 * 1024 * 1024,
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::IsolateCreation(_))));
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                initial_heap_size: 0,
+                max_heap_size: 1024,
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::IsolateCreation(_))));
+    }
+
+    #[test]
+    fn script_event_loop() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        runtime
+            .execute::<(), _>("var done = false; Promise.resolve().then(() => { done = true; });")
+            .expect("Can't execute code");
+
+        let checkpoints = runtime.counters().microtask_checkpoints;
+        assert!(!runtime.tick(std::time::Instant::now()));
+        assert_eq!(runtime.counters().microtask_checkpoints, checkpoints + 1);
+
+        let val: bool = runtime.execute("done").expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn pump_platform_tasks() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        runtime
+            .execute::<(), _>("var data = new Array(1000).fill(42); data = null;")
+            .expect("Can't execute code");
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(10);
+        runtime.pump_platform_tasks(deadline);
+
+        let val: i32 = runtime.execute("1 + 1").expect("Can't execute code");
+        assert_eq!(val, 2);
+    }
+
+    #[test]
+    fn heap_statistics() {
+        const MAX_HEAP_SIZE: usize = 5 * 1024 * 1024;
+
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                max_heap_size: MAX_HEAP_SIZE,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't not create runtime");
+
+        let heap_statistics = runtime.heap_statistics();
+
+        // This only tests if the values make some sense.
+        assert!(heap_statistics.heap_size_limit() >= MAX_HEAP_SIZE);
+        assert!(heap_statistics.total_heap_size() >= 64 * 1024);
+        assert!(heap_statistics.used_heap_size() >= 64 * 1024);
+        assert!(heap_statistics.total_physical_size() >= 64 * 1024);
+    }
+
+    #[test]
+    fn memory_reducer() {
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                memory_reducer: true,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't not create runtime");
+
+        let unreachable_object = |runtime: &mut Runtime<()>| {
+            let scope =
+                &mut v8::HandleScope::with_context(&mut runtime.isolate, &runtime.main_context);
+            let object = v8::Object::new(scope);
+            v8::Weak::new(scope, object)
+        };
+
+        let weak = unreachable_object(&mut runtime);
+        assert!(!weak.is_empty());
+        runtime.set_idle(true);
+        assert!(runtime.is_idle());
+        assert!(weak.is_empty());
+
+        // Only becoming idle reduces the memory.
+        let weak = unreachable_object(&mut runtime);
+        runtime.set_idle(true);
+        assert!(!weak.is_empty());
+
+        runtime.set_idle(false);
+        assert!(!runtime.is_idle());
+
+        let val: i32 = runtime.execute("42").expect("Can't execute code");
+        assert_eq!(val, 42);
+    }
+
+    #[test]
+    fn execute_code() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: i32 = runtime.execute("42 + 3").expect("Can't execute code");
+
+        assert_eq!(val, 45);
+    }
+
+    #[test]
+    fn execute_code_is_stateful() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: i32 = runtime.execute("var x = 1; x").expect("Can't execute code");
+        assert_eq!(val, 1);
+
+        let val: i32 = runtime.execute("x += 2; x").expect("Can't execute code");
+        assert_eq!(val, 3);
+    }
+
+    #[test]
+    fn execute_code_compile_error() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let ret: Result<(), Error> = runtime.execute("var = let");
+        let err = ret.expect_err("Expected an Script error");
+        assert!(matches!(err, Error::Script { .. }))
+    }
+
+    #[test]
+    fn check_code_strict_mode() {
+        initialize_with_defaults();
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                force_strict_mode: true,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let diagnostics = runtime.check("var y = ;");
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.line, 1);
+        assert_eq!(diagnostic.start_column, 8);
+        assert_eq!(diagnostic.start_position, 8);
+
+        let origin = ScriptOrigin {
+            name: "scripts/goblin.js".to_string(),
+            line_offset: 4,
+            ..Default::default()
+        };
+        let diagnostics = runtime.check_with_origin("var y = ;\nvar z = ;", &origin);
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.line, 5);
+        assert_eq!(diagnostic.start_column, 8);
+    }
+
+    #[test]
+    fn check_code() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        assert!(runtime.check("var x = 1; x + 2").is_empty());
+
+        let diagnostics = runtime.check("var x = 1;\nvar y = ;");
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.line, 2);
+        assert_eq!(diagnostic.start_column, 8);
+        assert_eq!(diagnostic.start_position, 19);
+
+        // Checking must not execute the script.
+        assert!(runtime.check("var z = 42;").is_empty());
+        let defined: bool = runtime
+            .execute("typeof z !== 'undefined'")
+            .expect("Can't execute code");
+        assert!(!defined);
+    }
+
+    #[test]
+    fn execute_code_execution_error() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let ret: Result<(), Error> = runtime.execute("unknown_function()");
+        let err = ret.expect_err("Expected an Script error");
+        assert!(matches!(err, Error::Script { .. }))
+    }
+
+    #[test]
+    fn execute_code_labeled() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute_labeled("ai:goblin#42", "40 + 2")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        let ret: Result<(), Error> = runtime.execute_labeled("ai:goblin#42", "unknown_function()");
+        let err = ret.expect_err("Expected an Script error");
+        let Error::Script(script_error) = &err else {
+            panic!("Expected an Script error");
+        };
+        assert_eq!(script_error.label.as_deref(), Some("ai:goblin#42"));
+        assert!(err.to_string().contains("[ai:goblin#42]"));
+    }
+
+    #[test]
+    fn execute_code_error_stack() {
+        initialize_with_defaults();
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                capture_stack_trace_for_uncaught_exceptions: Some(10),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let ret: Result<(), Error> = runtime.execute(
+            "function inner() {\n  throw new Error('boom');\n}\nfunction outer() { inner(); }\nouter();",
+        );
+        let Err(Error::Script(script_error)) = ret else {
+            panic!("Expected an Script error");
+        };
+        assert_eq!(script_error.line, 2);
+        assert_eq!(
+            script_error.source_line.as_deref(),
+            Some("  throw new Error('boom');")
+        );
+
+        let frames: Vec<(Option<&str>, usize)> = script_error
+            .stack
+            .iter()
+            .map(|frame| (frame.function_name.as_deref(), frame.line_number))
+            .collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[..2], [(Some("inner"), 2), (Some("outer"), 4)]);
+        assert_eq!(frames[2].1, 5);
+    }
+
+    #[test]
+    fn execute_with_origin() {
+        initialize_with_defaults();
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                capture_stack_trace_for_uncaught_exceptions: Some(10),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let origin = ScriptOrigin {
+            name: "scripts/goblin.js".to_string(),
+            line_offset: 10,
+            column_offset: 0,
+            source_map_url: Some("goblin.js.map".to_string()),
+        };
+
+        let value: i32 = runtime
+            .execute_with_origin("40 + 2", &origin)
+            .expect("Can't execute code");
+        assert_eq!(value, 42);
+
+        let ret: Result<(), Error> =
+            runtime.execute_with_origin("const a = 1;\nthrow new Error('boom');", &origin);
+        let Err(Error::Script(script_error)) = ret else {
+            panic!("Expected an Script error");
+        };
+        assert_eq!(
+            script_error.resource_name.as_deref(),
+            Some("scripts/goblin.js")
+        );
+        assert_eq!(script_error.line, 12);
+        assert_eq!(
+            script_error.stack[0].script_name.as_deref(),
+            Some("scripts/goblin.js")
+        );
+
+        let ret: Result<(), Error> = runtime.execute("throw new Error('boom');");
+        let Err(Error::Script(script_error)) = ret else {
+            panic!("Expected an Script error");
+        };
+        assert_eq!(script_error.resource_name, None);
+    }
+
+    #[test]
+    fn promise_reject_callback() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let rejections = Rc::new(RefCell::new(Vec::new()));
+        let callback_rejections = rejections.clone();
+        runtime.set_promise_reject_callback(move |err: &ScriptError| {
+            callback_rejections.borrow_mut().push(err.message.clone());
+        });
+
+        runtime
+            .execute::<(), _>("void Promise.reject(new Error('lost'));")
+            .expect("Can't execute code");
+        runtime
+            .execute::<(), _>(
+                "const p = Promise.reject(new Error('handled')); void p.catch(() => {});",
+            )
+            .expect("Can't execute code");
+        runtime
+            .execute::<(), _>("void (async () => { throw new Error('async'); })();")
+            .expect("Can't execute code");
+
+        let ret: Result<(), Error> = runtime.execute_async("Promise.reject(new Error('returned'))");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        runtime.perform_microtask_checkpoint();
+        assert_eq!(
+            *rejections.borrow(),
+            vec![
+                "Uncaught Error: lost".to_string(),
+                "Uncaught Error: async".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_code_undefined_result() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let ret: Result<i32, Error> = runtime.execute("function foo() { 42 }; foo();");
+        assert!(matches!(ret, Err(Error::UndefinedResult)));
+
+        let val: Option<i32> = runtime
+            .execute("function bar() { 42 }; bar();")
+            .expect("Can't execute code");
+        assert_eq!(val, None);
+
+        let val: i32 = runtime
+            .execute_or_default("function baz() { 42 }; baz();")
+            .expect("Can't execute code");
+        assert_eq!(val, 0);
+
+        let val: i32 = runtime
+            .execute_or_default("40 + 2")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        let ret: Result<i32, Error> = runtime.execute("'a'");
+        assert!(matches!(ret, Err(Error::Type(_))));
+    }
+
+    #[test]
+    fn extension_data() {
+        use crate::{
+            error::TypeError,
+            value::{Value, ValueScope},
+        };
+
+        initialize_with_defaults();
+
+        struct Scale(f64);
+        struct Scaled(f64);
+
+        impl<'scope> Deserialize<'scope> for Scaled {
+            fn deserialize(
+                scope: &mut ValueScope<'scope>,
+                value: Value<'scope>,
+            ) -> Result<Self, TypeError> {
+                let value = f64::deserialize(scope, value)?;
+                let scale = scope.extension_data::<Scale>("scale").map_or(1.0, |s| s.0);
+                Ok(Scaled(value * scale))
+            }
+        }
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: Scaled = runtime.execute("2.0").expect("Can't execute code");
+        assert_eq!(val.0, 2.0);
+
+        runtime.set_extension_data("scale", Scale(3.0));
+        let val: Scaled = runtime.execute("2.0").expect("Can't execute code");
+        assert_eq!(val.0, 6.0);
+
+        // Data of the wrong type is not returned.
+        runtime.set_extension_data("scale", 3.0f64);
+        let val: Scaled = runtime.execute("2.0").expect("Can't execute code");
+        assert_eq!(val.0, 2.0);
+    }
+
+    #[test]
+    fn execute_code_script_cache() {
+        initialize_with_defaults();
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                script_cache_capacity: 2,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        for _ in 0..3 {
+            let val: i32 = runtime.execute("40 + 2").expect("Can't execute code");
+            assert_eq!(val, 42);
+        }
+
+        let statistics = runtime.script_cache_statistics();
+        assert_eq!(statistics.hits, 2);
+        assert_eq!(statistics.misses, 1);
+        assert_eq!(statistics.entries, 1);
+
+        runtime
+            .execute::<(), _>("var a = 1;")
+            .expect("Can't execute code");
+        runtime
+            .execute::<(), _>("var b = 2;")
+            .expect("Can't execute code");
+
+        // The least recently used script was evicted.
+        let val: i32 = runtime.execute("40 + 2").expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        let statistics = runtime.script_cache_statistics();
+        assert_eq!(statistics.hits, 2);
+        assert_eq!(statistics.misses, 4);
+        assert_eq!(statistics.entries, 2);
+    }
+
+    #[test]
+    fn execute_code_async() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute_async("(async () => { const x = await Promise.resolve(40); return x + 2; })()")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        let val: i32 = runtime.execute_async("42").expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        let ret = runtime.execute_async::<i32, _>("Promise.reject(new Error('failed'))");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute_async::<i32, _>("new Promise(() => {})");
+        assert!(matches!(ret, Err(Error::PendingPromise)));
+    }
+
+    /// A future that is completed by another thread after the given duration.
+    struct Delay {
+        duration: Duration,
+        done: Arc<Mutex<bool>>,
+        started: bool,
+    }
+
+    impl Future for Delay {
+        type Output = Result<u32, String>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if *self.done.lock().unwrap() {
+                return Poll::Ready(Ok(self.duration.as_millis() as u32));
+            }
+
+            if !self.started {
+                self.started = true;
+                let done = self.done.clone();
+                let duration = self.duration;
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(duration);
+                    *done.lock().unwrap() = true;
+                    waker.wake();
+                });
+            }
+
+            Poll::Pending
+        }
+    }
+
+    /// An error with a source, which becomes the `cause` of the rejected promise.
+    #[derive(Debug)]
+    struct LoadError(std::num::ParseIntError);
+
+    impl std::fmt::Display for LoadError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Can't load the value")
+        }
+    }
+
+    impl std::error::Error for LoadError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn async_function() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(None);
+        extension.add_async_function("delay", |(ms,): (u64,)| Delay {
+            duration: Duration::from_millis(ms),
+            done: Arc::new(Mutex::new(false)),
+            started: false,
+        });
+        extension.add_async_function("fail", |()| async { Err::<(), _>("failed") });
+        extension.add_async_function("load", |(value,): (String,)| async move {
+            value.parse::<u32>().map_err(LoadError)
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: u32 = runtime
+            .execute_async("(async () => (await delay(5)) + (await delay(10)))()")
+            .expect("Can't execute code");
+        assert_eq!(val, 15);
+
+        let ret = runtime.execute_async::<(), _>("fail()");
+        assert!(matches!(ret, Err(Error::Script(err)) if err.message.contains("failed")));
+
+        let val: u32 = runtime
+            .execute_async("load('42')")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        match runtime.execute_async::<(), _>("load('ten')") {
+            Err(Error::Script(err)) => {
+                assert!(err.message.contains("Can't load the value"));
+                let cause = err.cause.expect("Cause is missing");
+                assert!(cause.message.contains("invalid digit"));
+                assert!(cause.cause.is_none());
+            }
+            ret => panic!("Unexpected result: {:?}", ret),
+        }
+
+        let val: bool = runtime
+            .execute_async(
+                "load('ten').catch(err => err.cause instanceof Error && !Object.keys(err).includes('cause'))",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+
+        runtime
+            .execute::<(), _>(
+                "globalThis.sum = 0; [1, 2, 3].forEach(ms => delay(ms).then(v => sum += v));",
+            )
+            .expect("Can't execute code");
+        assert!(runtime.has_pending_work());
+        runtime.run_event_loop();
+        assert!(!runtime.poll_event_loop());
+
+        let val: u32 = runtime.execute("sum").expect("Can't execute code");
+        assert_eq!(val, 6);
+    }
+
+    #[test]
+    fn execute_with_timeout() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let ret =
+            runtime.execute_with_timeout::<(), _>("while (true) {}", Duration::from_millis(50));
+        assert!(matches!(ret, Err(Error::Terminated)));
+
+        let val: i32 = runtime
+            .execute_with_timeout("1 + 1", Duration::from_secs(10))
+            .expect("Can't execute code");
+        assert_eq!(val, 2);
+    }
+
+    #[test]
+    fn on_terminated() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_function_with_state("begin", |state: &mut Vec<u32>, (id,): (u32,)| {
+            state.push(id)
+        });
+        extension.add_function_with_state("end", |state: &mut Vec<u32>, ()| {
+            state.pop();
+        });
+
+        let terminations = Rc::new(RefCell::new(0));
+        let hook_terminations = terminations.clone();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                on_terminated: Some(Box::new(move |state: &mut Vec<u32>| {
+                    *hook_terminations.borrow_mut() += 1;
+                    state.clear();
+                })),
+                ..Default::default()
+            },
+            Vec::new(),
+        )
+        .expect("Can't create runtime");
+
+        // Terminated while a host function call is running.
+        let handle = runtime.handle();
+        let mut extension = Extension::new(Some("host"));
+        extension.add_function("terminate", move |()| handle.terminate_execution());
+        let realm = runtime
+            .create_realm(vec![extension])
+            .expect("Can't create realm");
+        let ret = runtime.execute_in::<(), _>(&realm, "host.terminate(); while (true) {}");
+        assert!(matches!(ret, Err(Error::Terminated)));
+        assert_eq!(*terminations.borrow(), 1);
+
+        // Terminated between two host function calls, that belong together.
+        let ret = runtime.execute_with_timeout::<(), _>(
+            "let i = 0; while (true) { test.begin(i++); for (let j = 0; j < 100; j++) {} test.end(); }",
+            Duration::from_millis(50),
+        );
+        assert!(matches!(ret, Err(Error::Terminated)));
+        assert_eq!(*terminations.borrow(), 2);
+        assert!(runtime.state().is_empty());
+
+        let val: u32 = runtime
+            .execute("test.begin(7); test.end(); 1")
+            .expect("Can't execute code");
+        assert_eq!(val, 1);
+        assert_eq!(*terminations.borrow(), 2);
+    }
+
+    #[test]
+    fn execute_timed() {
+        initialize_with_defaults();
+
+        let mut global_extension = Extension::new(None);
+        global_extension.add_function("sleep", |(millis,): (u32,)| {
+            std::thread::sleep(Duration::from_millis(millis as u64));
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![global_extension],
+                script_cache_capacity: 8,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let (val, timing) = runtime.execute_timed::<i32, _>("sleep(10); sleep(10); 1 + 1");
+        assert_eq!(val.expect("Can't execute code"), 2);
+        assert!(timing.host_calls >= Duration::from_millis(20));
+        assert!(timing.execute >= timing.host_calls);
+        assert!(timing.wall >= timing.compile + timing.execute);
+        assert!(timing.script() <= timing.execute);
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        assert!(timing.cpu.is_some());
+
+        // The cached script is not compiled again.
+        let (_, timing) = runtime.execute_timed::<i32, _>("sleep(10); sleep(10); 1 + 1");
+        assert_eq!(timing.compile, Duration::ZERO);
+
+        let (val, timing) = runtime.execute_timed::<(), _>("throw new Error('fail')");
+        assert!(matches!(val, Err(Error::Script(_))));
+        assert_eq!(timing.host_calls, Duration::ZERO);
+    }
+
+    #[test]
+    fn runtime_handle() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let handle = runtime.handle();
+        let supervisor = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(handle.terminate_execution());
+        });
+
+        let ret = runtime.execute::<(), _>("while (true) {}");
+        assert!(matches!(ret, Err(Error::Terminated)));
+        supervisor.join().expect("Supervisor panicked");
+        assert!(!runtime.handle().is_execution_terminating());
+
+        let handle = runtime.handle();
+        let supervisor = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(handle.request_interrupt(|scope, global| {
+                let key = value::String::new(scope, "stop", value::NewStringType::Internalized);
+                let value = value::Boolean::new(scope, true);
+                global.set(scope, key.into(), value.into());
+            }));
+        });
+
+        let val: i32 = runtime
+            .execute("var stop = false; var i = 0; while (!stop) { i++; } 42")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+        supervisor.join().expect("Supervisor panicked");
+    }
+
+    #[test]
+    fn slow_script_report() {
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                stack_sampling: Some(StackSampling {
+                    threshold: Duration::from_millis(20),
+                    interval: Duration::from_millis(20),
+                    max_samples: 3,
+                    frame_limit: 8,
+                }),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let ret = runtime.execute_with_timeout::<(), _>(
+            "function spin() { while (true) {} } spin();",
+            Duration::from_millis(200),
+        );
+        assert!(matches!(ret, Err(Error::Terminated)));
+
+        let report = runtime
+            .slow_script_report()
+            .expect("Slow script was not reported");
+        assert!(report.terminated);
+        assert!(!report.samples.is_empty() && report.samples.len() <= 3);
+        assert!(report.samples.iter().all(|sample| sample
+            .frames
+            .iter()
+            .any(|frame| frame.function_name.as_deref() == Some("spin"))));
+
+        let _: i32 = runtime
+            .execute_with_timeout("1 + 1", Duration::from_secs(10))
+            .expect("Can't execute code");
+        assert!(runtime.slow_script_report().is_none());
+    }
+
+    #[test]
+    fn scope() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        runtime
+            .execute::<(), _>("var config = { name: 'kopi', level: 3 };")
+            .expect("Can't execute code");
+
+        let level = runtime.scope(|scope, global| {
+            let key = value::String::new(scope, "config", value::NewStringType::Internalized);
+            let config = global.get(scope, key.into()).expect("Can't get config");
+            let config = value::Object::try_from(config).expect("Config is not an object");
+
+            let key = value::String::new(scope, "level", value::NewStringType::Internalized);
+            let level = config.get(scope, key.into()).expect("Can't get level");
+            let level = i32::deserialize(scope, level).expect("Level is not a number");
+
+            let key = value::String::new(scope, "player", value::NewStringType::Internalized);
+            let player = value::Object::new(scope);
+            global.set(scope, key.into(), player.into());
+
+            let key = value::String::new(scope, "level", value::NewStringType::Internalized);
+            let value = value::Integer::new_from_i32(scope, level + 1);
+            player.set(scope, key.into(), value.into());
+
+            level
+        });
+        assert_eq!(level, 3);
+
+        let val: i32 = runtime.execute("player.level").expect("Can't execute code");
+        assert_eq!(val, 4);
+    }
+
+    #[test]
+    fn global_get_set() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let mut config = HashMap::new();
+        config.insert("speed".to_string(), 2);
+        config.insert("range".to_string(), 10);
+        runtime
+            .set_global("config", config)
+            .expect("Can't set global");
+
+        runtime
+            .execute::<(), _>("var result = config.speed * config.range;")
+            .expect("Can't execute code");
+
+        let val: i32 = runtime.get_global("result").expect("Can't get global");
+        assert_eq!(val, 20);
+
+        let ret = runtime.get_global::<i32>("missing");
+        assert!(matches!(ret, Err(Error::UndefinedResult)));
+
+        let val: Option<i32> = runtime.get_global("missing").expect("Can't get global");
+        assert!(val.is_none());
+
+        runtime
+            .execute::<(), _>("Object.defineProperty(globalThis, 'fail', { get() { throw 1; } });")
+            .expect("Can't execute code");
+        let ret = runtime.get_global::<i32>("fail");
+        assert!(matches!(ret, Err(Error::Script(_))));
+    }
+
+    #[test]
+    fn transfer_value() {
+        initialize_with_defaults();
+
+        let mut source = Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        let mut target = Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let data = source
+            .serialize_value(
+                "({ \
+                    map: new Map([['a', 1]]), \
+                    set: new Set([2, 3]), \
+                    date: new Date(1000), \
+                    bytes: new Uint8Array([4, 5]).buffer, \
+                })",
+            )
+            .expect("Can't serialize value");
+
+        target
+            .deserialize_value("transferred", &data)
+            .expect("Can't deserialize value");
+
+        let val: bool = target
+            .execute(
+                "transferred.map.get('a') === 1 \
+                    && transferred.set.has(3) \
+                    && transferred.date.getTime() === 1000 \
+                    && new Uint8Array(transferred.bytes)[1] === 5",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+
+        let ret = source.serialize_value("({ f() {} })");
+        assert!(matches!(ret, Err(Error::Type(_))));
+
+        let ret = target.deserialize_value("invalid", &[1, 2, 3]);
+        assert!(matches!(ret, Err(Error::Type(_))));
+    }
+
+    #[test]
+    fn instantiate_wasm() {
+        initialize_with_defaults();
+
+        // Imports `env.double` and exports `add`, `quadruple`, `load` and `memory`.
+        const MODULE: &[u8] = &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0c, 0x02, 0x60, 0x02, 0x7f,
+            0x7f, 0x01, 0x7f, 0x60, 0x01, 0x7f, 0x01, 0x7f, 0x02, 0x0e, 0x01, 0x03, 0x65, 0x6e,
+            0x76, 0x06, 0x64, 0x6f, 0x75, 0x62, 0x6c, 0x65, 0x00, 0x01, 0x03, 0x04, 0x03, 0x00,
+            0x01, 0x01, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x23, 0x04, 0x03, 0x61, 0x64, 0x64,
+            0x00, 0x01, 0x09, 0x71, 0x75, 0x61, 0x64, 0x72, 0x75, 0x70, 0x6c, 0x65, 0x00, 0x02,
+            0x04, 0x6c, 0x6f, 0x61, 0x64, 0x00, 0x03, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79,
+            0x02, 0x00, 0x0a, 0x1a, 0x03, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x0b, 0x08,
+            0x00, 0x20, 0x00, 0x10, 0x00, 0x10, 0x00, 0x0b, 0x07, 0x00, 0x20, 0x00, 0x28, 0x02,
+            0x00, 0x0b,
+        ];
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let instance = runtime
+            .instantiate_wasm(MODULE, "({ env: { double: (x) => x * 2 } })")
+            .expect("Can't instantiate module");
+
+        let val: i32 = runtime
+            .call_wasm(&instance, "add", &[20.into(), 22.into()])
+            .expect("Can't call export");
+        assert_eq!(val, 42);
+
+        let val: i32 = runtime
+            .call_wasm(&instance, "quadruple", &[3.into()])
+            .expect("Can't call export");
+        assert_eq!(val, 12);
+
+        let len = runtime
+            .wasm_memory(&instance, "memory", |memory| {
+                memory[..4].copy_from_slice(&1234_i32.to_le_bytes());
+                memory.len()
+            })
+            .expect("Can't access memory");
+        assert_eq!(len, 65536);
+
+        let val: i32 = runtime
+            .call_wasm(&instance, "load", &[0.into()])
+            .expect("Can't call export");
+        assert_eq!(val, 1234);
+
+        let ret = runtime.call_wasm::<i32>(&instance, "memory", &[]);
+        assert!(matches!(ret, Err(Error::Type(_))));
+
+        let mut other = Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        let ret = other.call_wasm::<i32>(&instance, "add", &[1.into(), 2.into()]);
+        assert!(matches!(ret, Err(Error::Internal(_))));
+        let ret = other.wasm_memory(&instance, "memory", |memory| memory.len());
+        assert!(matches!(ret, Err(Error::Internal(_))));
+
+        let ret = runtime.instantiate_wasm(MODULE, "");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.instantiate_wasm(&[0x00, 0x61, 0x73, 0x6d], "");
+        assert!(matches!(ret, Err(Error::Script(_))));
+    }
+
+    #[test]
+    fn near_heap_limit() {
+        initialize_with_defaults();
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let callback_calls = calls.clone();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                max_heap_size: 8 * 1024 * 1024,
+                on_near_heap_limit: Some(Box::new(move |limit| {
+                    callback_calls.borrow_mut().push(limit);
+                    limit
+                })),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let ret =
+            runtime.execute::<(), _>("(() => { const a = []; while (true) { a.push({}); } })()");
+        assert!(matches!(ret, Err(Error::HeapLimit)));
+        assert!(!calls.borrow().is_empty());
+
+        let val: i32 = runtime.execute("1 + 1").expect("Can't execute code");
+        assert_eq!(val, 2);
+    }
+
+    #[test]
+    fn code_verifier() {
+        initialize_with_defaults();
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                code_verifier: Some(Box::new(|script| {
+                    if script.source.starts_with("// signed") {
+                        Ok(())
+                    } else {
+                        Err(VerifyError::new(format!(
+                            "{:?} '{}' is not signed",
+                            script.kind,
+                            script.name.unwrap_or_default()
+                        )))
+                    }
+                })),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute("// signed\n1 + 1")
+            .expect("Can't execute code");
+        assert_eq!(val, 2);
+
+        let ret = runtime.execute::<i32, _>("1 + 1");
+        assert!(matches!(ret, Err(Error::Verification(_))));
+
+        let ret = runtime.compile_module("lib.js", "export const value = 1;");
+        let Err(Error::Verification(err)) = ret else {
+            panic!("Module was not verified");
+        };
+        assert_eq!(err.message, "Module 'lib.js' is not signed");
+
+        let ret = runtime.instantiate_wasm(&[0x00, 0x61, 0x73, 0x6d], "({ env: {} })");
+        let Err(Error::Verification(err)) = ret else {
+            panic!("Imports were not verified");
+        };
+        assert_eq!(err.message, "WasmImports '' is not signed");
+    }
+
+    #[test]
+    fn restore_globals() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        runtime
+            .execute::<(), _>("globalThis.counter = 1; var config = { a: [1, 2] };")
+            .expect("Can't execute code");
+
+        let snapshot = runtime.capture_globals().expect("Can't capture globals");
+        assert!(!snapshot.is_empty());
+
+        for _ in 0..2 {
+            runtime
+                .execute::<(), _>("counter++; config.a.push(3); globalThis.extra = true;")
+                .expect("Can't execute code");
+            runtime
+                .restore_globals(&snapshot)
+                .expect("Can't restore globals");
+
+            let val: bool = runtime
+                .execute(
+                    "counter === 1 && config.a.length === 2 && typeof globalThis.extra === 'undefined'",
+                )
+                .expect("Can't execute code");
+            assert!(val);
+        }
+    }
+
+    #[test]
+    fn restore_globals_built_ins() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("game"));
+        extension.add_function("answer", |()| 42);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let snapshot = runtime.capture_globals().expect("Can't capture globals");
+
+        runtime
+            .execute::<(), _>(
+                "Math.random = () => 4; \
+                Array.prototype.push = () => 0; \
+                Object.defineProperty(globalThis, 'hidden', { value: 1, configurable: true }); \
+                globalThis[Symbol.for('tag')] = 1; \
+                game.answer = () => 0; \
+                game.extra = 1; \
+                Object.setPrototypeOf(game, { injected: true });",
+            )
+            .expect("Can't execute code");
+        runtime
+            .restore_globals(&snapshot)
+            .expect("Can't restore globals");
+
+        let val: bool = runtime
+            .execute(
+                "Math.random() !== 4 && [].push(1) === 1 && !('hidden' in globalThis) && \
+                !(Symbol.for('tag') in globalThis) && game.answer() === 42 && \
+                !('extra' in game) && !('injected' in game)",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn lazy_global() {
+        initialize_with_defaults();
+
+        static COMPUTED: AtomicUsize = AtomicUsize::new(0);
+
+        fn compute() -> i32 {
+            COMPUTED.fetch_add(1, Ordering::Relaxed);
+            42
+        }
+
+        let mut options = RuntimeOptions::default();
+        options.lazy_global("answer", compute);
+
+        let mut runtime = Runtime::new(options, ()).expect("Can't create runtime");
+        assert_eq!(COMPUTED.load(Ordering::Relaxed), 0);
+
+        let val: i32 = runtime
+            .execute("answer + answer")
+            .expect("Can't execute code");
+        assert_eq!(val, 84);
+        assert_eq!(COMPUTED.load(Ordering::Relaxed), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn execute_module() {
+        initialize_with_defaults();
+
+        struct Loader;
+
+        impl ModuleLoader for Loader {
+            fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, String> {
+                Ok(format!(
+                    "{}/{}",
+                    referrer.trim_end_matches("/main.js"),
+                    specifier
+                ))
+            }
+
+            fn load(&self, name: &str) -> Result<String, String> {
+                match name {
+                    "app/math.js" => Ok("export const square = (x) => x * x;".to_string()),
+                    _ => Err(format!("Can't find module '{}'", name)),
+                }
+            }
+        }
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                module_loader: Some(Box::new(Loader)),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: serde_json::Value = runtime
+            .execute_module(
+                "app/main.js",
+                "import { square } from 'math.js'; \
+                export const value = square(await Promise.resolve(4));",
+            )
+            .expect("Can't execute module");
+        assert_eq!(val, serde_json::json!({ "value": 16 }));
+
+        let ret = runtime.execute_module::<serde_json::Value, _>(
+            "app/main.js",
+            "import { cube } from 'missing.js';",
+        );
+        assert!(matches!(ret, Err(Error::Script(_))));
+    }
+
+    #[test]
+    fn execute_module_with_type() {
+        initialize_with_defaults();
+
+        struct Loader;
+
+        impl ModuleLoader for Loader {
+            fn load(&self, name: &str) -> Result<String, String> {
+                match name {
+                    "config.json" => Ok(r#"{ "hp": 5, "name": "goblin" }"#.to_string()),
+                    _ => Err(format!("Can't find module '{}'", name)),
+                }
+            }
+
+            fn load_bytes(&self, name: &str) -> Result<Vec<u8>, String> {
+                match name {
+                    "sprite.bin" => Ok(vec![1, 2, 3]),
+                    _ => Err(format!("Can't find module '{}'", name)),
+                }
+            }
+        }
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                module_loader: Some(Box::new(Loader)),
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        runtime
+            .execute_module::<HashMap<String, i32>, _>(
+                "main.js",
+                "import config from 'config.json' assert { type: 'json' }; \
+                import sprite from 'sprite.bin' assert { type: 'bytes' }; \
+                globalThis.hp = config.hp; \
+                globalThis.sprite = Array.from(sprite);",
+            )
+            .expect("Can't execute module");
+
+        let hp: i32 = runtime.execute("hp").expect("Can't execute code");
+        assert_eq!(hp, 5);
+        let sprite: Vec<u8> = runtime.execute("sprite").expect("Can't execute code");
+        assert_eq!(sprite, vec![1, 2, 3]);
+
+        let ret = runtime.execute_module::<HashMap<String, i32>, _>(
+            "other.js",
+            "import config from 'config.json' assert { type: 'yaml' };",
+        );
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute_module::<HashMap<String, i32>, _>(
+            "bytes.js",
+            "import data from 'config.json' assert { type: 'bytes' };",
+        );
+        assert!(matches!(ret, Err(Error::Script(_))));
+    }
+
+    #[test]
+    fn load_bundle() {
+        initialize_with_defaults();
+
+        let mut bundle = Bundle::new("main.js");
+        bundle
+            .add_module(
+                "main.js",
+                "import { value } from 'lib.js'; globalThis.loaded = value;",
+            )
+            .add_module("lib.js", "export const value = 42;");
+        let bundle = Bundle::from_bytes(&bundle.to_bytes()).expect("Invalid bundle");
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        runtime
+            .load_bundle::<()>(&bundle)
+            .expect("Can't load bundle");
+
+        let val: i32 = runtime.execute("loaded").expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        // Invalid code caches are rejected by the engine, which then compiles the source.
+        let mut bundle = Bundle::new("cached.js");
+        bundle
+            .add_module("cached.js", "globalThis.cached = 1 + 1;")
+            .add_code_cache("cached.js", vec![1, 2, 3, 4]);
+        runtime
+            .load_bundle_bytes::<()>(&bundle.to_bytes())
+            .expect("Can't load bundle");
+        let val: i32 = runtime.execute("cached").expect("Can't execute code");
+        assert_eq!(val, 2);
+
+        let path = std::env::temp_dir().join("kopi_runtime_load_bundle.kopibundle");
+        let mut bundle = Bundle::new("file.js");
+        bundle.add_module("file.js", "globalThis.fromFile = true;");
+        std::fs::write(&path, bundle.to_bytes()).expect("Can't write bundle");
+        runtime
+            .load_bundle_file::<()>(&path)
+            .expect("Can't load bundle");
+        let _ = std::fs::remove_file(&path);
+        let val: bool = runtime.execute("fromFile").expect("Can't execute code");
+        assert!(val);
+
+        bundle.set_snapshot(vec![0; 16]);
+        let ret = runtime.load_bundle::<()>(&bundle);
+        assert!(matches!(ret, Err(Error::Bundle(_))));
+
+        let ret = runtime.load_bundle_bytes::<()>(b"KOPIBNDL");
+        assert!(matches!(ret, Err(Error::Bundle(_))));
+    }
+
+    #[test]
+    fn execute_code_shebang_and_bom() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute("\u{FEFF}#!/usr/bin/env kopi\n40 + 2")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        assert!(runtime.check("#!/usr/bin/env kopi\n1").is_empty());
+
+        let err = runtime
+            .execute::<i32, _>("#!/usr/bin/env kopi\nthrow new Error('line')")
+            .expect_err("Script didn't fail");
+        match err {
+            Error::Script(err) => assert_eq!(err.line, 2),
+            _ => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn script_error_display() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let err = runtime
+            .execute::<(), _>("let x = 1;\n\tthrow new Error('boom');")
+            .expect_err("Script didn't fail");
+        let msg = err.to_string();
+        assert!(msg.starts_with("Script error: 'Uncaught Error: boom' at <anonymous>:2:"));
+        assert!(msg.contains("\n\tthrow new Error('boom');\n\t"));
+        assert!(msg.ends_with('^'));
+
+        let err = runtime
+            .execute_module::<(), _>("main.js", "export {};\nthrow new Error('boom');")
+            .expect_err("Module didn't fail");
+        match err {
+            Error::Script(err) => {
+                assert_eq!(err.resource_name.as_deref(), Some("main.js"));
+                assert_eq!(err.line, 2);
+            }
+            _ => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn execute_code_strict_mode() {
+        initialize_with_defaults();
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                force_strict_mode: true,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let ret: Result<(), Error> = runtime.execute("leaked = 5;");
+        match ret {
+            Err(Error::Script(err)) => {
+                assert_eq!(err.line, 1);
+                assert_eq!(err.start_column, 0);
+                assert_eq!(err.source_line.as_deref(), Some("leaked = 5;"));
+            }
+            _ => panic!("Script didn't fail"),
+        }
+
+        let val: i32 = runtime.execute("40 + 2").expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        let val: Option<i32> = runtime.execute("var x = 1;").expect("Can't execute code");
+        assert_eq!(val, None);
+
+        let val: i32 = runtime
+            .execute_sloppy("legacy = 5; legacy")
+            .expect("Can't execute code");
+        assert_eq!(val, 5);
+
+        let ret: Result<i32, Error> = runtime.execute_sloppy("legacy = undefined; legacy");
+        assert!(matches!(ret, Err(Error::UndefinedResult)));
+
+        let val: i32 = runtime
+            .execute_or_default("let y = 1;")
+            .expect("Can't execute code");
+        assert_eq!(val, 0);
+
+        assert!(!runtime.check("with (Math) { PI }").is_empty());
+    }
+
+    #[test]
+    fn execute_code_into() {
+        initialize_with_defaults();
+        let mut runtime = Runtime::new(RuntimeOptions::default(), ())
+            .expect("Can't create runtime")
+            .with_scratch(1024);
+
+        let mut output = String::with_capacity(64);
+        let capacity = output.capacity();
+
+        runtime
+            .execute_into("'Hello ' + 'kopi ☕'", &mut output)
+            .expect("Can't execute code");
+        assert_eq!(output, "Hello kopi ☕");
+
+        runtime
+            .execute_into("42", &mut output)
+            .expect("Can't execute code");
+        assert_eq!(output, "42");
+        assert_eq!(output.capacity(), capacity);
+    }
+
+    #[test]
+    fn scratch_buffer_is_reused() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_function("length", |(text,): (String,)| text.chars().count() as u32);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime")
+        .with_scratch(1024);
+
+        let buffer = runtime.runtime_data.scratch.borrow().as_ptr();
+
+        let val: u32 = runtime
+            .execute("test.length('kopi ☕')")
+            .expect("Can't execute code");
+        assert_eq!(val, 6);
+
+        let scratch = runtime.runtime_data.scratch.borrow();
+        assert_eq!(scratch.as_ptr(), buffer);
+        assert_eq!(scratch.len(), 6);
+        assert!(scratch.capacity() >= 1024);
+        drop(scratch);
+
+        let val: u32 = runtime
+            .execute("test.length('a'.repeat(100000))")
+            .expect("Can't execute code");
+        assert_eq!(val, 100000);
+
+        let scratch = runtime.runtime_data.scratch.borrow();
+        assert!(scratch.is_empty());
+        assert!(scratch.capacity() >= 1024);
+        assert!(scratch.capacity() < 100000);
+    }
+
+    #[test]
+    fn execute_code_to_writer() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let mut output = Vec::new();
+        let written = runtime
+            .execute_to_writer("'kopi ☕ '.repeat(20000)", &mut output)
+            .expect("Can't execute code");
+        assert_eq!(written, output.len());
+        assert_eq!(output, "kopi ☕ ".repeat(20000).into_bytes());
+
+        let mut output = Vec::new();
+        let written = runtime
+            .execute_to_writer("new Uint8Array([1, 2, 3, 4]).subarray(1, 3)", &mut output)
+            .expect("Can't execute code");
+        assert_eq!(written, 2);
+        assert_eq!(output, vec![2, 3]);
+
+        let mut output = Vec::new();
+        let ret = runtime.execute_to_writer("42", &mut output);
+        assert!(matches!(ret, Err(Error::Type(_))));
+    }
+
+    #[test]
+    fn execute_code_error_cause() {
+        use std::error::Error as _;
+
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let ret: Result<(), Error> = runtime.execute(
+            "const inner = new TypeError('inner'); \
+            const middle = new Error('middle', { cause: inner }); \
+            throw new Error('outer', { cause: middle });",
+        );
+        let err = ret.expect_err("Expected an Script error");
+
+        let Error::Script(script_error) = &err else {
+            panic!("Expected an Script error");
+        };
+        assert!(script_error.message.contains("outer"));
+
+        let middle = err.source().expect("Expected a cause");
+        assert!(middle.to_string().contains("middle"));
+
+        let inner = middle.source().expect("Expected a cause");
+        assert!(inner.to_string().contains("inner"));
+        assert!(inner.source().is_none());
+    }
+
+    #[test]
+    fn execute_code_simple_functions() {
+        initialize_with_defaults();
+
+        let counter = Arc::new(AtomicI32::new(42));
+        let thread_counter1 = counter.clone();
+        let thread_counter2 = counter.clone();
+
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function("counter", move |()| {
+            thread_counter1.fetch_add(10, Ordering::SeqCst)
+        });
+
+        let mut global_extension = Extension::new(None);
+        global_extension.add_function("counter", move |()| {
+            thread_counter2.fetch_add(20, Ordering::SeqCst)
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension, global_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute("test.counter()")
+            .expect("Can't execute code");
+
+        assert_eq!(val, 42);
+        assert_eq!(counter.load(Ordering::SeqCst), 52);
+
+        let val: i32 = runtime.execute("counter()").expect("Can't execute code");
+
+        assert_eq!(val, 52);
+        assert_eq!(counter.load(Ordering::SeqCst), 72);
+    }
+
+    #[test]
+    fn extend_existing_object() {
+        initialize_with_defaults();
+
+        let mut math_extension = Extension::new_extending("Math");
+        math_extension.add_function("clamp", |(x, min, max): (f64, f64, f64)| x.clamp(min, max));
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![math_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: f64 = runtime
+            .execute("Math.clamp(5.0, 0.0, 1.0) + Math.max(1.0, 2.0)")
+            .expect("Can't execute code");
+        assert_eq!(val, 3.0);
+    }
+
+    #[test]
+    fn extend_existing_object_conflict() {
+        initialize_with_defaults();
+
+        let mut math_extension = Extension::new_extending("Math");
+        math_extension.add_function("max", |(x, y): (f64, f64)| x.max(y));
+
+        let result = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![math_extension],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(result, Err(Error::Extension(_))));
+
+        let mut missing_extension = Extension::new_extending("DoesNotExist");
+        missing_extension.add_function("test", |()| {});
+
+        let result = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![missing_extension],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(result, Err(Error::Extension(_))));
+    }
+
+    #[test]
+    fn execute_code_function_with_rate_limit() {
+        initialize_with_defaults();
+
+        let exceeded = Arc::new(AtomicI32::new(0));
+        let thread_exceeded = exceeded.clone();
+
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function_with_rate_limit(
+            "limited",
+            RateLimit {
+                calls_per_execute: Some(2),
+                on_exceeded: Some(Arc::new(move |name: &str| {
+                    assert_eq!(name, "test.limited");
+                    thread_exceeded.fetch_add(1, Ordering::SeqCst);
+                })),
+                ..Default::default()
+            },
+            |()| 42,
+        );
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let source = "test.limited(); test.limited(); \
+            try { test.limited(); false } catch (e) { e instanceof RangeError }";
+
+        let val: bool = runtime.execute(source).expect("Can't execute code");
+        assert!(val);
+        assert_eq!(exceeded.load(Ordering::SeqCst), 1);
+
+        // The limit is reset on every execution.
+        let val: i32 = runtime
+            .execute("test.limited()")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+        assert_eq!(exceeded.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn extension_dependencies() {
+        initialize_with_defaults();
+
+        let mut math = Extension::new_extending("core");
+        math.requires("core");
+        math.add_function("square", |(x,): (i32,)| x * x);
+
+        let mut core = Extension::new(Some("core"));
+        core.add_function("version", |()| 1);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![math, core],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute("core.square(4) + core.version()")
+            .expect("Can't execute code");
+        assert_eq!(val, 17);
+    }
+
+    #[test]
+    fn extension_namespace() {
+        initialize_with_defaults();
+
+        let mut global = Extension::new(None);
+        global
+            .namespace("game")
+            .function("spawn", |(id,): (i32,)| id + 1)
+            .namespace("math")
+            .function("square", |(x,): (i32,)| x * x);
+
+        let mut tools = Extension::new(Some("tools"));
+        tools
+            .namespace("text")
+            .function("len", |(text,): (String,)| text.len() as u32);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![global, tools],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute("game.spawn(1) + game.math.square(3) + tools.text.len('kopi')")
+            .expect("Can't execute code");
+        assert_eq!(val, 15);
+
+        let mut game = runtime.namespace("game").expect("Can't find namespace");
+        let spawn = game.function("spawn").expect("Can't find function");
+        assert_eq!(spawn.path(), "game.spawn");
+        assert!(game.function("despawn").is_none());
+        assert!(game.function("math").is_none());
+
+        let mut math = game.namespace("math").expect("Can't find namespace");
+        assert!(math.function("square").is_some());
+
+        assert!(runtime.namespace("tools.text").is_some());
+        assert!(runtime.namespace("unknown").is_none());
+    }
+
+    #[test]
+    fn extension_nested_namespace() {
+        initialize_with_defaults();
+
+        let mut vector = Extension::new(Some("game.math.vec"));
+        vector.add_function("dot", |(a, b): (Vec<i32>, Vec<i32>)| {
+            a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<i32>()
+        });
+
+        let mut game = Extension::new(Some("game"));
+        game.add_function("spawn", |(id,): (i32,)| id + 1);
+
+        let mut math = Extension::new(Some("game.math"));
+        math.add_function("square", |(x,): (i32,)| x * x);
+
+        let mut global = Extension::new(None);
+        global.namespace("game").function("version", |()| 1);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![vector, game, math, global],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute(
+                "game.version() + game.spawn(1) + game.math.square(3) \
+                    + game.math.vec.dot([1, 2], [3, 4])",
+            )
+            .expect("Can't execute code");
+        assert_eq!(val, 1 + 2 + 9 + 11);
+        assert!(runtime.namespace("game.math.vec").is_some());
+
+        let mut first = Extension::<()>::new(Some("game"));
+        first.add_function("spawn", |(id,): (i32,)| id);
+        let mut second = Extension::<()>::new(Some("game"));
+        second.add_function("spawn", |(id,): (i32,)| id);
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![first, second],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::Extension(_))));
+    }
 
     #[test]
-    fn runtime_creation() {
+    fn extension_intercept_modifications() {
+        initialize_with_defaults();
+
+        let attempts = Rc::new(RefCell::new(Vec::new()));
+        let interceptor_attempts = attempts.clone();
+
+        let mut game = Extension::new(Some("game"));
+        game.add_function("spawn", |(id,): (i32,)| id);
+        game.intercept_modifications(move |path, operation| {
+            interceptor_attempts
+                .borrow_mut()
+                .push((path.to_string(), operation));
+            path == "game.score"
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![game],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute_sloppy(
+                "game.spawn = () => 0; \
+                 delete game.spawn; \
+                 game.score = 10; \
+                 game = {}; \
+                 game.spawn(1) + game.score",
+            )
+            .expect("Can't execute code");
+        assert_eq!(val, 11);
+
+        let ret =
+            runtime.execute::<(), _>("Object.defineProperty(game, 'spawn', { value: () => 0 })");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        assert_eq!(
+            *attempts.borrow(),
+            vec![
+                ("game.spawn".to_string(), BindingOperation::Set),
+                ("game.spawn".to_string(), BindingOperation::Delete),
+                ("game.score".to_string(), BindingOperation::Set),
+                ("game.spawn".to_string(), BindingOperation::Define),
+            ]
+        );
+
+        let mut global = Extension::<()>::new(None);
+        global.intercept_modifications(|_, _| false);
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![global],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::Extension(_))));
+    }
+
+    #[test]
+    fn realm() {
+        initialize_with_defaults();
+
+        let mut main = Extension::new(Some("host"));
+        main.add_function("name", |()| "main".to_string());
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![main],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let mut tenant = Extension::new(Some("host"));
+        tenant.add_function("name", |()| "tenant".to_string());
+        let realm = runtime
+            .create_realm(vec![tenant])
+            .expect("Can't create realm");
+        let empty = runtime.create_realm(vec![]).expect("Can't create realm");
+
+        runtime
+            .execute::<(), _>("globalThis.shared = 1;")
+            .expect("Can't execute code");
+
+        let val: String = runtime
+            .execute_in(&realm, "host.name()")
+            .expect("Can't execute code");
+        assert_eq!(val, "tenant");
+
+        let val: bool = runtime
+            .execute_in(&realm, "typeof shared === 'undefined'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: bool = runtime
+            .execute_in(&empty, "typeof host === 'undefined'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: String = runtime.execute("host.name()").expect("Can't execute code");
+        assert_eq!(val, "main");
+
+        let ret = runtime.execute_in::<(), _>(&Realm { index: 42, ..realm }, "1");
+        assert!(matches!(ret, Err(Error::Internal(_))));
+
+        // A realm with a valid index must not run inside another runtime.
+        let mut other = Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        other.create_realm(vec![]).expect("Can't create realm");
+        other.create_realm(vec![]).expect("Can't create realm");
+        let ret = other.execute_in::<String, _>(&realm, "host.name()");
+        assert!(matches!(ret, Err(Error::Internal(_))));
+    }
+
+    #[test]
+    fn realm_security_token() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        let realm = runtime.create_realm(vec![]).expect("Can't create realm");
+        runtime
+            .execute_in::<(), _>(&realm, "globalThis.secret = 42;")
+            .expect("Can't execute code");
+
+        // Exposes the global object of the realm to the main context.
+        {
+            let scope =
+                &mut v8::HandleScope::with_context(&mut runtime.isolate, &runtime.main_context);
+            let realm_context = v8::Local::new(scope, &runtime.realms[realm.index]);
+            let realm_global = realm_context.global(scope);
+            let global = scope.get_current_context().global(scope);
+            let key = v8::String::new(scope, "tenant").expect("Can't create string");
+            global.set(scope, key.into(), realm_global.into());
+        }
+
+        let ret = runtime.execute::<i32, _>("tenant.secret");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        runtime
+            .set_security_token(None, "shared")
+            .expect("Can't set security token");
+        runtime
+            .set_security_token(Some(&realm), "shared")
+            .expect("Can't set security token");
+        let val: i32 = runtime
+            .execute("tenant.secret")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+
+        runtime
+            .use_default_security_token(Some(&realm))
+            .expect("Can't reset security token");
+        let ret = runtime.execute::<i32, _>("tenant.secret");
+        assert!(matches!(ret, Err(Error::Script(_))));
+    }
+
+    #[cfg(feature = "hide-intl")]
+    #[test]
+    fn hide_intl() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        let realm = runtime.create_realm(vec![]).expect("Can't create realm");
+
+        let val: bool = runtime
+            .execute("typeof Intl === 'undefined'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: bool = runtime
+            .execute_in(&realm, "typeof Intl === 'undefined'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        for source in [
+            "'a'.localeCompare('b')",
+            "'a'.normalize()",
+            "(1).toLocaleString()",
+            "new Date(0).toLocaleDateString()",
+        ] {
+            let ret = runtime.execute::<(), _>(source);
+            assert!(
+                matches!(&ret, Err(Error::Script(err)) if err.message.contains("without Intl")),
+                "{} didn't throw",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn host_call_breadcrumbs() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("game"));
+        extension.add_function("spawn", |(id,): (i32,)| id);
+        extension.add_function("despawn", |(_id,): (i32,)| ());
+        extension.add_static_function("sub", sub);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                host_call_breadcrumbs: 3,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let err = runtime
+            .execute::<(), _>(
+                "game.spawn(1); game.spawn(2); game.sub(2, 3); game.despawn(1); \
+                 throw new Error('boom');",
+            )
+            .expect_err("Script must fail");
+        let Error::Script(script_error) = err else {
+            panic!("Expected a script error, got {:?}", err);
+        };
+        assert_eq!(
+            script_error.host_calls,
+            vec!["game.spawn", "sub", "game.despawn"]
+        );
+    }
+
+    #[test]
+    fn extension_function_order() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("ordered"));
+        for name in ["zeta", "alpha", "mu", "beta"] {
+            extension.add_function(name, |()| 0);
+        }
+        extension.add_function("alpha", |()| 1);
+        assert_eq!(
+            extension.names().collect::<Vec<_>>(),
+            vec!["zeta", "alpha", "mu", "beta"]
+        );
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let keys: Vec<String> = runtime
+            .execute("Object.keys(ordered)")
+            .expect("Can't execute code");
+        assert_eq!(keys, vec!["zeta", "alpha", "mu", "beta"]);
+
+        let alpha: i32 = runtime
+            .execute("ordered.alpha()")
+            .expect("Can't execute code");
+        assert_eq!(alpha, 1);
+    }
+
+    #[test]
+    fn extension_property() {
+        initialize_with_defaults();
+
+        let frame_time = Arc::new(AtomicU32::new(16));
+        let getter_frame_time = frame_time.clone();
+        let setter_frame_time = frame_time.clone();
+
+        let mut extension = Extension::new(Some("game"));
+        extension.add_property(
+            "frameTime",
+            move || getter_frame_time.load(Ordering::SeqCst),
+            move |value: u32| setter_frame_time.store(value, Ordering::SeqCst),
+        );
+        extension.add_getter("version", || "1.0".to_string());
+
+        let mut global = Extension::new(None);
+        global.namespace("engine").getter("ticks", || 42);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension, global],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: u32 = runtime
+            .execute("game.frameTime")
+            .expect("Can't execute code");
+        assert_eq!(val, 16);
+
+        frame_time.store(33, Ordering::SeqCst);
+        let val: u32 = runtime
+            .execute("game.frameTime = game.frameTime + 1; game.frameTime")
+            .expect("Can't execute code");
+        assert_eq!(val, 34);
+        assert_eq!(frame_time.load(Ordering::SeqCst), 34);
+
+        let val: String = runtime
+            .execute("`${game.version} ${engine.ticks}`")
+            .expect("Can't execute code");
+        assert_eq!(val, "1.0 42");
+
+        let ret = runtime.execute::<(), _>("game.frameTime = 'slow'");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute::<(), _>("'use strict'; game.version = '2.0'");
+        assert!(matches!(ret, Err(Error::Script(_))));
+    }
+
+    #[test]
+    fn class() {
+        initialize_with_defaults();
+
+        struct Counter {
+            count: i32,
+            step: i32,
+        }
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_class(
+            Class::new("Counter")
+                .constructor(|(start,): (i32,)| Counter {
+                    count: start,
+                    step: 1,
+                })
+                .method("increment", |counter, ()| {
+                    counter.count += counter.step;
+                    counter.count
+                })
+                .getter("count", |counter| counter.count)
+                .property(
+                    "step",
+                    |counter| counter.step,
+                    |counter, step: i32| counter.step = step,
+                ),
+        );
+        extension.add_class(Class::<Counter>::new("Opaque"));
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute(
+                "const counter = new test.Counter(10); \
+                 counter.increment(); \
+                 counter.step = 5; \
+                 counter.increment(); \
+                 counter.count + counter.step",
+            )
+            .expect("Can't execute code");
+        assert_eq!(val, 21);
+
+        let val: bool = runtime
+            .execute(
+                "counter instanceof test.Counter && \
+                 test.Counter.name === 'Counter' && \
+                 new test.Counter(1) !== new test.Counter(1)",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+
+        let ret = runtime.execute::<(), _>("test.Counter(1)");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute::<(), _>("counter.count = 1");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute::<(), _>("counter.step = 'fast'");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute::<(), _>("test.Counter.prototype.increment.call({})");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let ret = runtime.execute::<(), _>("new test.Opaque()");
+        assert!(matches!(ret, Err(Error::Script(_))));
+
+        let val: String = runtime
+            .execute("try { new test.Counter('ten'); 'created' } catch (e) { e.name }")
+            .expect("Can't execute code");
+        assert_eq!(val, "TypeError");
+    }
+
+    #[test]
+    fn class_finalizer_while_borrowed() {
+        initialize_with_defaults();
+
+        struct Counter;
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_class(Class::new("Counter").constructor(|()| Counter));
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        runtime
+            .execute::<(), _>("for (let i = 0; i < 10; i++) { new test.Counter(); }")
+            .expect("Can't execute code");
+
+        {
+            // The finalizers run while the instances are borrowed.
+            let instances = runtime.runtime_data.class_instances.borrow_mut();
+            assert_eq!(instances.len(), 10);
+
+            runtime.isolate.low_memory_notification();
+            let platform = v8::V8::get_current_platform();
+            while v8::Platform::pump_message_loop(&platform, &mut runtime.isolate, false) {}
+
+            assert_eq!(instances.len(), 10);
+        }
+
+        assert!(runtime.runtime_data.class_instances.borrow_mut().is_empty());
+    }
+
+    #[test]
+    fn extension_footprint() {
+        initialize_with_defaults();
+
+        let offset = 10;
+        let mut global = Extension::new(None);
+        global.add_function("offset", move |(x,): (i32,)| x + offset);
+        global
+            .namespace("game")
+            .function("spawn", |(id,): (i32,)| id);
+
+        let mut events = Extension::new(Some("events"));
+        events.set_prototype("({})");
+        events.add_fastcall_function("add", add);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![global, events],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let footprints = runtime.extension_footprint();
+        assert_eq!(
+            footprints,
+            &[
+                ExtensionFootprint {
+                    name: "global".to_string(),
+                    functions: 2,
+                    closures: 2,
+                    closure_bytes: std::mem::size_of::<i32>(),
+                    templates: 3,
+                    script_bytes: 0,
+                },
+                ExtensionFootprint {
+                    name: "events".to_string(),
+                    functions: 1,
+                    closures: 0,
+                    closure_bytes: 0,
+                    templates: 1,
+                    script_bytes: 4,
+                },
+            ]
+        );
+
+        let val: i32 = runtime
+            .execute("offset(game.spawn(1)) + events.add(1, 2)")
+            .expect("Can't execute code");
+        assert_eq!(val, 14);
+    }
+
+    #[test]
+    fn extension_prototype() {
+        initialize_with_defaults();
+
+        let mut events = Extension::new(Some("events"));
+        events.set_prototype("({ on(name, listener) { this[name] = listener; return true; } })");
+        events.add_function("emit", |(x,): (i32,)| x * 2);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![events],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: bool = runtime
+            .execute("events.on('tick', () => 1) && typeof events.tick === 'function'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: i32 = runtime
+            .execute("events.emit(21)")
+            .expect("Can't execute code");
+        assert_eq!(val, 42);
+    }
+
+    #[test]
+    fn extension_prototype_invalid() {
+        initialize_with_defaults();
+
+        let mut events = Extension::<()>::new(Some("events"));
+        events.set_prototype("42");
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![events],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::Extension(_))));
+
+        let mut global = Extension::<()>::new(None);
+        global.set_prototype("({})");
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![global],
+                ..Default::default()
+            },
+            (),
+        );
+        match ret {
+            Err(Error::Extension(msg)) => {
+                assert_eq!(msg, "A global extension can't have a prototype")
+            }
+            _ => panic!("Runtime was created"),
+        }
+    }
+
+    #[test]
+    fn extension_dependencies_missing() {
+        initialize_with_defaults();
+
+        let mut math = Extension::<()>::new(Some("math"));
+        math.requires("core");
+
+        let ret = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![math],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::Extension(_))));
+    }
+
+    #[test]
+    fn extension_dependencies_cyclic() {
         initialize_with_defaults();
 
-        // Multiple runtimes can be created.
-        let runtime0 = Runtime::new(RuntimeOptions::default(), ());
-        assert!(runtime0.is_ok());
+        let mut a = Extension::<()>::new(Some("a"));
+        a.requires("b");
+        let mut b = Extension::<()>::new(Some("b"));
+        b.requires("a");
 
-        let runtime1 = Runtime::new(RuntimeOptions::default(), ());
-        assert!(runtime1.is_ok());
+        let ret = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![a, b],
+                ..Default::default()
+            },
+            (),
+        );
+        assert!(matches!(ret, Err(Error::Extension(_))));
     }
 
     #[test]
-    fn runtime_creation_multiple_thread() {
+    fn counters() {
         initialize_with_defaults();
 
-        let handle0: JoinHandle<Result<(), Error>> = std::thread::spawn(|| {
-            let mut runtime0 = Runtime::new(RuntimeOptions::default(), ())?;
-            let val: i32 = runtime0.execute("var x = 30; x")?;
-            assert_eq!(val, 30);
-            Ok(())
-        });
+        let mut extension = Extension::new(None);
+        extension.add_function("add", |(a, b): (i32, i32)| a + b);
 
-        let handle1: JoinHandle<Result<(), Error>> = std::thread::spawn(|| {
-            let mut runtime1 = Runtime::new(RuntimeOptions::default(), ())?;
-            let val: i32 = runtime1.execute("var x = 20; x")?;
-            assert_eq!(val, 20);
-            Ok(())
-        });
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                script_cache_capacity: 4,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
 
-        handle0.join().expect("thread 0 died").expect("error found");
-        handle1.join().expect("thread 1 died").expect("error found");
+        for _ in 0..2 {
+            let val: i32 = runtime.execute("add(1, 2)").expect("Can't execute code");
+            assert_eq!(val, 3);
+        }
+        assert!(runtime.execute::<i32, _>("throw new Error()").is_err());
+
+        let counters = runtime.counters();
+        assert_eq!(counters.scripts_compiled, 2);
+        assert_eq!(counters.executions, 3);
+        assert_eq!(counters.exceptions_thrown, 1);
+        assert_eq!(counters.host_calls, 2);
+        assert_eq!(counters.microtask_checkpoints, 3);
     }
 
     #[test]
-    fn heap_statistics() {
-        const MAX_HEAP_SIZE: usize = 5 * 1024 * 1024;
-
+    fn strict_bigints() {
         initialize_with_defaults();
 
+        let mut extension = Extension::new(None);
+        extension.add_function("big", |(x,): (u64,)| x.to_string());
+        extension.add_function("small", |(x,): (u32,)| x.to_string());
+
         let mut runtime = Runtime::new(
             RuntimeOptions {
-                max_heap_size: MAX_HEAP_SIZE,
+                extensions: vec![extension],
+                strict_bigints: true,
                 ..Default::default()
             },
             (),
         )
-        .expect("Can't not create runtime");
+        .expect("Can't create runtime");
 
-        let heap_statistics = runtime.heap_statistics();
+        let val: String = runtime.execute("big(42n)").expect("Can't execute code");
+        assert_eq!(val, "42");
+        let val: String = runtime.execute("small(42)").expect("Can't execute code");
+        assert_eq!(val, "42");
 
-        // This only tests if the values make some sense.
-        assert!(heap_statistics.heap_size_limit() >= MAX_HEAP_SIZE);
-        assert!(heap_statistics.total_heap_size() >= 64 * 1024);
-        assert!(heap_statistics.used_heap_size() >= 64 * 1024);
-        assert!(heap_statistics.total_physical_size() >= 64 * 1024);
+        let val: String = runtime.execute("big(42).code").expect("Can't execute code");
+        assert_eq!(val, "ERR_ARG_TYPE");
+        let val: String = runtime
+            .execute("big(NaN).code")
+            .expect("Can't execute code");
+        assert_eq!(val, "ERR_ARG_TYPE");
+        let val: String = runtime
+            .execute("small(42n).code")
+            .expect("Can't execute code");
+        assert_eq!(val, "ERR_ARG_TYPE");
     }
 
     #[test]
-    fn execute_code() {
+    fn type_error_code() {
         initialize_with_defaults();
-        let mut runtime =
-            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
 
-        let val: i32 = runtime.execute("42 + 3").expect("Can't execute code");
+        let mut extension = Extension::new(None);
+        extension.add_function("byte", |(x,): (u8,)| x);
 
-        assert_eq!(val, 45);
-    }
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
 
-    #[test]
-    fn execute_code_is_stateful() {
-        initialize_with_defaults();
-        let mut runtime =
-            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        let val: String = runtime
+            .execute("byte(256).code")
+            .expect("Can't execute code");
+        assert_eq!(val, "ERR_ARG_RANGE");
 
-        let val: i32 = runtime.execute("var x = 1; x").expect("Can't execute code");
-        assert_eq!(val, 1);
+        let val: String = runtime
+            .execute("byte({}).code")
+            .expect("Can't execute code");
+        assert_eq!(val, "ERR_ARG_TYPE");
 
-        let val: i32 = runtime.execute("x += 2; x").expect("Can't execute code");
-        assert_eq!(val, 3);
+        let val: String = runtime
+            .execute("const e = byte('x'); `${e.path} ${e.expected} ${e.actual}`")
+            .expect("Can't execute code");
+        assert_eq!(val, "arguments[0] u8 string");
+
+        let err = runtime
+            .execute::<u8, _>("256")
+            .expect_err("Conversion didn't fail");
+        match err {
+            Error::Type(err) => assert_eq!(err.code, ErrorCode::ArgumentRange),
+            _ => panic!("Unexpected error: {}", err),
+        }
     }
 
     #[test]
-    fn execute_code_compile_error() {
+    fn conversion_error_stats() {
         initialize_with_defaults();
-        let mut runtime =
-            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
 
-        let ret: Result<(), Error> = runtime.execute("var = let");
-        let err = ret.expect_err("Expected an Script error");
-        assert!(matches!(err, Error::Script { .. }))
-    }
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function("square", |(x,): (i32,)| x * x);
+        test_extension.add_function("negate", |(x,): (bool,)| !x);
 
-    #[test]
-    fn execute_code_execution_error() {
-        initialize_with_defaults();
-        let mut runtime =
-            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
 
-        let ret: Result<(), Error> = runtime.execute("unknown_function()");
-        let err = ret.expect_err("Expected an Script error");
-        assert!(matches!(err, Error::Script { .. }))
+        let source = "test.square('a'); test.square(Symbol()); test.square(2); test.negate(true);";
+        runtime
+            .execute::<(), _>(source)
+            .expect("Can't execute code");
+
+        let stats = runtime.conversion_error_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats.get("test.square"), Some(&2));
+
+        runtime.reset_conversion_error_stats();
+        assert!(runtime.conversion_error_stats().is_empty());
     }
 
     #[test]
-    fn execute_code_simple_functions() {
+    fn host_call_depth_limit() {
         initialize_with_defaults();
 
-        let counter = Arc::new(AtomicI32::new(42));
-        let thread_counter1 = counter.clone();
-        let thread_counter2 = counter.clone();
-
-        let mut test_extension = Extension::new(Some("test"));
-        test_extension.add_function("counter", move |()| {
-            thread_counter1.fetch_add(10, Ordering::SeqCst)
-        });
-
         let mut global_extension = Extension::new(None);
-        global_extension.add_function("counter", move |()| {
-            thread_counter2.fetch_add(20, Ordering::SeqCst)
-        });
+        global_extension.add_function("echo", |(text,): (String,)| text);
 
         let mut runtime = Runtime::new(
             RuntimeOptions {
-                extensions: vec![test_extension, global_extension],
+                extensions: vec![global_extension],
+                max_host_call_depth: Some(8),
                 ..Default::default()
             },
             (),
         )
         .expect("Can't create runtime");
 
-        let val: i32 = runtime
-            .execute("test.counter()")
-            .expect("Can't execute code");
-
-        assert_eq!(val, 42);
-        assert_eq!(counter.load(Ordering::SeqCst), 52);
+        // Converting the argument into a string calls `toString()`, which calls the host
+        // function again.
+        let source = "var depth = 0; \
+            var o = { toString() { depth++; return echo(o); } }; \
+            try { echo(o); false } catch (e) { e instanceof RangeError && depth === 8 }";
 
-        let val: i32 = runtime.execute("counter()").expect("Can't execute code");
+        let val: bool = runtime.execute(source).expect("Can't execute code");
+        assert!(val);
 
-        assert_eq!(val, 52);
-        assert_eq!(counter.load(Ordering::SeqCst), 72);
+        let val: String = runtime
+            .execute("echo('still working')")
+            .expect("Can't execute code");
+        assert_eq!(val, "still working");
     }
 
     #[test]
@@ -443,6 +5401,45 @@ mod test {
         assert_eq!(counter.load(Ordering::SeqCst), 45);
     }
 
+    #[test]
+    fn execute_code_function_with_defaults() {
+        initialize_with_defaults();
+
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function_with_defaults(
+            "madd",
+            (1.0, 2.0, 3.0),
+            move |(a, b, c): (f64, f64, f64)| a + (b * c),
+        );
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: f64 = runtime.execute("test.madd()").expect("Can't execute code");
+        assert_eq!(val, 7.0);
+
+        let val: f64 = runtime
+            .execute("test.madd(10)")
+            .expect("Can't execute code");
+        assert_eq!(val, 16.0);
+
+        let val: f64 = runtime
+            .execute("test.madd(10, undefined, 5)")
+            .expect("Can't execute code");
+        assert_eq!(val, 20.0);
+
+        let val: f64 = runtime
+            .execute("test.madd(10, 2, 5)")
+            .expect("Can't execute code");
+        assert_eq!(val, 20.0);
+    }
+
     #[test]
     fn execute_code_simple_function_with_state() {
         initialize_with_defaults();
@@ -472,6 +5469,32 @@ mod test {
         assert_eq!(val, 60);
     }
 
+    #[test]
+    fn execute_code_function_mut() {
+        initialize_with_defaults();
+
+        let mut history = Vec::new();
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function_mut("record", move |(x,): (i32,)| {
+            history.push(x);
+            history.iter().sum::<i32>()
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute("test.record(1); test.record(2); test.record(3)")
+            .expect("Can't execute code");
+        assert_eq!(val, 6);
+    }
+
     static_function! {
         fn sub(x: i32, y: i32) -> i32 {
             x - y
@@ -595,4 +5618,164 @@ mod test {
 
         assert_eq!(*state.borrow(), 101);
     }
+
+    #[test]
+    fn execute_code_fastcall_disabled() {
+        initialize_with_defaults();
+
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_fastcall_function("add", add);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension],
+                disable_fastcalls: true,
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: i32 = runtime
+            .execute(
+                "let sum = 0; for (let i = 0; i < 100000; i++) { sum = test.add(sum, 1); } sum",
+            )
+            .expect("Can't execute code");
+
+        assert_eq!(val, 100000);
+    }
+
+    #[test]
+    fn pause_runtime() {
+        initialize_with_defaults();
+
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function_with_state("counter", move |state: &mut i32, ()| {
+            *state += 1;
+            *state
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension, TimerExtension::create()],
+                ..Default::default()
+            },
+            0,
+        )
+        .expect("Can't create runtime");
+
+        runtime
+            .execute::<(), _>(
+                "test.counter(); var saved = 'level 1'; setTimeout(test.counter, 100);",
+            )
+            .expect("Can't execute code");
+
+        let start = Instant::now();
+        runtime.poll_timers(start);
+        runtime.poll_timers(start + Duration::from_millis(60));
+
+        let snapshot = {
+            let mut guard = runtime.pause();
+            assert_eq!(*guard.state(), 1);
+            *guard.state_mut() = 10;
+            guard.capture_globals().expect("Can't capture globals")
+        };
+
+        // The time of the pause doesn't count, 40 ms of the delay are left.
+        assert_eq!(runtime.poll_timers(start + Duration::from_secs(5)), 0);
+        assert_eq!(
+            runtime.poll_timers(start + Duration::from_secs(5) + Duration::from_millis(39)),
+            0
+        );
+        assert_eq!(
+            runtime.poll_timers(start + Duration::from_secs(5) + Duration::from_millis(40)),
+            1
+        );
+
+        runtime
+            .execute::<(), _>("saved = 'level 2';")
+            .expect("Can't execute code");
+        runtime
+            .restore_globals(&snapshot)
+            .expect("Can't restore globals");
+
+        let val: String = runtime.execute("saved").expect("Can't execute code");
+        assert_eq!(val, "level 1");
+
+        let val: i32 = runtime
+            .execute("test.counter()")
+            .expect("Can't execute code");
+        assert_eq!(val, 12);
+    }
+
+    #[test]
+    fn execute_sliced() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let mut slices = Vec::new();
+        let val: i32 = runtime
+            .execute_sliced(
+                "const end = Date.now() + 50; let n = 0; while (Date.now() < end) { n++; } 42",
+                Duration::from_millis(5),
+                |execution| slices.push(execution.slices()),
+            )
+            .expect("Can't execute code");
+
+        assert_eq!(val, 42);
+        assert!(!slices.is_empty());
+        assert!(slices.iter().copied().eq(1..=slices.len() as u32));
+    }
+
+    #[test]
+    fn execute_sliced_terminate() {
+        initialize_with_defaults();
+
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let result = runtime.execute_sliced::<(), _, _>(
+            "while (true) {}",
+            Duration::from_millis(1),
+            |execution| {
+                if execution.slices() == 3 {
+                    execution.terminate();
+                }
+            },
+        );
+        assert!(matches!(result, Err(Error::Terminated)));
+
+        let val: i32 = runtime.execute("1 + 1").expect("Can't execute code");
+        assert_eq!(val, 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn report_leaked_closures() {
+        initialize_with_defaults();
+
+        let mut test_extension = Extension::new(Some("test"));
+        test_extension.add_function("one", |()| 1);
+        test_extension.add_function("two", |()| 2);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![test_extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        assert!(runtime.leaked_closures().is_empty());
+
+        // Simulates extension code, that keeps a closure beyond the lifetime of the runtime.
+        let leaked = runtime.closures[1].1.clone();
+        assert_eq!(runtime.leaked_closures(), vec![("test.two".to_string(), 1)]);
+
+        drop(runtime);
+        assert_eq!(Arc::strong_count(&leaked), 1);
+    }
 }
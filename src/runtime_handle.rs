@@ -0,0 +1,71 @@
+//! Implements the handle to control a runtime from other threads.
+
+use std::ffi::c_void;
+
+use crate::value::{Object, Seal, ValueScope};
+
+/// A callback, that runs inside the runtime once it handles an interrupt.
+type InterruptCallback =
+    Box<dyn for<'scope> FnOnce(&mut ValueScope<'scope>, Object<'scope>) + Send>;
+
+/// A handle to a [`crate::Runtime`], that can be sent to other threads.
+///
+/// Can be used by a supervisor thread to stop runaway scripts. Created by
+/// [`crate::Runtime::handle()`]. The methods return `false` once the runtime was dropped.
+#[derive(Clone)]
+pub struct RuntimeHandle(v8::IsolateHandle);
+
+impl RuntimeHandle {
+    pub(crate) fn new(handle: v8::IsolateHandle) -> Self {
+        Self(handle)
+    }
+
+    /// Terminates the running script, which results in [`crate::error::Error::Terminated`].
+    ///
+    /// If no script is running, the next script that runs inside the runtime is terminated.
+    pub fn terminate_execution(&self) -> bool {
+        self.0.terminate_execution()
+    }
+
+    /// Returns `true` if a termination was requested and the runtime is still unwinding the
+    /// terminated script.
+    pub fn is_execution_terminating(&self) -> bool {
+        self.0.is_execution_terminating()
+    }
+
+    /// Requests the callback to be run inside the runtime, once the running script reaches a
+    /// safe point. The callback runs on the thread of the runtime and receives the global
+    /// object, like the closure of [`crate::Runtime::scope()`].
+    ///
+    /// If no script is running, the callback runs once the next script runs inside the runtime.
+    /// Callbacks that never run are leaked.
+    pub fn request_interrupt<F>(&self, callback: F) -> bool
+    where
+        F: for<'scope> FnOnce(&mut ValueScope<'scope>, Object<'scope>) + Send + 'static,
+    {
+        let callback: Box<InterruptCallback> = Box::new(Box::new(callback));
+        let data = Box::into_raw(callback) as *mut c_void;
+
+        if !self.0.request_interrupt(run_interrupt, data) {
+            // SAFETY: The interrupt was not scheduled, so the data is still owned.
+            drop(unsafe { Box::from_raw(data as *mut InterruptCallback) });
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Runs the callback of an interrupt. Is called by the engine on the thread of the isolate.
+extern "C" fn run_interrupt(isolate: &mut v8::Isolate, data: *mut c_void) {
+    // SAFETY: The data was created with `Box::into_raw()` when the interrupt was requested.
+    let callback = unsafe { Box::from_raw(data as *mut InterruptCallback) };
+
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = scope.get_current_context();
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let global = context.global(scope);
+
+    callback(scope.seal(), global.seal());
+}
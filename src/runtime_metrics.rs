@@ -0,0 +1,53 @@
+//! Emits the counters and statistics of the runtimes through the `metrics` facade.
+//!
+//! The following metrics are emitted:
+//!
+//! * `kopi_scripts_compiled_total` (counter)
+//! * `kopi_executions_total` (counter)
+//! * `kopi_exceptions_total` (counter)
+//! * `kopi_host_calls_total` (counter)
+//! * `kopi_microtask_checkpoints_total` (counter)
+//! * `kopi_execute_duration_seconds` (histogram)
+//! * `kopi_heap_used_bytes` (gauge)
+//! * `kopi_heap_total_bytes` (gauge)
+
+use std::time::Duration;
+
+use crate::{heap_statistics::HeapStatistics, runtime::RuntimeCounters};
+
+/// Emits the increments of the counters of a runtime.
+pub(crate) fn record_counters(old: &RuntimeCounters, new: &RuntimeCounters) {
+    let increments = [
+        (
+            "kopi_scripts_compiled_total",
+            new.scripts_compiled - old.scripts_compiled,
+        ),
+        ("kopi_executions_total", new.executions - old.executions),
+        (
+            "kopi_exceptions_total",
+            new.exceptions_thrown - old.exceptions_thrown,
+        ),
+        ("kopi_host_calls_total", new.host_calls - old.host_calls),
+        (
+            "kopi_microtask_checkpoints_total",
+            new.microtask_checkpoints - old.microtask_checkpoints,
+        ),
+    ];
+
+    for (name, increment) in increments {
+        if increment > 0 {
+            metrics::counter!(name, increment);
+        }
+    }
+}
+
+/// Emits the duration of an execution.
+pub(crate) fn record_execution(duration: Duration) {
+    metrics::histogram!("kopi_execute_duration_seconds", duration);
+}
+
+/// Emits the heap statistics of a runtime.
+pub(crate) fn record_heap_statistics(statistics: &HeapStatistics) {
+    metrics::gauge!("kopi_heap_used_bytes", statistics.used_heap_size() as f64);
+    metrics::gauge!("kopi_heap_total_bytes", statistics.total_heap_size() as f64);
+}
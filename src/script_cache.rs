@@ -0,0 +1,114 @@
+//! Implements the cache of compiled scripts.
+
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+/// Statistics about the script cache of a runtime.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScriptCacheStatistics {
+    /// Number of executions that re-used a compiled script.
+    pub hits: u64,
+    /// Number of executions that needed to compile the script.
+    pub misses: u64,
+    /// Number of scripts that are currently cached.
+    pub entries: usize,
+}
+
+struct ScriptCacheEntry {
+    hash: u64,
+    source: String,
+    script: v8::Global<v8::UnboundScript>,
+}
+
+/// LRU cache of compiled scripts, keyed by the hash of their source.
+pub(crate) struct ScriptCache {
+    capacity: usize,
+    /// The least recently used entry is at the front.
+    entries: VecDeque<ScriptCacheEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+impl ScriptCache {
+    /// Creates a new [`ScriptCache`]. A capacity of `0` disables the cache.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns `true` if the cache is enabled.
+    #[inline(always)]
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns the hash of the given source.
+    pub(crate) fn hash(source: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the compiled script of the source and marks it as the most recently used script.
+    pub(crate) fn get(
+        &mut self,
+        hash: u64,
+        source: &str,
+    ) -> Option<&v8::Global<v8::UnboundScript>> {
+        let Some(index) = self
+            .entries
+            .iter()
+            .position(|entry| entry.hash == hash && entry.source == source)
+        else {
+            self.misses += 1;
+            return None;
+        };
+
+        self.hits += 1;
+        let entry = self.entries.remove(index)?;
+        self.entries.push_back(entry);
+        self.entries.back().map(|entry| &entry.script)
+    }
+
+    /// Inserts a compiled script. Evicts the least recently used script if the cache is full.
+    pub(crate) fn insert(
+        &mut self,
+        hash: u64,
+        source: String,
+        script: v8::Global<v8::UnboundScript>,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(ScriptCacheEntry {
+            hash,
+            source,
+            script,
+        });
+    }
+
+    /// Removes all cached scripts.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the statistics of the cache.
+    pub(crate) fn statistics(&self) -> ScriptCacheStatistics {
+        ScriptCacheStatistics {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Implements the origin of classic scripts.
+
+use crate::value::{new_string, NewStringType};
+
+/// The origin of a classic script, that is reported in script errors and stack traces and used
+/// by the developer tools to locate the source map of the script.
+///
+/// Scripts without an origin are reported as `<anonymous>`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScriptOrigin {
+    /// The name of the resource, for example the file name of the script.
+    pub name: String,
+    /// The line offset of the script inside the resource (zero based).
+    pub line_offset: i32,
+    /// The column offset of the first line of the script inside the resource (zero based).
+    pub column_offset: i32,
+    /// The URL of the source map of the script.
+    pub source_map_url: Option<String>,
+}
+
+impl ScriptOrigin {
+    /// Creates a new origin with the given resource name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates the V8 origin of the script.
+    pub(crate) fn to_v8<'scope>(
+        &self,
+        scope: &mut v8::HandleScope<'scope>,
+    ) -> v8::ScriptOrigin<'scope> {
+        let name = new_string(scope, &self.name, NewStringType::Normal);
+        let source_map_url: v8::Local<v8::Value> = match self.source_map_url.as_deref() {
+            Some(url) => new_string(scope, url, NewStringType::Normal).into(),
+            None => v8::undefined(scope).into(),
+        };
+
+        v8::ScriptOrigin::new(
+            scope,
+            name.into(),
+            self.line_offset,
+            self.column_offset,
+            false,
+            0,
+            source_map_url,
+            false,
+            false,
+            false,
+        )
+    }
+}
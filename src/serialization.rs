@@ -1,9 +1,14 @@
 //! Implements the serialization / deserialization of ECMAScript values.
 
 mod deserialize_impl;
+mod numbers;
 mod serialize_impl;
+mod strings;
 pub use deserialize_impl::*;
+pub(crate) use numbers::NUMBER_POLICY;
 pub use serialize_impl::*;
+pub(crate) use strings::{deserialize_string_into, STRICT_STRINGS};
+pub use strings::{OneByteString, StrictString, WString};
 
 #[cfg(feature = "serde")]
 mod serde;
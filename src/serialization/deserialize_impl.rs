@@ -1,9 +1,56 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{
+    numbers::{clamped_special_number, strict_bigints},
+    strings::{deserialize_lossy_string, deserialize_strict_string, strict_strings},
+};
 use crate::{
-    error::{create_type_error, TypeError},
+    error::{create_type_error, ErrorCode, TypeError},
     traits::Deserialize,
-    value::{BigInt, Boolean, Integer, Number, Value, ValueScope},
+    value::{
+        Array, BigInt, Boolean, Integer, Number, Object, PropertyFilter, Unseal, Value, ValueScope,
+    },
 };
 
+/// Returns the value as a BigInt, unless the runtime only accepts numbers for integer types
+/// smaller than 64 bit.
+#[inline(always)]
+fn mixed_bigint<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Option<BigInt<'scope>> {
+    match strict_bigints(scope) {
+        true => None,
+        false => BigInt::try_from(value).ok(),
+    }
+}
+
+/// Returns the value as an integer, unless the runtime only accepts BigInts for 64 bit integer
+/// types.
+#[inline(always)]
+fn mixed_integer<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Option<Integer<'scope>> {
+    match strict_bigints(scope) {
+        true => None,
+        false => Integer::try_from(value).ok(),
+    }
+}
+
+/// Returns the clamped NaN, ±Infinity or -0 for 64 bit integer types, unless the runtime only
+/// accepts BigInts for them.
+#[inline(always)]
+fn mixed_special_number<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Option<f64> {
+    match strict_bigints(scope) {
+        true => None,
+        false => clamped_special_number(value),
+    }
+}
+
 impl<'scope> Deserialize<'scope> for () {
     #[inline(always)]
     fn deserialize(
@@ -24,6 +71,7 @@ impl<'scope> Deserialize<'scope> for bool {
             Ok(val.value())
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an i8",
                 scope,
                 &value,
@@ -38,7 +86,48 @@ impl<'scope> Deserialize<'scope> for String {
         scope: &mut ValueScope<'scope>,
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
-        Ok(value.to_string_representation(scope))
+        if strict_strings() {
+            deserialize_strict_string(scope, value)
+        } else {
+            deserialize_lossy_string(scope, value)
+        }
+    }
+}
+
+impl<'scope> Deserialize<'scope> for char {
+    #[inline(always)]
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        if crate::value::String::try_from(value).is_err() {
+            return Err(create_type_error(
+                ErrorCode::ArgumentType,
+                "Value can't be converted to a char",
+                scope,
+                &value,
+            ));
+        }
+
+        let string = deserialize_strict_string(scope, value).map_err(|_| {
+            create_type_error(
+                ErrorCode::ArgumentValue,
+                "Value is not a valid Unicode scalar",
+                scope,
+                &value,
+            )
+        })?;
+
+        let mut chars = string.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(create_type_error(
+                ErrorCode::ArgumentValue,
+                "Value must be a string with exactly one Unicode scalar",
+                scope,
+                &value,
+            )),
+        }
     }
 }
 
@@ -49,23 +138,39 @@ impl<'scope> Deserialize<'scope> for i8 {
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
         if let Ok(val) = Integer::try_from(value) {
-            let val = i8::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an i8", scope, &value))?;
+            let val = i8::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an i8",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
-        } else if let Ok(val) = BigInt::try_from(value) {
+        } else if let Some(val) = mixed_bigint(scope, value) {
             let (val, lossless) = val.value_i64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an i8",
                     scope,
                     &value,
                 ));
             }
-            let val = i8::try_from(val)
-                .map_err(|_| create_type_error("Value not in range for an i8", scope, &value))?;
+            let val = i8::try_from(val).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an i8",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
+        } else if let Some(val) = clamped_special_number(value) {
+            Ok(val as i8)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an i8",
                 scope,
                 &value,
@@ -81,23 +186,39 @@ impl<'scope> Deserialize<'scope> for i16 {
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
         if let Ok(val) = Integer::try_from(value) {
-            let val = i16::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an i16", scope, &value))?;
+            let val = i16::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an i16",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
-        } else if let Ok(val) = BigInt::try_from(value) {
+        } else if let Some(val) = mixed_bigint(scope, value) {
             let (val, lossless) = val.value_i64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an i16",
                     scope,
                     &value,
                 ));
             }
-            let val = i16::try_from(val)
-                .map_err(|_| create_type_error("Value not in range for an i16", scope, &value))?;
+            let val = i16::try_from(val).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an i16",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
+        } else if let Some(val) = clamped_special_number(value) {
+            Ok(val as i16)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an i16",
                 scope,
                 &value,
@@ -113,23 +234,39 @@ impl<'scope> Deserialize<'scope> for i32 {
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
         if let Ok(val) = Integer::try_from(value) {
-            let val = i32::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an i32", scope, &value))?;
+            let val = i32::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an i32",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
-        } else if let Ok(val) = BigInt::try_from(value) {
+        } else if let Some(val) = mixed_bigint(scope, value) {
             let (val, lossless) = val.value_i64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an i32",
                     scope,
                     &value,
                 ));
             }
-            let val = i32::try_from(val)
-                .map_err(|_| create_type_error("Value not in range for an i32", scope, &value))?;
+            let val = i32::try_from(val).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an i32",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
+        } else if let Some(val) = clamped_special_number(value) {
+            Ok(val as i32)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an i32",
                 scope,
                 &value,
@@ -144,20 +281,24 @@ impl<'scope> Deserialize<'scope> for i64 {
         scope: &mut ValueScope<'scope>,
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
-        if let Ok(val) = Integer::try_from(value) {
+        if let Some(val) = mixed_integer(scope, value) {
             Ok(val.value())
         } else if let Ok(val) = BigInt::try_from(value) {
             let (val, lossless) = val.value_i64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an i64",
                     scope,
                     &value,
                 ));
             }
             Ok(val)
+        } else if let Some(val) = mixed_special_number(scope, value) {
+            Ok(val as i64)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an i64",
                 scope,
                 &value,
@@ -173,23 +314,39 @@ impl<'scope> Deserialize<'scope> for u8 {
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
         if let Ok(val) = Integer::try_from(value) {
-            let val = u8::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an u8", scope, &value))?;
+            let val = u8::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u8",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
-        } else if let Ok(val) = BigInt::try_from(value) {
+        } else if let Some(val) = mixed_bigint(scope, value) {
             let (val, lossless) = val.value_u64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an u8",
                     scope,
                     &value,
                 ));
             }
-            let val = u8::try_from(val)
-                .map_err(|_| create_type_error("Value not in range for an u8", scope, &value))?;
+            let val = u8::try_from(val).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u8",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
+        } else if let Some(val) = clamped_special_number(value) {
+            Ok(val as u8)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an u8",
                 scope,
                 &value,
@@ -205,23 +362,39 @@ impl<'scope> Deserialize<'scope> for u16 {
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
         if let Ok(val) = Integer::try_from(value) {
-            let val = u16::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an u16", scope, &value))?;
+            let val = u16::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u16",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
-        } else if let Ok(val) = BigInt::try_from(value) {
+        } else if let Some(val) = mixed_bigint(scope, value) {
             let (val, lossless) = val.value_u64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an u16",
                     scope,
                     &value,
                 ));
             }
-            let val = u16::try_from(val)
-                .map_err(|_| create_type_error("Value not in range for an u16", scope, &value))?;
+            let val = u16::try_from(val).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u16",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
+        } else if let Some(val) = clamped_special_number(value) {
+            Ok(val as u16)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an u16",
                 scope,
                 &value,
@@ -237,23 +410,39 @@ impl<'scope> Deserialize<'scope> for u32 {
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
         if let Ok(val) = Integer::try_from(value) {
-            let val = u32::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an u32", scope, &value))?;
+            let val = u32::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u32",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
-        } else if let Ok(val) = BigInt::try_from(value) {
+        } else if let Some(val) = mixed_bigint(scope, value) {
             let (val, lossless) = val.value_u64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an u32",
                     scope,
                     &value,
                 ));
             }
-            let val = u32::try_from(val)
-                .map_err(|_| create_type_error("Value not in range for an u32", scope, &value))?;
+            let val = u32::try_from(val).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u32",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
+        } else if let Some(val) = clamped_special_number(value) {
+            Ok(val as u32)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an u32",
                 scope,
                 &value,
@@ -268,22 +457,32 @@ impl<'scope> Deserialize<'scope> for u64 {
         scope: &mut ValueScope<'scope>,
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
-        if let Ok(val) = Integer::try_from(value) {
-            let val = u64::try_from(val.value())
-                .map_err(|_| create_type_error("Value not in range for an u64", scope, &value))?;
+        if let Some(val) = mixed_integer(scope, value) {
+            let val = u64::try_from(val.value()).map_err(|_| {
+                create_type_error(
+                    ErrorCode::ArgumentRange,
+                    "Value not in range for an u64",
+                    scope,
+                    &value,
+                )
+            })?;
             Ok(val)
         } else if let Ok(val) = BigInt::try_from(value) {
             let (val, lossless) = val.value_u64();
             if !lossless {
                 return Err(create_type_error(
+                    ErrorCode::ArgumentRange,
                     "Value not in range for an u64",
                     scope,
                     &value,
                 ));
             }
             Ok(val)
+        } else if let Some(val) = mixed_special_number(scope, value) {
+            Ok(val as u64)
         } else {
             Err(create_type_error(
+                ErrorCode::ArgumentType,
                 "Value can't be converted to an u16",
                 scope,
                 &value,
@@ -298,8 +497,9 @@ impl<'scope> Deserialize<'scope> for f32 {
         scope: &mut ValueScope<'scope>,
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
-        let value = Number::try_from(value)
-            .map_err(|_| create_type_error("Value not a f32", scope, &value))?;
+        let value = Number::try_from(value).map_err(|_| {
+            create_type_error(ErrorCode::ArgumentType, "Value not a f32", scope, &value)
+        })?;
         Ok(value.value() as f32)
     }
 }
@@ -310,15 +510,200 @@ impl<'scope> Deserialize<'scope> for f64 {
         scope: &mut ValueScope<'scope>,
         value: Value<'scope>,
     ) -> Result<Self, TypeError> {
-        let value = Number::try_from(value)
-            .map_err(|_| create_type_error("Value not a f64", scope, &value))?;
+        let value = Number::try_from(value).map_err(|_| {
+            create_type_error(ErrorCode::ArgumentType, "Value not a f64", scope, &value)
+        })?;
         Ok(value.value())
     }
 }
 
+impl<'scope, T> Deserialize<'scope> for Option<T>
+where
+    T: Deserialize<'scope>,
+{
+    #[inline(always)]
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        if value.is_null_or_undefined() {
+            Ok(None)
+        } else {
+            T::deserialize(scope, value).map(Some)
+        }
+    }
+}
+
+/// Returns the value as an array.
+#[inline(always)]
+fn array<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Result<Array<'scope>, TypeError> {
+    Array::try_from(value).map_err(|_| {
+        create_type_error(
+            ErrorCode::ArgumentType,
+            "Value is not an array",
+            scope,
+            &value,
+        )
+    })
+}
+
+/// Deserializes the element of the array at the given index. The index is added to the message
+/// of the error.
+#[inline(always)]
+fn element<'scope, T>(
+    scope: &mut ValueScope<'scope>,
+    array: Array<'scope>,
+    index: u32,
+) -> Result<T, TypeError>
+where
+    T: Deserialize<'scope>,
+{
+    let Some(value) = array.get(scope, index) else {
+        return Err(TypeError {
+            code: ErrorCode::Conversion,
+            msg: format!("Can't read the element at index {}", index),
+        });
+    };
+
+    T::deserialize(scope, value).map_err(|err| TypeError {
+        code: err.code,
+        msg: format!("Element at index {}: {}", index, err.msg),
+    })
+}
+
+impl<'scope, T> Deserialize<'scope> for Vec<T>
+where
+    T: Deserialize<'scope>,
+{
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        let array = array(scope, value)?;
+        (0..array.len())
+            .map(|index| element(scope, array, index))
+            .collect()
+    }
+}
+
+impl<'scope, T, const N: usize> Deserialize<'scope> for [T; N]
+where
+    T: Deserialize<'scope>,
+{
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        let array = array(scope, value)?;
+        if array.len() as usize != N {
+            return Err(create_type_error(
+                ErrorCode::ArgumentValue,
+                format!("Array must have {} elements", N),
+                scope,
+                &value,
+            ));
+        }
+
+        let elements = Vec::<T>::deserialize(scope, value)?;
+        match elements.try_into() {
+            Ok(elements) => Ok(elements),
+            Err(_) => Err(TypeError {
+                code: ErrorCode::ArgumentValue,
+                msg: format!("Array must have {} elements", N),
+            }),
+        }
+    }
+}
+
+/// Deserializes the enumerable own properties of a plain object into key-value pairs.
+fn entries<'scope, T>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Result<Vec<(String, T)>, TypeError>
+where
+    T: Deserialize<'scope>,
+{
+    match Object::try_from(value) {
+        Ok(object) if !value.unseal().is_array() => {
+            object.entries(scope, PropertyFilter::ONLY_ENUMERABLE)
+        }
+        _ => Err(create_type_error(
+            ErrorCode::ArgumentType,
+            "Value is not an object",
+            scope,
+            &value,
+        )),
+    }
+}
+
+impl<'scope, T> Deserialize<'scope> for HashMap<String, T>
+where
+    T: Deserialize<'scope>,
+{
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        entries(scope, value).map(HashMap::from_iter)
+    }
+}
+
+impl<'scope, T> Deserialize<'scope> for BTreeMap<String, T>
+where
+    T: Deserialize<'scope>,
+{
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        entries(scope, value).map(BTreeMap::from_iter)
+    }
+}
+
+macro_rules! deserialize_tuple {
+    ($len:literal, $($generic:ident $index:literal),+) => {
+        impl<'scope, $($generic,)+> Deserialize<'scope> for ($($generic,)+)
+        where
+            $($generic: Deserialize<'scope>,)+
+        {
+            fn deserialize(
+                scope: &mut ValueScope<'scope>,
+                value: Value<'scope>,
+            ) -> Result<Self, TypeError> {
+                let array = array(scope, value)?;
+                if array.len() != $len {
+                    return Err(create_type_error(
+                        ErrorCode::ArgumentValue,
+                        concat!("Array must have ", $len, " elements"),
+                        scope,
+                        &value,
+                    ));
+                }
+
+                Ok(($(element::<$generic>(scope, array, $index)?,)+))
+            }
+        }
+    };
+}
+
+deserialize_tuple!(1, A 0);
+deserialize_tuple!(2, A 0, B 1);
+deserialize_tuple!(3, A 0, B 1, C 2);
+deserialize_tuple!(4, A 0, B 1, C 2, D 3);
+deserialize_tuple!(5, A 0, B 1, C 2, D 3, E 4);
+deserialize_tuple!(6, A 0, B 1, C 2, D 3, E 4, F 5);
+deserialize_tuple!(7, A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+deserialize_tuple!(8, A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
 #[cfg(test)]
 mod test {
-    use std::fmt::Debug;
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fmt::Debug,
+    };
 
     use crate::{initialize_with_defaults, traits::DeserializeOwned, Runtime, RuntimeOptions};
 
@@ -375,6 +760,74 @@ mod test {
         test_from(r, "false", "false".to_string());
     }
 
+    #[test]
+    fn from_value_for_char() {
+        initialize_with_defaults();
+        let r = &mut Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        test_from(r, "'a'", 'a');
+        test_from(r, "'☕'", '☕');
+        test_from(r, "'\u{1F600}'", '\u{1F600}');
+
+        assert!(r.execute::<char, _>("''").is_err());
+        assert!(r.execute::<char, _>("'ab'").is_err());
+        assert!(r.execute::<char, _>("'\\uD800'").is_err());
+        assert!(r.execute::<char, _>("1").is_err());
+    }
+
+    #[test]
+    fn from_value_for_option() {
+        initialize_with_defaults();
+        let r = &mut Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        test_from(r, "undefined", None::<i32>);
+        test_from(r, "null", None::<i32>);
+        test_from(r, "42", Some(42i32));
+
+        assert!(r.execute::<Option<i32>, _>("'a'").is_err());
+    }
+
+    #[test]
+    fn from_value_for_sequence() {
+        initialize_with_defaults();
+        let r = &mut Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        test_from(r, "[1, 2, 3]", vec![1i32, 2, 3]);
+        test_from(r, "[]", Vec::<i32>::new());
+        test_from(r, "[[1], [2, 3]]", vec![vec![1u8], vec![2, 3]]);
+        test_from(r, "[1, 2, 3]", [1i32, 2, 3]);
+        test_from(r, "[1, 'a', true]", (1i32, "a".to_string(), true));
+
+        assert!(r.execute::<Vec<i32>, _>("1").is_err());
+        assert!(r.execute::<Vec<i32>, _>("[1, 'a']").is_err());
+        assert!(r.execute::<[i32; 2], _>("[1, 2, 3]").is_err());
+        assert!(r.execute::<(i32, i32), _>("[1]").is_err());
+    }
+
+    #[test]
+    fn from_value_for_map() {
+        initialize_with_defaults();
+        let r = &mut Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let expected = HashMap::from([("a".to_string(), 1i32), ("b".to_string(), 2i32)]);
+        test_from(r, "({ a: 1, b: 2 })", expected);
+        test_from(r, "({})", BTreeMap::<String, i32>::new());
+        test_from(
+            r,
+            "({ 1: 'x', b: 'y' })",
+            BTreeMap::from([
+                ("1".to_string(), "x".to_string()),
+                ("b".to_string(), "y".to_string()),
+            ]),
+        );
+
+        assert!(r.execute::<HashMap<String, i32>, _>("[1, 2]").is_err());
+        assert!(r.execute::<HashMap<String, i32>, _>("null").is_err());
+        assert!(r
+            .execute::<BTreeMap<String, i32>, _>("({ a: 'a' })")
+            .is_err());
+    }
+
     #[test]
     fn from_value_for_integer() {
         initialize_with_defaults();
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{
+    error::{ErrorCode, TypeError},
+    runtime::RuntimeData,
+    value::{Unseal, Value, ValueScope},
+    NumberPolicy,
+};
+
+/// Stores the configured [`NumberPolicy`].
+pub(crate) static NUMBER_POLICY: AtomicU8 = AtomicU8::new(NumberPolicy::PassThrough as u8);
+
+/// Returns the configured [`NumberPolicy`].
+#[inline(always)]
+fn number_policy() -> NumberPolicy {
+    match NUMBER_POLICY.load(Ordering::Relaxed) {
+        policy if policy == NumberPolicy::Error as u8 => NumberPolicy::Error,
+        policy if policy == NumberPolicy::Clamp as u8 => NumberPolicy::Clamp,
+        _ => NumberPolicy::PassThrough,
+    }
+}
+
+/// Returns `true` if the runtime of the scope only accepts BigInts for 64 bit integer types and
+/// numbers for smaller integer types.
+#[inline(always)]
+pub(crate) fn strict_bigints(scope: &mut ValueScope) -> bool {
+    RuntimeData::from_isolate(scope.unseal()).map_or(false, |data| data.strict_bigints)
+}
+
+/// Returns `true` if the number is NaN, ±Infinity or -0.
+#[inline(always)]
+fn is_special(number: f64) -> bool {
+    !number.is_finite() || (number == 0.0 && number.is_sign_negative())
+}
+
+/// Returns the number that is used for NaN, ±Infinity and -0 when deserializing into an integer.
+///
+/// Returns `None` if the value is not one of these numbers or if the policy doesn't clamp them.
+/// The returned number can be converted with a saturating `as` cast into the integer type.
+#[inline(always)]
+pub(crate) fn clamped_special_number(value: Value) -> Option<f64> {
+    clamp_special_number(value, number_policy())
+}
+
+fn clamp_special_number(value: Value, policy: NumberPolicy) -> Option<f64> {
+    if policy != NumberPolicy::Clamp {
+        return None;
+    }
+
+    let value = v8::Local::<v8::Number>::try_from(value.unseal()).ok()?;
+    let number = value.value();
+
+    if is_special(number) {
+        Some(if number.is_nan() { 0.0 } else { number })
+    } else {
+        None
+    }
+}
+
+/// Applies the configured [`NumberPolicy`] to a float that is serialized.
+#[inline(always)]
+pub(crate) fn serialize_float(number: f64) -> Result<f64, TypeError> {
+    apply_float_policy(number, number_policy())
+}
+
+fn apply_float_policy(number: f64, policy: NumberPolicy) -> Result<f64, TypeError> {
+    if !is_special(number) {
+        return Ok(number);
+    }
+
+    match policy {
+        NumberPolicy::PassThrough => Ok(number),
+        NumberPolicy::Error => Err(TypeError {
+            code: ErrorCode::ArgumentRange,
+            msg: format!("Number {} is not allowed", number),
+        }),
+        NumberPolicy::Clamp => Ok(if number.is_nan() || number == 0.0 {
+            0.0
+        } else if number.is_sign_positive() {
+            f64::MAX
+        } else {
+            f64::MIN
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_float_policy, clamp_special_number};
+    use crate::{value::test::test_value, NumberPolicy};
+
+    #[test]
+    fn float_policy() {
+        for policy in [
+            NumberPolicy::PassThrough,
+            NumberPolicy::Error,
+            NumberPolicy::Clamp,
+        ] {
+            assert_eq!(apply_float_policy(1.5, policy).ok(), Some(1.5));
+        }
+
+        let val = apply_float_policy(f64::NAN, NumberPolicy::PassThrough).expect("Invalid number");
+        assert!(val.is_nan());
+        let val = apply_float_policy(-0.0, NumberPolicy::PassThrough).expect("Invalid number");
+        assert!(val.is_sign_negative());
+
+        assert!(apply_float_policy(f64::NAN, NumberPolicy::Error).is_err());
+        assert!(apply_float_policy(f64::INFINITY, NumberPolicy::Error).is_err());
+        assert!(apply_float_policy(-0.0, NumberPolicy::Error).is_err());
+
+        assert_eq!(
+            apply_float_policy(f64::NAN, NumberPolicy::Clamp).ok(),
+            Some(0.0)
+        );
+        assert_eq!(
+            apply_float_policy(f64::INFINITY, NumberPolicy::Clamp).ok(),
+            Some(f64::MAX)
+        );
+        assert_eq!(
+            apply_float_policy(f64::NEG_INFINITY, NumberPolicy::Clamp).ok(),
+            Some(f64::MIN)
+        );
+        let val = apply_float_policy(-0.0, NumberPolicy::Clamp).expect("Invalid number");
+        assert!(val.is_sign_positive());
+    }
+
+    #[test]
+    fn clamp_special_numbers() {
+        test_value("-Infinity", |v| {
+            assert_eq!(clamp_special_number(v, NumberPolicy::PassThrough), None);
+            let val = clamp_special_number(v, NumberPolicy::Clamp).expect("Not clamped");
+            assert_eq!(val as i32, i32::MIN);
+            assert_eq!(val as u32, 0);
+        });
+        test_value("NaN", |v| {
+            let val = clamp_special_number(v, NumberPolicy::Clamp).expect("Not clamped");
+            assert_eq!(val as i32, 0);
+        });
+        test_value("1.5", |v| {
+            assert_eq!(clamp_special_number(v, NumberPolicy::Clamp), None);
+        });
+    }
+}
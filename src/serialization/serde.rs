@@ -1,7 +1,10 @@
 mod deserializer;
+mod json;
+mod options;
 mod serializer;
 
 use deserializer::ValueDeserializer;
+pub use options::Options;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -19,7 +22,7 @@ pub fn from_value<'scope, T>(
 where
     T: Deserialize<'scope>,
 {
-    let deserializer = &mut ValueDeserializer::from_value(scope, value);
+    let deserializer = ValueDeserializer::from_value(scope, value);
     let t = T::deserialize(deserializer)?;
     Ok(t)
 }
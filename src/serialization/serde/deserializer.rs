@@ -1,252 +1,526 @@
-use serde::{de::Visitor, Deserializer};
+use serde::{
+    de::{
+        DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    Deserializer,
+};
 
 use crate::{
-    error::TypeError,
-    value::{Value, ValueScope},
+    error::{create_type_error, ErrorCode, TypeError},
+    traits::Deserialize,
+    value::{
+        Array, ArrayBuffer, ArrayBufferView, BigInt, Integer, Map, Number, Object, Primitive,
+        Unseal, Value, ValueScope,
+    },
 };
 
 /// Custom serde deserializer for the engine values.
-pub(crate) struct ValueDeserializer<'de, 'scope> {
-    _scope: &'de ValueScope<'scope>,
-    _value: Value<'scope>,
+pub(crate) struct ValueDeserializer<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    value: Value<'scope>,
 }
 
 impl<'a, 'scope> ValueDeserializer<'a, 'scope> {
     /// Deserializes a [`Value`] into a Rust type.
-    pub fn from_value(scope: &'a ValueScope<'scope>, value: Value<'scope>) -> Self {
-        ValueDeserializer {
-            _scope: scope,
-            _value: value,
-        }
+    pub fn from_value(scope: &'a mut ValueScope<'scope>, value: Value<'scope>) -> Self {
+        ValueDeserializer { scope, value }
+    }
+
+    fn type_error<S>(self, msg: S) -> TypeError
+    where
+        S: AsRef<str>,
+    {
+        create_type_error(ErrorCode::Conversion, msg, self.scope, &self.value)
     }
 }
 
-impl<'de, 'a, 'scope> Deserializer<'de> for &'a mut ValueDeserializer<'a, 'scope> {
+/// Returns the own enumerable string keys of an object.
+fn object_keys<'scope>(
+    scope: &mut ValueScope<'scope>,
+    object: Object<'scope>,
+) -> Result<Array<'scope>, TypeError> {
+    let args = v8::GetPropertyNamesArgs {
+        key_conversion: v8::KeyConversionMode::ConvertToString,
+        ..Default::default()
+    };
+
+    object
+        .own_property_names(scope, args)
+        .ok_or_else(|| TypeError {
+            code: ErrorCode::Conversion,
+            msg: "Can't read the property names of the object".to_string(),
+        })
+}
+
+macro_rules! deserialize_primitive {
+    ($method:ident, $value_type:ty, $visit:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let value = <$value_type as Deserialize>::deserialize(self.scope, self.value)?;
+            visitor.$visit(value)
+        }
+    };
+}
+
+impl<'de, 'a, 'scope> Deserializer<'de> for ValueDeserializer<'a, 'scope> {
     type Error = TypeError;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        let value = self.value.unseal();
+
+        if value.is_null_or_undefined() {
+            visitor.visit_unit()
+        } else if value.is_boolean() {
+            self.deserialize_bool(visitor)
+        } else if let Ok(integer) = Integer::try_from(self.value) {
+            visitor.visit_i64(integer.value())
+        } else if let Ok(number) = Number::try_from(self.value) {
+            visitor.visit_f64(number.value())
+        } else if let Ok(bigint) = BigInt::try_from(self.value) {
+            if let (value, true) = bigint.value_i64() {
+                visitor.visit_i64(value)
+            } else if let (value, true) = bigint.value_u64() {
+                visitor.visit_u64(value)
+            } else {
+                Err(self.type_error("BigInt is not in range for an i64 or u64"))
+            }
+        } else if value.is_string() {
+            self.deserialize_string(visitor)
+        } else if value.is_array() {
+            self.deserialize_seq(visitor)
+        } else if value.is_array_buffer() || value.is_array_buffer_view() {
+            self.deserialize_byte_buf(visitor)
+        } else if value.is_function() || value.is_symbol() {
+            Err(self.type_error("Value can't be deserialized"))
+        } else {
+            self.deserialize_map(visitor)
+        }
     }
 
-    fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    deserialize_primitive!(deserialize_bool, bool, visit_bool);
+    deserialize_primitive!(deserialize_i8, i8, visit_i8);
+    deserialize_primitive!(deserialize_i16, i16, visit_i16);
+    deserialize_primitive!(deserialize_i32, i32, visit_i32);
+    deserialize_primitive!(deserialize_i64, i64, visit_i64);
+    deserialize_primitive!(deserialize_u8, u8, visit_u8);
+    deserialize_primitive!(deserialize_u16, u16, visit_u16);
+    deserialize_primitive!(deserialize_u32, u32, visit_u32);
+    deserialize_primitive!(deserialize_u64, u64, visit_u64);
+    deserialize_primitive!(deserialize_f32, f32, visit_f32);
+    deserialize_primitive!(deserialize_f64, f64, visit_f64);
+    deserialize_primitive!(deserialize_str, std::string::String, visit_string);
+    deserialize_primitive!(deserialize_string, std::string::String, visit_string);
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        let string = self.value.to_string_representation(self.scope);
+        let mut chars = string.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(self.type_error("Value can't be converted to a char")),
+        }
     }
 
-    fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_byte_buf(visitor)
     }
 
-    fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        if let Ok(view) = ArrayBufferView::try_from(self.value) {
+            let view = view.unseal();
+            let mut data = vec![0u8; view.byte_length()];
+            view.copy_contents(&mut data);
+            visitor.visit_byte_buf(data)
+        } else if let Ok(buffer) = ArrayBuffer::try_from(self.value) {
+            visitor.visit_byte_buf(buffer.as_ref().to_vec())
+        } else if self.value.unseal().is_array() {
+            self.deserialize_seq(visitor)
+        } else {
+            Err(self.type_error("Value can't be converted to bytes"))
+        }
     }
 
-    fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        if self.value.is_null_or_undefined() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
     }
 
-    fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_unit()
     }
 
-    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_unit(visitor)
     }
 
-    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        let Ok(array) = Array::try_from(self.value) else {
+            return Err(self.type_error("Value can't be converted to a sequence"));
+        };
+
+        visitor.visit_seq(ArrayAccess {
+            scope: self.scope,
+            array,
+            index: 0,
+        })
     }
 
-    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_seq(visitor)
     }
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_seq(visitor)
     }
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        if let Ok(map) = Map::try_from(self.value) {
+            let entries = map.to_array(self.scope);
+            visitor.visit_map(MapEntriesAccess {
+                scope: self.scope,
+                entries,
+                index: 0,
+            })
+        } else if let Ok(object) = Object::try_from(self.value) {
+            let keys = object_keys(self.scope, object)?;
+            visitor.visit_map(ObjectAccess {
+                scope: self.scope,
+                object,
+                keys,
+                index: 0,
+                value: None,
+            })
+        } else {
+            Err(self.type_error("Value can't be converted to a map"))
+        }
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_map(visitor)
     }
 
-    fn deserialize_str<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        if self.value.unseal().is_string() {
+            // Unit variants are represented as plain strings.
+            let variant = self.value.to_string_representation(self.scope);
+            visitor.visit_enum(variant.into_deserializer())
+        } else if let Ok(object) = Object::try_from(self.value) {
+            // All other variants are represented as objects with a single key.
+            let keys = object_keys(self.scope, object)?;
+            if keys.len() != 1 {
+                return Err(self.type_error("Enum object must have exactly one key"));
+            }
+
+            let Some(variant) = keys.get(self.scope, 0) else {
+                return Err(self.type_error("Can't read the enum variant"));
+            };
+            let Some(value) = object.get(self.scope, variant) else {
+                return Err(self.type_error("Can't read the enum value"));
+            };
+
+            visitor.visit_enum(EnumAccessor {
+                scope: self.scope,
+                variant,
+                value,
+            })
+        } else {
+            Err(self.type_error("Value can't be converted to an enum"))
+        }
     }
 
-    fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_string(visitor)
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_unit()
     }
+}
+
+/// Gives access to the elements of an array.
+struct ArrayAccess<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    array: Array<'scope>,
+    index: u32,
+}
+
+impl<'de, 'a, 'scope> SeqAccess<'de> for ArrayAccess<'a, 'scope> {
+    type Error = TypeError;
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
     where
-        V: Visitor<'de>,
+        T: DeserializeSeed<'de>,
     {
-        todo!()
+        if self.index >= self.array.len() {
+            return Ok(None);
+        }
+
+        let value = self
+            .array
+            .get(self.scope, self.index)
+            .ok_or_else(|| TypeError {
+                code: ErrorCode::Conversion,
+                msg: format!("Can't read the array element at index {}", self.index),
+            })?;
+        self.index += 1;
+
+        seed.deserialize(ValueDeserializer::from_value(&mut *self.scope, value))
+            .map(Some)
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        todo!()
+    fn size_hint(&self) -> Option<usize> {
+        Some((self.array.len() - self.index) as usize)
     }
+}
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+/// Gives access to the entries of a map.
+struct MapEntriesAccess<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    /// Contains the keys and values in the form of: `key0, value0, key1, value1...`
+    entries: Array<'scope>,
+    index: u32,
+}
+
+impl<'de, 'a, 'scope> MapAccess<'de> for MapEntriesAccess<'a, 'scope> {
+    type Error = TypeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
-        V: Visitor<'de>,
+        K: DeserializeSeed<'de>,
     {
-        todo!()
+        if self.index >= self.entries.len() {
+            return Ok(None);
+        }
+
+        let key = self
+            .entries
+            .get(self.scope, self.index)
+            .ok_or_else(|| TypeError {
+                code: ErrorCode::Conversion,
+                msg: "Can't read the key of a map entry".to_string(),
+            })?;
+        self.index += 1;
+
+        seed.deserialize(ValueDeserializer::from_value(&mut *self.scope, key))
+            .map(Some)
     }
 
-    fn deserialize_unit_struct<V>(
-        self,
-        _name: &'static str,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'de>,
+        V: DeserializeSeed<'de>,
     {
-        todo!()
+        let value = self
+            .entries
+            .get(self.scope, self.index)
+            .ok_or_else(|| TypeError {
+                code: ErrorCode::Conversion,
+                msg: "Can't read the value of a map entry".to_string(),
+            })?;
+        self.index += 1;
+
+        seed.deserialize(ValueDeserializer::from_value(&mut *self.scope, value))
     }
 
-    fn deserialize_newtype_struct<V>(
-        self,
-        _name: &'static str,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        todo!()
+    fn size_hint(&self) -> Option<usize> {
+        Some(((self.entries.len() - self.index) / 2) as usize)
     }
+}
+
+/// Gives access to the own enumerable properties of an object.
+struct ObjectAccess<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    object: Object<'scope>,
+    keys: Array<'scope>,
+    index: u32,
+    value: Option<Value<'scope>>,
+}
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+impl<'de, 'a, 'scope> MapAccess<'de> for ObjectAccess<'a, 'scope> {
+    type Error = TypeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
-        V: Visitor<'de>,
+        K: DeserializeSeed<'de>,
     {
-        todo!()
+        if self.index >= self.keys.len() {
+            return Ok(None);
+        }
+
+        let key = self
+            .keys
+            .get(self.scope, self.index)
+            .ok_or_else(|| TypeError {
+                code: ErrorCode::Conversion,
+                msg: "Can't read the key of an object property".to_string(),
+            })?;
+        self.index += 1;
+        self.value = self.object.get(self.scope, key);
+
+        seed.deserialize(ValueDeserializer::from_value(&mut *self.scope, key))
+            .map(Some)
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
-        V: Visitor<'de>,
+        V: DeserializeSeed<'de>,
     {
-        todo!()
+        let value = match self.value.take() {
+            Some(value) => value,
+            None => Primitive::new_undefined(self.scope).into(),
+        };
+
+        seed.deserialize(ValueDeserializer::from_value(&mut *self.scope, value))
     }
 
-    fn deserialize_tuple_struct<V>(
-        self,
-        _name: &'static str,
-        _len: usize,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        todo!()
+    fn size_hint(&self) -> Option<usize> {
+        Some((self.keys.len() - self.index) as usize)
     }
+}
+
+/// Gives access to an externally tagged enum variant (`{ "variant": value }`).
+struct EnumAccessor<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    variant: Value<'scope>,
+    value: Value<'scope>,
+}
 
-    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+impl<'de, 'a, 'scope> EnumAccess<'de> for EnumAccessor<'a, 'scope> {
+    type Error = TypeError;
+    type Variant = VariantAccessor<'a, 'scope>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where
-        V: Visitor<'de>,
+        V: DeserializeSeed<'de>,
     {
-        todo!()
+        let variant = seed.deserialize(ValueDeserializer::from_value(
+            &mut *self.scope,
+            self.variant,
+        ))?;
+
+        Ok((
+            variant,
+            VariantAccessor {
+                scope: self.scope,
+                value: self.value,
+            },
+        ))
     }
+}
 
-    fn deserialize_struct<V>(
-        self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        todo!()
+/// Gives access to the value of an enum variant.
+struct VariantAccessor<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    value: Value<'scope>,
+}
+
+impl<'de, 'a, 'scope> VariantAccess<'de> for VariantAccessor<'a, 'scope> {
+    type Error = TypeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
     }
 
-    fn deserialize_enum<V>(
-        self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
     where
-        V: Visitor<'de>,
+        T: DeserializeSeed<'de>,
     {
-        todo!()
+        seed.deserialize(ValueDeserializer::from_value(self.scope, self.value))
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        ValueDeserializer::from_value(self.scope, self.value).deserialize_seq(visitor)
     }
 
-    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        ValueDeserializer::from_value(self.scope, self.value).deserialize_map(visitor)
     }
 }
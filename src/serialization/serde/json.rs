@@ -0,0 +1,103 @@
+use v8::NewStringType;
+
+use crate::{
+    error::TypeError,
+    serialization::serde::from_value,
+    value::{Array, Object, Primitive, String, Value, ValueScope},
+    Deserialize, Serialize,
+};
+
+impl<'scope> Deserialize<'scope> for serde_json::Value {
+    #[inline(always)]
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        from_value(scope, value)
+    }
+}
+
+impl Serialize for serde_json::Value {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        match self {
+            serde_json::Value::Null => Ok(Primitive::new_null(scope).into()),
+            serde_json::Value::Bool(value) => value.serialize(scope),
+            serde_json::Value::Number(number) => {
+                if let Some(value) = number.as_i64() {
+                    value.serialize(scope)
+                } else if let Some(value) = number.as_u64() {
+                    value.serialize(scope)
+                } else {
+                    number.as_f64().unwrap_or(f64::NAN).serialize(scope)
+                }
+            }
+            serde_json::Value::String(value) => value.serialize(scope),
+            serde_json::Value::Array(values) => {
+                let mut elements = Vec::with_capacity(values.len());
+                for value in values {
+                    elements.push(value.serialize(scope)?);
+                }
+                Ok(Array::new_with_elements(scope, elements).into())
+            }
+            serde_json::Value::Object(map) => {
+                let object = Object::new(scope);
+                for (key, value) in map {
+                    let key = String::try_new(scope, key, NewStringType::Normal)?;
+                    let value = value.serialize(scope)?;
+                    object.set(scope, key.into(), value);
+                }
+                Ok(object.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{initialize_with_defaults, Extension, Runtime, RuntimeOptions};
+
+    #[test]
+    fn deserialize_json_value() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: serde_json::Value = runtime
+            .execute("({ a: 1, b: [true, null, 'c'], d: { e: 1.5 } })")
+            .expect("Can't execute code");
+        assert_eq!(
+            val,
+            json!({ "a": 1, "b": [true, null, "c"], "d": { "e": 1.5 } })
+        );
+    }
+
+    #[test]
+    fn serialize_json_value() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(None);
+        extension.add_function(
+            "config",
+            |()| json!({ "name": "kopi", "values": [1, 2.5, false], "empty": null }),
+        );
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: bool = runtime
+            .execute(
+                "const c = config(); \
+                c.name === 'kopi' && c.values[1] === 2.5 && c.values[2] === false && c.empty === null",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+    }
+}
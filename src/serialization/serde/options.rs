@@ -0,0 +1,170 @@
+use std::ops::{Deref, DerefMut};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    error::{create_type_error, ErrorCode, TypeError},
+    serialization::serde::from_value,
+    value::{Object, Value, ValueScope},
+};
+
+/// Argument wrapper that deserializes an options object into `T`.
+///
+/// Is most useful as the trailing argument of an extension function. If the argument is
+/// `undefined` or `null`, `T::default()` is used. Missing fields of a given options object can
+/// be defaulted by using `#[serde(default)]` on `T`.
+///
+/// # Example
+///
+/// ```
+/// use kopi::{Extension, Options};
+/// use serde::Deserialize;
+///
+/// #[derive(Default, Deserialize)]
+/// #[serde(default)]
+/// struct FormatOptions {
+///     uppercase: bool,
+///     repeat: u32,
+/// }
+///
+/// let mut extension = Extension::<()>::new(None);
+/// extension.add_function(
+///     "format",
+///     |(text, options): (String, Options<FormatOptions>)| {
+///         let text = if options.uppercase {
+///             text.to_uppercase()
+///         } else {
+///             text
+///         };
+///         text.repeat(options.repeat.max(1) as usize)
+///     },
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options<T>(pub T);
+
+impl<T> Options<T> {
+    /// Returns the inner value.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Options<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Options<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'scope, T> crate::Deserialize<'scope> for Options<T>
+where
+    T: DeserializeOwned + Default,
+{
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        if value.is_null_or_undefined() {
+            return Ok(Options(T::default()));
+        }
+
+        if Object::try_from(value).is_err() {
+            return Err(create_type_error(
+                ErrorCode::ArgumentType,
+                "Options must be an object",
+                scope,
+                &value,
+            ));
+        }
+
+        from_value(scope, value).map(Options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{initialize_with_defaults, Extension, Runtime, RuntimeOptions};
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(default)]
+    struct TestOptions {
+        name: std::string::String,
+        count: u32,
+        verbose: bool,
+    }
+
+    fn create_runtime() -> Runtime<()> {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_function(
+            "describe",
+            |(prefix, options): (std::string::String, Options<TestOptions>)| {
+                format!(
+                    "{} {} {} {}",
+                    prefix, options.name, options.count, options.verbose
+                )
+            },
+        );
+
+        Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime")
+    }
+
+    #[test]
+    fn options_missing() {
+        let mut runtime = create_runtime();
+        let val: std::string::String = runtime
+            .execute("test.describe('a')")
+            .expect("Can't execute code");
+        assert_eq!(val, "a  0 false");
+    }
+
+    #[test]
+    fn options_partial() {
+        let mut runtime = create_runtime();
+        let val: std::string::String = runtime
+            .execute("test.describe('a', { count: 3 })")
+            .expect("Can't execute code");
+        assert_eq!(val, "a  3 false");
+    }
+
+    #[test]
+    fn options_full() {
+        let mut runtime = create_runtime();
+        let val: std::string::String = runtime
+            .execute("test.describe('a', { name: 'b', count: 3, verbose: true })")
+            .expect("Can't execute code");
+        assert_eq!(val, "a b 3 true");
+    }
+
+    #[test]
+    fn options_not_an_object() {
+        let mut runtime = create_runtime();
+        let val: bool = runtime
+            .execute("test.describe('a', 42) instanceof TypeError")
+            .expect("Can't execute code");
+        assert!(val);
+    }
+}
@@ -4,26 +4,55 @@ use serde::ser::{
 };
 
 use crate::{
-    error::TypeError,
-    value::{Primitive, Value, ValueScope},
+    error::{ErrorCode, TypeError},
+    value::{Array, Map, NameCache, Object, Primitive, Uint8Array, Unseal, Value, ValueScope},
     Serialize,
 };
 
 /// Custom serializer to serialize a Rust type into a engine [`Value`].
+///
+/// Sequences and tuples are serialized as arrays, structs as objects and bytes as a
+/// `Uint8Array`. Maps are serialized as objects if all keys are strings or numbers, otherwise
+/// as a `Map`. Enums use the externally tagged representation: unit variants are serialized as
+/// strings, all other variants as objects with the name of the variant as the single key.
 pub(crate) struct ValueSerializer<'a, 'scope> {
     pub(crate) scope: &'a mut ValueScope<'scope>,
 }
 
+/// Serializes the value with a new serializer that re-borrows the scope.
+fn serialize_value<'scope, T>(
+    scope: &mut ValueScope<'scope>,
+    value: &T,
+) -> Result<Value<'scope>, TypeError>
+where
+    T: ?Sized + serde::ser::Serialize,
+{
+    let mut serializer = ValueSerializer { scope };
+    value.serialize(&mut serializer)
+}
+
+/// Wraps the value inside an object with the name of the variant as the single key.
+fn tag_variant<'scope>(
+    scope: &mut ValueScope<'scope>,
+    variant: &'static str,
+    value: Value<'scope>,
+) -> Value<'scope> {
+    let object = Object::new(scope);
+    let key = NameCache::get(scope, variant);
+    object.set(scope, key.into(), value);
+    object.into()
+}
+
 impl<'a, 'scope> Serializer for &'a mut ValueSerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
-    type SerializeSeq = Self;
-    type SerializeTuple = Self;
-    type SerializeTupleStruct = Self;
-    type SerializeTupleVariant = Self;
-    type SerializeMap = Self;
-    type SerializeStruct = Self;
-    type SerializeStructVariant = Self;
+    type SerializeSeq = ArraySerializer<'a, 'scope>;
+    type SerializeTuple = ArraySerializer<'a, 'scope>;
+    type SerializeTupleStruct = ArraySerializer<'a, 'scope>;
+    type SerializeTupleVariant = ArraySerializer<'a, 'scope>;
+    type SerializeMap = MapSerializer<'a, 'scope>;
+    type SerializeStruct = ObjectSerializer<'a, 'scope>;
+    type SerializeStructVariant = ObjectSerializer<'a, 'scope>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         v.serialize(self.scope)
@@ -77,12 +106,8 @@ impl<'a, 'scope> Serializer for &'a mut ValueSerializer<'a, 'scope> {
         v.serialize(self.scope)
     }
 
-    // TODO I think we need to use the array buffer here (maybe even a v8::Uint8Array). It seems we have to write into it using a "typed_array.buffer().data()"?
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        let _owned = v.to_vec().into_boxed_slice();
-        // TODO maybe with v8::ArrayBuffer::new_backing_store_from_vec() we can give the initialized view?
-        // TODO maybe we then return a v8::Uint8 view on it (it takes an ArrayBuffer).
-        todo!()
+        Ok(Uint8Array::new_from_vec(self.scope, v.to_vec()).into())
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -108,9 +133,9 @@ impl<'a, 'scope> Serializer for &'a mut ValueSerializer<'a, 'scope> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        Ok(NameCache::get(self.scope, variant).into())
     }
 
     fn serialize_newtype_struct<T: ?Sized>(
@@ -128,44 +153,52 @@ impl<'a, 'scope> Serializer for &'a mut ValueSerializer<'a, 'scope> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        let value = serialize_value(self.scope, value)?;
+        Ok(tag_variant(self.scope, variant, value))
     }
 
-    // TODO how do we handle the different specialization arrays?
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        todo!()
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(ArraySerializer::new(
+            self.scope,
+            len.unwrap_or_default(),
+            None,
+        ))
     }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        todo!()
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(ArraySerializer::new(self.scope, len, None))
     }
 
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        todo!()
+        Ok(ArraySerializer::new(self.scope, len, None))
     }
 
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
+        variant: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        todo!()
+        Ok(ArraySerializer::new(self.scope, len, Some(variant)))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        todo!()
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapSerializer {
+            scope: self.scope,
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
     }
 
     fn serialize_struct(
@@ -173,150 +206,461 @@ impl<'a, 'scope> Serializer for &'a mut ValueSerializer<'a, 'scope> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        todo!()
+        Ok(ObjectSerializer::new(self.scope, None))
     }
 
     fn serialize_struct_variant(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        todo!()
+        Ok(ObjectSerializer::new(self.scope, Some(variant)))
+    }
+}
+
+/// Serializes sequences, tuples and tuple variants into an array.
+pub(crate) struct ArraySerializer<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    elements: Vec<Value<'scope>>,
+    variant: Option<&'static str>,
+}
+
+impl<'a, 'scope> ArraySerializer<'a, 'scope> {
+    fn new(scope: &'a mut ValueScope<'scope>, len: usize, variant: Option<&'static str>) -> Self {
+        Self {
+            scope,
+            elements: Vec::with_capacity(len),
+            variant,
+        }
+    }
+
+    fn push<T: ?Sized>(&mut self, value: &T) -> Result<(), TypeError>
+    where
+        T: serde::ser::Serialize,
+    {
+        let value = serialize_value(self.scope, value)?;
+        self.elements.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value<'scope>, TypeError> {
+        let array = Array::new_with_elements(self.scope, self.elements).into();
+        match self.variant {
+            Some(variant) => Ok(tag_variant(self.scope, variant, array)),
+            None => Ok(array),
+        }
     }
 }
 
-// TODO This needs it's own struct, since we need to have an array where we append to.
-impl<'a, 'scope> SerializeSeq for &mut ValueSerializer<'a, 'scope> {
+impl<'a, 'scope> SerializeSeq for ArraySerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
-    fn serialize_element<T: ?Sized>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.push(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        self.finish()
     }
 }
 
-// TODO This needs it's own struct, since we need to have an array where we append to.
-impl<'a, 'scope> SerializeTuple for &'a mut ValueSerializer<'a, 'scope> {
+impl<'a, 'scope> SerializeTuple for ArraySerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
-    fn serialize_element<T: ?Sized>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.push(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        self.finish()
     }
 }
 
-// TODO This needs it's own struct, since we need to have an array where we append to.
-impl<'a, 'scope> SerializeTupleStruct for &'a mut ValueSerializer<'a, 'scope> {
+impl<'a, 'scope> SerializeTupleStruct for ArraySerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
-    fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.push(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        self.finish()
     }
 }
 
-// TODO This needs it's own struct, since we need to have an array where we append to.
-impl<'a, 'scope> SerializeTupleVariant for &'a mut ValueSerializer<'a, 'scope> {
+impl<'a, 'scope> SerializeTupleVariant for ArraySerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
-    fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.push(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        self.finish()
     }
 }
 
-// TODO This needs it's own struct, since we need to have a map where we append to.
-impl<'a, 'scope> SerializeMap for &'a mut ValueSerializer<'a, 'scope> {
+/// Serializes maps into an object or a `Map`.
+pub(crate) struct MapSerializer<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    entries: Vec<(Value<'scope>, Value<'scope>)>,
+    key: Option<Value<'scope>>,
+}
+
+impl<'a, 'scope> SerializeMap for MapSerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
-    fn serialize_key<T: ?Sized>(&mut self, _key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.key = Some(serialize_value(self.scope, key)?);
+        Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        let Some(key) = self.key.take() else {
+            return Err(TypeError {
+                code: ErrorCode::Conversion,
+                msg: "Map value was serialized before its key".to_string(),
+            });
+        };
+        let value = serialize_value(self.scope, value)?;
+        self.entries.push((key, value));
+        Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        // Objects can only have property keys, other keys would be converted into strings.
+        let property_keys = self.entries.iter().all(|(key, _)| {
+            let key = key.unseal();
+            key.is_string() || key.is_number()
+        });
+
+        if property_keys {
+            let object = Object::new(self.scope);
+            for (key, value) in self.entries {
+                object.set(self.scope, key, value);
+            }
+            Ok(object.into())
+        } else {
+            let map = Map::new(self.scope);
+            for (key, value) in self.entries {
+                map.set(self.scope, key, value);
+            }
+            Ok(map.into())
+        }
     }
 }
 
-// TODO This needs it's own struct, since we need to have a object where we append to.
-impl<'a, 'scope> SerializeStruct for &'a mut ValueSerializer<'a, 'scope> {
+/// Serializes structs and struct variants into an object.
+pub(crate) struct ObjectSerializer<'a, 'scope> {
+    scope: &'a mut ValueScope<'scope>,
+    object: Object<'scope>,
+    variant: Option<&'static str>,
+}
+
+impl<'a, 'scope> ObjectSerializer<'a, 'scope> {
+    fn new(scope: &'a mut ValueScope<'scope>, variant: Option<&'static str>) -> Self {
+        let object = Object::new(scope);
+        Self {
+            scope,
+            object,
+            variant,
+        }
+    }
+
+    fn set<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), TypeError>
+    where
+        T: serde::ser::Serialize,
+    {
+        let value = serialize_value(self.scope, value)?;
+        let key = NameCache::get(self.scope, key);
+        self.object.set(self.scope, key.into(), value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value<'scope>, TypeError> {
+        match self.variant {
+            Some(variant) => Ok(tag_variant(self.scope, variant, self.object.into())),
+            None => Ok(self.object.into()),
+        }
+    }
+}
+
+impl<'a, 'scope> SerializeStruct for ObjectSerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
     fn serialize_field<T: ?Sized>(
         &mut self,
-        _key: &'static str,
-        _value: &T,
+        key: &'static str,
+        value: &T,
     ) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.set(key, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        self.finish()
     }
 }
 
-// TODO This needs it's own struct, since we need to have a object where we append to.
-impl<'a, 'scope> SerializeStructVariant for &'a mut ValueSerializer<'a, 'scope> {
+impl<'a, 'scope> SerializeStructVariant for ObjectSerializer<'a, 'scope> {
     type Ok = Value<'scope>;
     type Error = TypeError;
 
     fn serialize_field<T: ?Sized>(
         &mut self,
-        _key: &'static str,
-        _value: &T,
+        key: &'static str,
+        value: &T,
     ) -> Result<(), Self::Error>
     where
         T: serde::ser::Serialize,
     {
-        todo!()
+        self.set(key, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        todo!()
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use crate::{
+        error::TypeError,
+        initialize_with_defaults, to_value,
+        value::{Value, ValueScope},
+        Extension, Runtime, RuntimeOptions,
+    };
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Point(i32, i32),
+        Rect { width: u32, height: u32 },
+    }
+
+    #[derive(Serialize)]
+    struct Scene {
+        name: String,
+        shapes: Vec<Shape>,
+        tags: BTreeMap<String, bool>,
+        grid: BTreeMap<(u8, u8), u8>,
+        #[serde(with = "serde_bytes_slice")]
+        data: Vec<u8>,
+    }
+
+    mod serde_bytes_slice {
+        pub fn serialize<S>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.serialize_bytes(data)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Bytes(#[serde(with = "serde_bytes_slice")] Vec<u8>);
+
+    struct SerdeResult<T>(T);
+
+    impl<T: Serialize> crate::Serialize for SerdeResult<T> {
+        fn serialize<'scope>(
+            self,
+            scope: &mut ValueScope<'scope>,
+        ) -> Result<Value<'scope>, TypeError> {
+            to_value(scope, self.0)
+        }
+    }
+
+    fn runtime_with(extension: Extension<()>) -> Runtime<()> {
+        initialize_with_defaults();
+        Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime")
+    }
+
+    #[test]
+    fn serialize_compound_types() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(None);
+        extension.add_function("scene", |()| {
+            SerdeResult(Scene {
+                name: "test".to_string(),
+                shapes: vec![
+                    Shape::Empty,
+                    Shape::Circle(1.5),
+                    Shape::Point(1, 2),
+                    Shape::Rect {
+                        width: 3,
+                        height: 4,
+                    },
+                ],
+                tags: BTreeMap::from([("a".to_string(), true)]),
+                grid: BTreeMap::from([((0, 1), 2)]),
+                data: vec![1, 2, 3],
+            })
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: String = runtime
+            .execute(
+                "const s = scene(); \
+                JSON.stringify([s.name, s.shapes, s.tags, s.grid instanceof Map, \
+                s.grid.get(Array.from(s.grid.keys())[0]), \
+                s.data instanceof Uint8Array, Array.from(s.data)])",
+            )
+            .expect("Can't execute code");
+        assert_eq!(
+            val,
+            r#"["test",["Empty",{"Circle":1.5},{"Point":[1,2]},{"Rect":{"width":3,"height":4}}],{"a":true},true,2,true,[1,2,3]]"#
+        );
+    }
+
+    #[test]
+    fn serialize_enum_variants() {
+        let mut extension = Extension::new(None);
+        extension.add_function("shape", |(kind,): (u8,)| {
+            SerdeResult(match kind {
+                0 => Shape::Empty,
+                1 => Shape::Circle(1.5),
+                2 => Shape::Point(1, 2),
+                _ => Shape::Rect {
+                    width: 3,
+                    height: 4,
+                },
+            })
+        });
+        let mut runtime = runtime_with(extension);
+
+        let val: bool = runtime
+            .execute("shape(0) === 'Empty'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: bool = runtime
+            .execute(
+                "const circle = shape(1); \
+                Object.keys(circle).length === 1 && circle.Circle === 1.5",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: bool = runtime
+            .execute(
+                "const point = shape(2); \
+                Array.isArray(point.Point) && point.Point[0] === 1 && point.Point[1] === 2",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: bool = runtime
+            .execute(
+                "const rect = shape(3); \
+                Object.keys(rect).length === 1 && rect.Rect.width === 3 && rect.Rect.height === 4",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn serialize_map_keys() {
+        let mut extension = Extension::new(None);
+        extension.add_function("numbers", |()| {
+            SerdeResult(BTreeMap::from([(1u32, "a"), (2u32, "b")]))
+        });
+        extension.add_function("flags", |()| {
+            SerdeResult(BTreeMap::from([(true, 1u8), (false, 0u8)]))
+        });
+        extension.add_function("cells", |()| {
+            SerdeResult(BTreeMap::from([((0u8, 1u8), 2u8)]))
+        });
+        let mut runtime = runtime_with(extension);
+
+        let val: bool = runtime
+            .execute("const n = numbers(); !(n instanceof Map) && n[1] === 'a' && n[2] === 'b'")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: bool = runtime
+            .execute(
+                "const f = flags(); f instanceof Map && f.get(true) === 1 && f.get(false) === 0",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: String = runtime
+            .execute("JSON.stringify(Array.from(cells().entries()))")
+            .expect("Can't execute code");
+        assert_eq!(val, "[[[0,1],2]]");
+    }
+
+    #[test]
+    fn serialize_bytes() {
+        let mut extension = Extension::new(None);
+        extension.add_function("bytes", |(len,): (u8,)| {
+            SerdeResult(Bytes((1..=len).collect()))
+        });
+        let mut runtime = runtime_with(extension);
+
+        let val: bool = runtime
+            .execute("const b = bytes(3); b instanceof Uint8Array && b.length === 3")
+            .expect("Can't execute code");
+        assert!(val);
+
+        let val: Vec<u8> = runtime
+            .execute("Array.from(bytes(3))")
+            .expect("Can't execute code");
+        assert_eq!(val, vec![1, 2, 3]);
+
+        let val: bool = runtime
+            .execute("const e = bytes(0); e instanceof Uint8Array && e.length === 0")
+            .expect("Can't execute code");
+        assert!(val);
     }
 }
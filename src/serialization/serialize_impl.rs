@@ -1,9 +1,15 @@
+use std::collections::{BTreeMap, HashMap};
+
 use v8::NewStringType;
 
+use super::numbers::serialize_float;
 use crate::{
     error::TypeError,
     traits::Serialize,
-    value::{BigInt, Boolean, Integer, Number, Primitive, String, Value, ValueScope},
+    value::{
+        BigInt, Boolean, Float32Array, Float64Array, Integer, Number, Object, Primitive, String,
+        Uint8Array, Value, ValueScope,
+    },
 };
 
 const MAX_SAFE_INTEGER: i64 = 2i64.pow(53) - 1i64;
@@ -103,31 +109,108 @@ impl Serialize for u64 {
 impl Serialize for f32 {
     #[inline(always)]
     fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
-        Ok(Number::new(scope, f64::from(self)).into())
+        let value = serialize_float(f64::from(self))?;
+        Ok(Number::new(scope, value).into())
     }
 }
 
 impl Serialize for f64 {
     #[inline(always)]
     fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
-        Ok(Number::new(scope, self).into())
+        let value = serialize_float(self)?;
+        Ok(Number::new(scope, value).into())
     }
 }
 
 impl Serialize for std::string::String {
     fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
-        Ok(String::new(scope, self.as_str(), NewStringType::Normal).into())
+        Ok(String::try_new(scope, self.as_str(), NewStringType::Normal)?.into())
     }
 }
 
 impl Serialize for &str {
     fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
-        Ok(String::new(scope, self, NewStringType::Normal).into())
+        Ok(String::try_new(scope, self, NewStringType::Normal)?.into())
+    }
+}
+
+impl<T> Serialize for Option<T>
+where
+    T: Serialize,
+{
+    #[inline(always)]
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        match self {
+            Some(value) => value.serialize(scope),
+            None => Ok(Primitive::new_null(scope).into()),
+        }
+    }
+}
+
+/// Transfers the ownership of the bytes into the backing store of an `Uint8Array` without
+/// copying them.
+impl Serialize for Vec<u8> {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        Ok(Uint8Array::new_from_vec(scope, self).into())
+    }
+}
+
+/// Transfers the ownership of the floats into the backing store of a `Float32Array` without
+/// copying them.
+impl Serialize for Vec<f32> {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        Ok(Float32Array::new_from_vec(scope, self).into())
+    }
+}
+
+/// Transfers the ownership of the floats into the backing store of a `Float64Array` without
+/// copying them.
+impl Serialize for Vec<f64> {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        Ok(Float64Array::new_from_vec(scope, self).into())
+    }
+}
+
+/// Serializes the key-value pairs into the properties of a plain object.
+fn serialize_entries<'scope, I, T>(
+    scope: &mut ValueScope<'scope>,
+    entries: I,
+) -> Result<Value<'scope>, TypeError>
+where
+    I: IntoIterator<Item = (std::string::String, T)>,
+    T: Serialize,
+{
+    let object = Object::new(scope);
+    for (key, value) in entries {
+        let key = String::try_new(scope, key.as_str(), NewStringType::Normal)?;
+        let value = value.serialize(scope)?;
+        object.set(scope, key.into(), value);
+    }
+    Ok(object.into())
+}
+
+impl<T> Serialize for HashMap<std::string::String, T>
+where
+    T: Serialize,
+{
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        serialize_entries(scope, self)
+    }
+}
+
+impl<T> Serialize for BTreeMap<std::string::String, T>
+where
+    T: Serialize,
+{
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        serialize_entries(scope, self)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::{BTreeMap, HashMap};
+
     use super::{MAX_SAFE_INTEGER, MIN_SAFE_INTEGER};
     use crate::{
         initialize_with_defaults, traits::Serialize, Extension, FunctionArguments, Runtime,
@@ -201,6 +284,78 @@ mod test {
         test("bigint", "18446744073709551615n", |()| u64::MAX);
     }
 
+    #[test]
+    fn into_value_for_option() {
+        test("number", "1", |()| Some(1i32));
+        test("object", "null", |()| Option::<i32>::None);
+    }
+
+    #[test]
+    fn into_value_for_vec() {
+        initialize_with_defaults();
+        let mut extension = Extension::new(None);
+        extension.add_function("bytes", |(length,): (u32,)| vec![7u8; length as usize]);
+        extension.add_function("floats", |()| {
+            let mut data = Vec::with_capacity(16);
+            data.extend_from_slice(&[0.5f32, -1.5]);
+            data
+        });
+        extension.add_function("doubles", |()| vec![1.25f64, 2.5, 3.75]);
+
+        let mut r = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let ok: bool = r
+            .execute(
+                "const b = bytes(1048576); const f = floats(); const d = doubles(); \
+                b instanceof Uint8Array && b.length === 1048576 && b[0] === 7 && \
+                f instanceof Float32Array && f.length === 2 && f[1] === -1.5 && \
+                d instanceof Float64Array && d.reduce((a, x) => a + x) === 7.5 && \
+                bytes(0).length === 0",
+            )
+            .expect("Can't execute code");
+        assert!(ok);
+    }
+
+    #[test]
+    fn into_value_for_map() {
+        initialize_with_defaults();
+        let mut extension = Extension::new(None);
+        extension.add_function("hash", |()| {
+            HashMap::from([("a".to_string(), 1i32), ("b".to_string(), 2i32)])
+        });
+        extension.add_function("btree", |()| {
+            BTreeMap::from([
+                ("a".to_string(), Some("x".to_string())),
+                ("b".to_string(), None),
+            ])
+        });
+
+        let mut r = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let ok: bool = r
+            .execute(
+                "const h = hash(); const b = btree(); \
+                Object.keys(h).length === 2 && h.a === 1 && h.b === 2 && \
+                b.a === 'x' && b.b === null",
+            )
+            .expect("Can't execute code");
+        assert!(ok);
+    }
+
     #[test]
     fn safe_integer() {
         assert_eq!(MIN_SAFE_INTEGER, -9007199254740991);
@@ -0,0 +1,403 @@
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    error::{create_type_error, ErrorCode, TypeError},
+    runtime::RuntimeData,
+    traits::{Deserialize, Serialize},
+    value::{Seal, Unseal, Value, ValueScope},
+};
+
+/// Is set when the [`crate::StringPolicy::Strict`] policy was configured.
+pub(crate) static STRICT_STRINGS: AtomicBool = AtomicBool::new(false);
+
+/// Returns `true` if strings should be deserialized strictly.
+#[inline(always)]
+pub(crate) fn strict_strings() -> bool {
+    STRICT_STRINGS.load(Ordering::Relaxed)
+}
+
+/// A string that is always deserialized strictly.
+///
+/// Returns a type error if the string contains unpaired surrogates, which can't be represented
+/// as UTF-8, regardless of the configured [`crate::StringPolicy`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StrictString(pub std::string::String);
+
+/// A string as raw UTF-16 code units.
+///
+/// Can be used to access the raw data of strings, that contain unpaired surrogates.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WString(pub Vec<u16>);
+
+/// A string as raw one-byte (Latin-1) characters.
+///
+/// Can be used as an argument of fastcall functions (see [`crate::fastcall_function`]), in which
+/// case the data of the string is borrowed from the engine. Returns a type error if the string
+/// contains characters, that can't be represented as Latin-1.
+///
+/// The borrowed data is only valid during the call, so the argument can't be kept beyond it.
+/// Use [`OneByteString::into_owned()`] to keep the string:
+///
+/// ```rust,compile_fail
+/// use kopi::{fastcall_function, OneByteString};
+///
+/// struct State {
+///     key: Option<OneByteString<'static>>,
+/// }
+///
+/// fastcall_function! {
+///     fn store_key(state: &mut State, key: OneByteString<'static>) {
+///         state.key = Some(key);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OneByteString<'a>(pub Cow<'a, [u8]>);
+
+impl<'a> OneByteString<'a> {
+    /// Returns the Latin-1 characters of the string.
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the string with owned characters, copying borrowed characters.
+    pub fn into_owned(self) -> OneByteString<'static> {
+        OneByteString(Cow::Owned(self.0.into_owned()))
+    }
+
+    /// Returns the string as a `&str`, if it only contains ASCII characters.
+    pub fn as_str(&self) -> Option<&str> {
+        if self.0.is_ascii() {
+            std::str::from_utf8(&self.0).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Decodes the Latin-1 characters into a string.
+    pub fn decode(&self) -> std::string::String {
+        self.0.iter().map(|&c| c as char).collect()
+    }
+}
+
+/// Returns the UTF-16 code units of the string representation of the value.
+fn to_utf16<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Result<Vec<u16>, TypeError> {
+    let mut buffer = Vec::new();
+    write_utf16(scope, value, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Calls the function with the scratch buffer of the runtime. Falls back to a temporary buffer
+/// if the scope doesn't belong to a runtime or the scratch buffer is already in use.
+///
+/// The scratch buffer is shrunk back to its configured capacity, if a long string grew it.
+fn with_scratch<'scope, F, R>(scope: &mut ValueScope<'scope>, f: F) -> R
+where
+    F: FnOnce(&mut ValueScope<'scope>, &mut Vec<u16>) -> R,
+{
+    let Some(data) = RuntimeData::from_isolate(scope.unseal()) else {
+        return f(scope, &mut Vec::new());
+    };
+
+    let Ok(mut scratch) = data.scratch.try_borrow_mut() else {
+        return f(scope, &mut Vec::new());
+    };
+
+    let result = f(scope, &mut scratch);
+
+    let capacity = data.scratch_capacity.get();
+    if scratch.capacity() > capacity {
+        scratch.clear();
+        scratch.shrink_to(capacity);
+    }
+
+    result
+}
+
+/// Writes the UTF-16 code units of the string representation of the value into the buffer.
+fn write_utf16<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+    buffer: &mut Vec<u16>,
+) -> Result<(), TypeError> {
+    let Some(string) = value.unseal().to_string(scope.unseal()) else {
+        return Err(create_type_error(
+            ErrorCode::ArgumentType,
+            "Value can't be converted to a string",
+            scope,
+            &value,
+        ));
+    };
+
+    buffer.clear();
+    buffer.resize(string.length(), 0);
+    string.write(
+        scope.unseal(),
+        buffer,
+        0,
+        v8::WriteOptions::NO_NULL_TERMINATION,
+    );
+
+    Ok(())
+}
+
+/// Deserializes the string representation of the value. Returns a type error if the string
+/// contains unpaired surrogates.
+pub(crate) fn deserialize_strict_string<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Result<std::string::String, TypeError> {
+    with_scratch(scope, |scope, buffer| {
+        write_utf16(scope, value, buffer)?;
+        std::string::String::from_utf16(buffer).map_err(|_| TypeError {
+            code: ErrorCode::ArgumentValue,
+            msg: "String contains unpaired surrogates".to_string(),
+        })
+    })
+}
+
+/// Deserializes the string representation of the value. Unpaired surrogates are replaced with
+/// the replacement character (U+FFFD).
+pub(crate) fn deserialize_lossy_string<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+) -> Result<std::string::String, TypeError> {
+    with_scratch(scope, |scope, buffer| {
+        write_utf16(scope, value, buffer)?;
+        Ok(std::string::String::from_utf16_lossy(buffer))
+    })
+}
+
+/// Writes the string representation of the value into the target string, re-using its
+/// allocation. Unpaired surrogates are replaced with the replacement character (U+FFFD), or
+/// result in a type error when the [`crate::StringPolicy::Strict`] policy was configured.
+pub(crate) fn deserialize_string_into<'scope>(
+    scope: &mut ValueScope<'scope>,
+    value: Value<'scope>,
+    target: &mut std::string::String,
+) -> Result<(), TypeError> {
+    with_scratch(scope, |scope, buffer| {
+        write_utf16(scope, value, buffer)?;
+
+        target.clear();
+        for c in char::decode_utf16(buffer.iter().copied()) {
+            match c {
+                Ok(c) => target.push(c),
+                Err(_) if strict_strings() => {
+                    return Err(TypeError {
+                        code: ErrorCode::ArgumentValue,
+                        msg: "String contains unpaired surrogates".to_string(),
+                    })
+                }
+                Err(_) => target.push(char::REPLACEMENT_CHARACTER),
+            }
+        }
+
+        Ok(())
+    })
+}
+
+impl<'scope> Deserialize<'scope> for StrictString {
+    #[inline(always)]
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        deserialize_strict_string(scope, value).map(StrictString)
+    }
+}
+
+impl Serialize for StrictString {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        self.0.serialize(scope)
+    }
+}
+
+impl<'scope, 'a> Deserialize<'scope> for OneByteString<'a> {
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        let Some(string) = value.unseal().to_string(scope.unseal()) else {
+            return Err(create_type_error(
+                ErrorCode::ArgumentType,
+                "Value can't be converted to a string",
+                scope,
+                &value,
+            ));
+        };
+
+        if !string.contains_only_onebyte() {
+            return Err(TypeError {
+                code: ErrorCode::ArgumentValue,
+                msg: "String contains characters outside of Latin-1".to_string(),
+            });
+        }
+
+        let mut buffer = vec![0; string.length()];
+        string.write_one_byte(
+            scope.unseal(),
+            &mut buffer,
+            0,
+            v8::WriteOptions::NO_NULL_TERMINATION,
+        );
+
+        Ok(OneByteString(Cow::Owned(buffer)))
+    }
+}
+
+impl<'scope> Deserialize<'scope> for WString {
+    #[inline(always)]
+    fn deserialize(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Result<Self, TypeError> {
+        to_utf16(scope, value).map(WString)
+    }
+}
+
+impl Serialize for WString {
+    fn serialize<'scope>(self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        let string =
+            v8::String::new_from_two_byte(scope.unseal(), &self.0, v8::NewStringType::Normal)
+                .ok_or_else(|| TypeError {
+                    code: ErrorCode::ArgumentRange,
+                    msg: "String is too large".to_string(),
+                })?;
+        let value: v8::Local<v8::Value> = string.into();
+        Ok(value.seal())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::{OneByteString, StrictString, WString};
+    use crate::{fastcall_function, initialize_with_defaults, Extension, Runtime, RuntimeOptions};
+
+    fastcall_function! {
+        fn key_length(key: OneByteString) -> u32 { key.as_bytes().len() as u32 }
+    }
+
+    fastcall_function! {
+        fn store_key(state: &mut Rc<RefCell<Vec<OneByteString<'static>>>>, key: OneByteString) {
+            state.borrow_mut().push(key.into_owned());
+        }
+    }
+
+    #[test]
+    fn strict_string() {
+        initialize_with_defaults();
+        let mut runtime =
+            Runtime::new(RuntimeOptions::default(), ()).expect("Can't create runtime");
+
+        let val: StrictString = runtime.execute("'kopi ☕'").expect("Can't execute code");
+        assert_eq!(val.0, "kopi ☕");
+
+        let val: Result<StrictString, _> = runtime.execute("'kopi \\uD800'");
+        assert!(val.is_err());
+    }
+
+    #[test]
+    fn wstring() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(None);
+        extension.add_function("reverse", |(mut text,): (WString,)| {
+            text.0.reverse();
+            text
+        });
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: WString = runtime.execute("'a\\uD800b'").expect("Can't execute code");
+        assert_eq!(val.0, vec![0x61, 0xD800, 0x62]);
+
+        let val: bool = runtime
+            .execute("reverse('a\\uD800b') === 'b\\uD800a'")
+            .expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn one_byte_string() {
+        initialize_with_defaults();
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_fastcall_function("keyLength", key_length);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime");
+
+        let val: u32 = runtime
+            .execute(
+                "let sum = 0; for (let i = 0; i < 100000; i++) { sum += test.keyLength('kopi'); } sum",
+            )
+            .expect("Can't execute code");
+        assert_eq!(val, 400000);
+
+        let val: u32 = runtime
+            .execute("test.keyLength('caf\\u00e9')")
+            .expect("Can't execute code");
+        assert_eq!(val, 4);
+
+        let val: Result<u32, _> = runtime.execute("test.keyLength('kopi ☕')");
+        assert!(val.is_err());
+
+        let val: OneByteString = runtime.execute("'caf\\u00e9'").expect("Can't execute code");
+        assert_eq!(val.as_str(), None);
+        assert_eq!(val.decode(), "café");
+    }
+
+    #[test]
+    fn one_byte_string_into_owned() {
+        initialize_with_defaults();
+
+        let keys = Rc::new(RefCell::new(Vec::new()));
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_fastcall_function("storeKey", store_key);
+
+        let mut runtime = Runtime::new(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            keys.clone(),
+        )
+        .expect("Can't create runtime");
+
+        runtime
+            .execute::<(), _>(
+                "for (let i = 0; i < 100000; i++) { test.storeKey(i % 2 ? 'key1' : 'key0'); }",
+            )
+            .expect("Can't execute code");
+        drop(runtime);
+
+        let keys = keys.borrow();
+        assert_eq!(keys.len(), 100000);
+        assert_eq!(keys[0].as_str(), Some("key0"));
+        assert_eq!(keys[99999].as_str(), Some("key1"));
+    }
+}
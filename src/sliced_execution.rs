@@ -0,0 +1,188 @@
+//! Implements the time sliced execution of scripts, that hands the control back to the host
+//! between slices.
+
+use std::{
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::error::Error;
+
+/// The progress of an execution with [`crate::Runtime::execute_sliced()`], that is handed to the
+/// host between two slices.
+#[derive(Debug)]
+pub struct SlicedExecution {
+    start: Instant,
+    slices: u32,
+    terminate: bool,
+}
+
+impl SlicedExecution {
+    /// Returns the number of slices, that finished so far.
+    pub fn slices(&self) -> u32 {
+        self.slices
+    }
+
+    /// Returns the time since the start of the execution, including the time spent by the host
+    /// between the slices.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Terminates the execution instead of running the next slice, which results in
+    /// [`crate::error::Error::Terminated`].
+    pub fn terminate(&mut self) {
+        self.terminate = true;
+    }
+}
+
+/// The state of a running execution, that lives on the stack of the thread of the runtime.
+pub(crate) struct SliceState<'host> {
+    between_slices: &'host mut dyn FnMut(&mut SlicedExecution),
+    execution: SlicedExecution,
+}
+
+impl<'host> SliceState<'host> {
+    pub(crate) fn new(between_slices: &'host mut dyn FnMut(&mut SlicedExecution)) -> Self {
+        Self {
+            between_slices,
+            execution: SlicedExecution {
+                start: Instant::now(),
+                slices: 0,
+                terminate: false,
+            },
+        }
+    }
+}
+
+/// The events, that are sent to the slicer thread.
+enum SliceEvent {
+    /// The host returned and the script continues with the next slice.
+    Resumed,
+    /// The execution finished.
+    Finished,
+}
+
+/// The data shared between the slicer thread and its interrupts.
+struct SharedSlicer {
+    /// The address of the [`SliceState`] of the running execution. Is zero once the execution
+    /// finished, so that interrupts which run afterwards do nothing.
+    state: AtomicUsize,
+    sender: Mutex<Sender<SliceEvent>>,
+}
+
+impl SharedSlicer {
+    fn send(&self, event: SliceEvent) {
+        // The sender holds no data that could be corrupted, so a poisoned lock can still be used.
+        let sender = self
+            .sender
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = sender.send(event);
+    }
+}
+
+/// Requests an interrupt after each slice of an execution, which runs the callback of the host
+/// on the stack of the script.
+pub(crate) struct Slicer {
+    thread: JoinHandle<()>,
+    shared: Arc<SharedSlicer>,
+}
+
+impl Slicer {
+    /// Starts slicing the execution, that is about to start inside the isolate of the handle.
+    ///
+    /// The state must stay valid until [`Slicer::finish()`] is called.
+    pub(crate) fn start(
+        handle: v8::IsolateHandle,
+        budget_per_slice: Duration,
+        state: &mut SliceState,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let shared = Arc::new(SharedSlicer {
+            state: AtomicUsize::new(state as *mut SliceState as usize),
+            sender: Mutex::new(sender),
+        });
+
+        let slicer_shared = shared.clone();
+        let thread = std::thread::spawn(move || {
+            run_slicer(handle, budget_per_slice, receiver, slicer_shared)
+        });
+
+        Self { thread, shared }
+    }
+
+    /// Stops slicing the execution. Must be called on the thread of the runtime.
+    pub(crate) fn finish(self) -> Result<(), Error> {
+        self.shared.state.store(0, Ordering::SeqCst);
+        self.shared.send(SliceEvent::Finished);
+
+        if self.thread.join().is_err() {
+            return Err(Error::Internal("Slicer thread panicked".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Requests an interrupt once the script used its budget and waits until the host returned, so
+/// that the time spent by the host doesn't count towards the budget of the next slice.
+fn run_slicer(
+    handle: v8::IsolateHandle,
+    budget_per_slice: Duration,
+    receiver: Receiver<SliceEvent>,
+    shared: Arc<SharedSlicer>,
+) {
+    let mut deadline = Instant::now() + budget_per_slice;
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(SliceEvent::Resumed) => continue,
+            Ok(SliceEvent::Finished) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        let data = Arc::into_raw(shared.clone()) as *mut c_void;
+        if !handle.request_interrupt(end_slice, data) {
+            // SAFETY: The interrupt was not scheduled, so the data is still owned.
+            drop(unsafe { Arc::from_raw(data as *const SharedSlicer) });
+            break;
+        }
+
+        match receiver.recv() {
+            Ok(SliceEvent::Resumed) => deadline = Instant::now() + budget_per_slice,
+            Ok(SliceEvent::Finished) | Err(_) => break,
+        }
+    }
+}
+
+/// Hands the control to the host at the end of a slice. Is called by the engine on the thread
+/// of the isolate, once the running script reaches a safe point.
+///
+/// Interrupts that are not handled before the execution finishes, run at the start of the next
+/// execution and do nothing.
+extern "C" fn end_slice(isolate: &mut v8::Isolate, data: *mut c_void) {
+    // SAFETY: The data was created with `Arc::into_raw()` when the interrupt was requested.
+    let shared = unsafe { Arc::from_raw(data as *const SharedSlicer) };
+
+    let state = shared.state.load(Ordering::SeqCst) as *mut SliceState;
+
+    // SAFETY: The address is only set while the execution runs on this thread and the state
+    //         lives on the stack of the execution.
+    let Some(state) = (unsafe { state.as_mut() }) else {
+        return;
+    };
+
+    state.execution.slices += 1;
+    (state.between_slices)(&mut state.execution);
+
+    if state.execution.terminate {
+        isolate.terminate_execution();
+    }
+    shared.send(SliceEvent::Resumed);
+}
@@ -0,0 +1,219 @@
+//! Implements the watchdog of executions with a timeout and the stack sampling of slow scripts.
+
+use std::{
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::Error,
+    value::{OwnedStackFrame, Seal},
+};
+
+/// Configures the sampling of the stack of slow scripts.
+///
+/// Once an execution with a timeout runs longer than the threshold, the stack of the running
+/// script is captured in the given interval, until the execution finishes or the maximal number
+/// of samples is reached. The samples are reported with [`crate::Runtime::slow_script_report()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StackSampling {
+    /// The duration after which an execution is considered slow and the sampling starts.
+    pub threshold: Duration,
+    /// The duration between two samples.
+    pub interval: Duration,
+    /// The maximal number of samples per execution.
+    pub max_samples: usize,
+    /// The maximal number of frames per sample.
+    pub frame_limit: usize,
+}
+
+impl Default for StackSampling {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_secs(1),
+            interval: Duration::from_millis(100),
+            max_samples: 5,
+            frame_limit: 16,
+        }
+    }
+}
+
+/// The stack of a slow script, captured while it was running.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackSample {
+    /// The time since the start of the execution.
+    pub elapsed: Duration,
+    /// The frames of the stack, starting with the innermost frame.
+    pub frames: Vec<OwnedStackFrame>,
+}
+
+/// The report of an execution, that was slower than the threshold of the [`StackSampling`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlowScriptReport {
+    /// The duration of the execution.
+    pub duration: Duration,
+    /// `true` if the execution was terminated, because it exceeded its timeout.
+    pub terminated: bool,
+    /// The captured stacks of the script. Can be empty, if the script finished before the first
+    /// sample was taken.
+    pub samples: Vec<StackSample>,
+}
+
+/// Collects the stack samples of an execution.
+struct Sampler {
+    start: Instant,
+    frame_limit: usize,
+    samples: Mutex<Vec<StackSample>>,
+}
+
+impl Sampler {
+    /// The samples are only plain data, so a poisoned lock can still be used.
+    fn samples(&self) -> MutexGuard<'_, Vec<StackSample>> {
+        self.samples
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The result of an execution, that was watched by a [`Watchdog`].
+pub(crate) struct WatchdogResult {
+    /// `true` if the watchdog terminated the execution.
+    pub(crate) terminated: bool,
+    /// The report, if the execution was slower than the sampling threshold.
+    pub(crate) report: Option<SlowScriptReport>,
+}
+
+/// Watches an execution on a separate thread. Terminates the execution once it exceeds its
+/// timeout and samples the stack of slow scripts.
+pub(crate) struct Watchdog {
+    thread: JoinHandle<()>,
+    sender: Sender<()>,
+    terminated: Arc<AtomicBool>,
+    sampler: Option<(StackSampling, Arc<Sampler>)>,
+}
+
+impl Watchdog {
+    /// Starts watching the execution, that is about to start inside the isolate of the handle.
+    pub(crate) fn start(
+        handle: v8::IsolateHandle,
+        timeout: Duration,
+        sampling: Option<StackSampling>,
+    ) -> Self {
+        let start = Instant::now();
+        let terminated = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel::<()>();
+
+        let sampler = sampling.map(|sampling| {
+            let sampler = Arc::new(Sampler {
+                start,
+                frame_limit: sampling.frame_limit,
+                samples: Mutex::new(Vec::new()),
+            });
+            (sampling, sampler)
+        });
+
+        let watchdog_terminated = terminated.clone();
+        let watchdog_sampler = sampler.clone();
+        let thread = std::thread::spawn(move || {
+            let deadline = start + timeout;
+            let mut requested_samples = 0;
+            let mut next_sample = watchdog_sampler
+                .as_ref()
+                .map(|(sampling, _)| start + sampling.threshold);
+
+            loop {
+                let wake_up = match next_sample {
+                    Some(next_sample) if next_sample < deadline => next_sample,
+                    _ => deadline,
+                };
+
+                // Dropping the sender wakes up the watchdog, if the execution finished in time.
+                let timeout = wake_up.saturating_duration_since(Instant::now());
+                if let Ok(()) | Err(RecvTimeoutError::Disconnected) = receiver.recv_timeout(timeout)
+                {
+                    break;
+                }
+
+                if Instant::now() >= deadline {
+                    watchdog_terminated.store(true, Ordering::SeqCst);
+                    handle.terminate_execution();
+                    break;
+                }
+
+                if let Some((sampling, sampler)) = watchdog_sampler.as_ref() {
+                    let data = Arc::into_raw(sampler.clone()) as *mut c_void;
+                    if !handle.request_interrupt(sample_stack, data) {
+                        // SAFETY: The interrupt was not scheduled, so the data is still owned.
+                        drop(unsafe { Arc::from_raw(data as *const Sampler) });
+                    }
+
+                    requested_samples += 1;
+                    next_sample = if requested_samples < sampling.max_samples {
+                        Some(wake_up + sampling.interval)
+                    } else {
+                        None
+                    };
+                }
+            }
+        });
+
+        Self {
+            thread,
+            sender,
+            terminated,
+            sampler,
+        }
+    }
+
+    /// Stops watching the execution.
+    pub(crate) fn finish(self) -> Result<WatchdogResult, Error> {
+        drop(self.sender);
+        if self.thread.join().is_err() {
+            return Err(Error::Internal("Watchdog thread panicked".to_string()));
+        }
+
+        let terminated = self.terminated.load(Ordering::SeqCst);
+        let report = self.sampler.and_then(|(sampling, sampler)| {
+            let duration = sampler.start.elapsed();
+            if duration < sampling.threshold {
+                return None;
+            }
+
+            Some(SlowScriptReport {
+                duration,
+                terminated,
+                samples: std::mem::take(&mut *sampler.samples()),
+            })
+        });
+
+        Ok(WatchdogResult { terminated, report })
+    }
+}
+
+/// Captures the stack of the running script. Is called by the engine on the thread of the
+/// isolate, once the running script reaches a safe point.
+///
+/// Interrupts that are not handled before the execution finishes, run at the start of the next
+/// execution. Their samples are discarded, since the report was already created.
+extern "C" fn sample_stack(isolate: &mut v8::Isolate, data: *mut c_void) {
+    // SAFETY: The data was created with `Arc::into_raw()` when the interrupt was requested.
+    let sampler = unsafe { Arc::from_raw(data as *const Sampler) };
+
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = scope.get_current_context();
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let Some(stack_trace) = v8::StackTrace::current_stack_trace(scope, sampler.frame_limit) else {
+        return;
+    };
+
+    let frames = stack_trace.seal().to_owned_frames(scope.seal());
+    let elapsed = sampler.start.elapsed();
+    sampler.samples().push(StackSample { elapsed, frames });
+}
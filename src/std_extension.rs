@@ -0,0 +1,329 @@
+//! Implements the standard library extension.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::Extension;
+
+/// Namespace of the standard library extension.
+const NAMESPACE: &str = "std";
+
+/// Provides utility functions for scripts under the `std` namespace.
+///
+/// All numeric functions are implemented as fastcall functions. Functions working on strings
+/// are implemented as static functions, since fastcall functions don't support strings.
+///
+/// | Function                                     | Description                                        |
+/// |----------------------------------------------|----------------------------------------------------|
+/// | `std.clamp(x, min, max)`                     | Clamps `x` between `min` and `max`.                |
+/// | `std.lerp(a, b, t)`                          | Linear interpolation between `a` and `b`.          |
+/// | `std.inverseLerp(a, b, x)`                   | Returns `t` so that `lerp(a, b, t) == x`.          |
+/// | `std.remap(x, inMin, inMax, outMin, outMax)` | Maps `x` from one range into another.              |
+/// | `std.smoothstep(edge0, edge1, x)`            | Hermite interpolation between `0` and `1`.         |
+/// | `std.wrap(x, min, max)`                      | Wraps `x` into the range `[min, max)`.             |
+/// | `std.fract(x)`                               | Returns the fractional part of `x`.                |
+/// | `std.sign(x)`                                | Returns `-1`, `0` or `1`.                          |
+/// | `std.degToRad(x)`                            | Converts degrees into radians.                     |
+/// | `std.radToDeg(x)`                            | Converts radians into degrees.                     |
+/// | `std.approxEqual(a, b, epsilon)`             | Returns `true` if `a` and `b` differ by `epsilon`. |
+/// | `std.isPowerOfTwo(x)`                        | Returns `true` if `x` is a power of two.           |
+/// | `std.nextPowerOfTwo(x)`                      | Returns the next power of two of `x`.              |
+/// | `std.gcd(a, b)`                              | Returns the greatest common divisor.               |
+/// | `std.rand()`                                 | Returns a random number in `[0, 1)`.               |
+/// | `std.randRange(min, max)`                    | Returns a random number in `[min, max)`.           |
+/// | `std.randInt(min, max)`                      | Returns a random integer in `[min, max]`.          |
+/// | `std.hashString(text)`                       | Returns the 32 bit FNV-1a hash of a string.        |
+///
+/// The random number generator is not cryptographically secure.
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::{initialize_with_defaults, Runtime, RuntimeOptions, StdExtension};
+///
+/// initialize_with_defaults();
+///
+/// let mut runtime = Runtime::new(
+///     RuntimeOptions {
+///         extensions: vec![StdExtension::create()],
+///         ..Default::default()
+///     },
+///     (),
+/// )
+/// .expect("Can't create runtime");
+///
+/// let val: f64 = runtime
+///     .execute("std.clamp(std.lerp(0.0, 10.0, 0.5), 0.0, 2.0)")
+///     .expect("Can't execute code");
+///
+/// assert_eq!(val, 2.0);
+/// ```
+pub struct StdExtension;
+
+impl StdExtension {
+    /// Creates the standard library extension.
+    pub fn create<STATE>() -> Extension<STATE> {
+        let mut extension = Extension::new(Some(NAMESPACE));
+
+        extension.add_fastcall_function("clamp", clamp);
+        extension.add_fastcall_function("lerp", lerp);
+        extension.add_fastcall_function("inverseLerp", inverse_lerp);
+        extension.add_fastcall_function("remap", remap);
+        extension.add_fastcall_function("smoothstep", smoothstep);
+        extension.add_fastcall_function("wrap", wrap);
+        extension.add_fastcall_function("fract", fract);
+        extension.add_fastcall_function("sign", sign);
+        extension.add_fastcall_function("degToRad", deg_to_rad);
+        extension.add_fastcall_function("radToDeg", rad_to_deg);
+        extension.add_fastcall_function("approxEqual", approx_equal);
+        extension.add_fastcall_function("isPowerOfTwo", is_power_of_two);
+        extension.add_fastcall_function("nextPowerOfTwo", next_power_of_two);
+        extension.add_fastcall_function("gcd", gcd);
+        extension.add_fastcall_function("rand", rand);
+        extension.add_fastcall_function("randRange", rand_range);
+        extension.add_fastcall_function("randInt", rand_int);
+        extension.add_static_function("hashString", hash_string);
+
+        extension
+    }
+}
+
+fastcall_function! {
+    fn clamp(x: f64, min: f64, max: f64) -> f64 {
+        x.max(min).min(max)
+    }
+}
+
+fastcall_function! {
+    fn lerp(a: f64, b: f64, t: f64) -> f64 {
+        a + (b - a) * t
+    }
+}
+
+fastcall_function! {
+    fn inverse_lerp(a: f64, b: f64, x: f64) -> f64 {
+        if a == b {
+            0.0
+        } else {
+            (x - a) / (b - a)
+        }
+    }
+}
+
+fastcall_function! {
+    fn remap(x: f64, in_min: f64, in_max: f64, out_min: f64, out_max: f64) -> f64 {
+        if in_min == in_max {
+            out_min
+        } else {
+            out_min + (out_max - out_min) * ((x - in_min) / (in_max - in_min))
+        }
+    }
+}
+
+fastcall_function! {
+    fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+        if edge0 == edge1 {
+            if x < edge0 {
+                0.0
+            } else {
+                1.0
+            }
+        } else {
+            let t = ((x - edge0) / (edge1 - edge0)).max(0.0).min(1.0);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+fastcall_function! {
+    fn wrap(x: f64, min: f64, max: f64) -> f64 {
+        let range = max - min;
+        if range == 0.0 {
+            min
+        } else {
+            min + (x - min).rem_euclid(range)
+        }
+    }
+}
+
+fastcall_function! {
+    fn fract(x: f64) -> f64 {
+        x.fract()
+    }
+}
+
+fastcall_function! {
+    fn sign(x: f64) -> f64 {
+        if x > 0.0 {
+            1.0
+        } else if x < 0.0 {
+            -1.0
+        } else {
+            x
+        }
+    }
+}
+
+fastcall_function! {
+    fn deg_to_rad(x: f64) -> f64 {
+        x.to_radians()
+    }
+}
+
+fastcall_function! {
+    fn rad_to_deg(x: f64) -> f64 {
+        x.to_degrees()
+    }
+}
+
+fastcall_function! {
+    fn approx_equal(a: f64, b: f64, epsilon: f64) -> bool {
+        (a - b).abs() <= epsilon
+    }
+}
+
+fastcall_function! {
+    fn is_power_of_two(x: u32) -> bool {
+        x.is_power_of_two()
+    }
+}
+
+fastcall_function! {
+    fn next_power_of_two(x: u32) -> u32 {
+        x.checked_next_power_of_two().unwrap_or(0)
+    }
+}
+
+fastcall_function! {
+    fn gcd(a: u32, b: u32) -> u32 {
+        let (mut a, mut b) = (a, b);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+}
+
+fastcall_function! {
+    fn rand() -> f64 {
+        next_random()
+    }
+}
+
+fastcall_function! {
+    fn rand_range(min: f64, max: f64) -> f64 {
+        min + (max - min) * next_random()
+    }
+}
+
+fastcall_function! {
+    fn rand_int(min: i32, max: i32) -> i32 {
+        let (min, max) = (min.min(max) as f64, min.max(max) as f64);
+        (min + ((max - min + 1.0) * next_random()).floor()) as i32
+    }
+}
+
+static_function! {
+    fn hash_string(text: String) -> u32 {
+        text.bytes().fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        })
+    }
+}
+
+/// The state of the random number generator. Is seeded on first use.
+static RANDOM_STATE: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the next number of a xorshift64* generator in the range of `[0, 1)`.
+fn next_random() -> f64 {
+    let mut x = RANDOM_STATE.load(Ordering::Relaxed);
+    if x == 0 {
+        x = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15)
+            | 1;
+    }
+
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    RANDOM_STATE.store(x, Ordering::Relaxed);
+
+    let random = x.wrapping_mul(0x2545_F491_4F6C_DD1D);
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{initialize_with_defaults, Runtime, RuntimeOptions, StdExtension};
+
+    fn create_runtime() -> Runtime<()> {
+        initialize_with_defaults();
+
+        Runtime::new(
+            RuntimeOptions {
+                extensions: vec![StdExtension::create()],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime")
+    }
+
+    #[test]
+    fn math_functions() {
+        let mut runtime = create_runtime();
+
+        let val: f64 = runtime
+            .execute("std.clamp(5.0, 0.0, 1.0)")
+            .expect("Can't execute code");
+        assert_eq!(val, 1.0);
+
+        let val: f64 = runtime
+            .execute("std.remap(5.0, 0.0, 10.0, 0.0, 100.0)")
+            .expect("Can't execute code");
+        assert_eq!(val, 50.0);
+
+        let val: f64 = runtime
+            .execute("std.wrap(-1.0, 0.0, 10.0)")
+            .expect("Can't execute code");
+        assert_eq!(val, 9.0);
+
+        let val: u32 = runtime
+            .execute("std.gcd(12, 18)")
+            .expect("Can't execute code");
+        assert_eq!(val, 6);
+    }
+
+    #[test]
+    fn random_functions() {
+        let mut runtime = create_runtime();
+
+        let val: bool = runtime
+            .execute(
+                "let valid = true; \
+                for (let i = 0; i < 1000; i++) { \
+                    const x = std.randInt(-3, 3); \
+                    valid = valid && x >= -3 && x <= 3; \
+                    const y = std.randRange(1.0, 2.0); \
+                    valid = valid && y >= 1.0 && y < 2.0; \
+                } \
+                valid",
+            )
+            .expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn hash_string() {
+        let mut runtime = create_runtime();
+
+        let val: u32 = runtime
+            .execute("std.hashString('hello')")
+            .expect("Can't execute code");
+        assert_eq!(val, 0x4f9f2cab);
+    }
+}
@@ -0,0 +1,303 @@
+//! Helpers to write concise tests for extensions, that are built on top of the crate.
+//!
+//! # Example
+//!
+//! ```rust
+//! use kopi::{assert_script_eq, testing::with_runtime};
+//!
+//! with_runtime(|runtime| {
+//!     assert_script_eq!(runtime, "1 + 1", 2);
+//!     assert_script_eq!(runtime, "'kopi'.toUpperCase()", "KOPI".to_string());
+//! });
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::Error, initialize_with_defaults, DeserializeOwned, Runtime, RuntimeOptions,
+    ScriptEventLoop,
+};
+
+pub use crate::assert_script_eq;
+
+/// Creates a runtime with the default options and calls the closure with it.
+///
+/// Initializes the V8 engine with the default options, if it's not initialized yet.
+pub fn with_runtime<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut Runtime<()>) -> R,
+{
+    with_runtime_options(RuntimeOptions::default(), (), f)
+}
+
+/// Creates a runtime with the given options and state and calls the closure with it.
+///
+/// Initializes the V8 engine with the default options, if it's not initialized yet.
+///
+/// # Panics
+///
+/// Panics if the runtime can't be created, for example because an extension is invalid.
+pub fn with_runtime_options<STATE, F, R>(options: RuntimeOptions<STATE>, state: STATE, f: F) -> R
+where
+    F: FnOnce(&mut Runtime<STATE>) -> R,
+{
+    initialize_with_defaults();
+
+    let mut runtime = match Runtime::new(options, state) {
+        Ok(runtime) => runtime,
+        Err(err) => panic!("Can't create runtime: {}", err),
+    };
+    f(&mut runtime)
+}
+
+/// Executes the script and returns the evaluated value. The value is only used to infer the
+/// type of the result.
+///
+/// Used by the [`crate::assert_script_eq`] macro.
+#[doc(hidden)]
+pub fn execute_as<STATE, T>(runtime: &mut Runtime<STATE>, source: &str, _: &T) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    runtime.execute(source)
+}
+
+/// Asserts that the script evaluates to the expected value.
+///
+/// The evaluated value is deserialized into the type of the expected value. Panics with the
+/// error, if the script can't be executed.
+///
+/// # Example
+///
+/// ```rust
+/// use kopi::{assert_script_eq, testing::with_runtime};
+///
+/// with_runtime(|runtime| {
+///     assert_script_eq!(runtime, "[1, 2].length", 2_u32);
+///     assert_script_eq!(runtime, "null", None::<i32>, "null must map to None");
+/// });
+/// ```
+#[macro_export]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+macro_rules! assert_script_eq {
+    ($runtime:expr, $source:expr, $expected:expr $(,)?) => {{
+        let expected = $expected;
+        match $crate::testing::execute_as($runtime, $source, &expected) {
+            Ok(value) => assert_eq!(value, expected, "Script: {}", $source),
+            Err(err) => panic!("Can't execute script '{}': {}", $source, err),
+        }
+    }};
+    ($runtime:expr, $source:expr, $expected:expr, $($arg:tt)+) => {{
+        let expected = $expected;
+        match $crate::testing::execute_as($runtime, $source, &expected) {
+            Ok(value) => assert_eq!(value, expected, $($arg)+),
+            Err(err) => panic!("Can't execute script '{}': {}", $source, err),
+        }
+    }};
+}
+
+/// A clock, that only advances when told to. Drives the event loop of a runtime with the time
+/// of the clock, so that timers can be tested deterministically.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use kopi::{
+///     testing::{with_runtime_options, FakeClock},
+///     RuntimeOptions, TimerExtension,
+/// };
+///
+/// let options = RuntimeOptions {
+///     extensions: vec![TimerExtension::create()],
+///     ..Default::default()
+/// };
+///
+/// with_runtime_options(options, (), |runtime| {
+///     let mut clock = FakeClock::new();
+///     runtime
+///         .execute::<(), _>("var frames = 0; setInterval(() => frames++, 16);")
+///         .expect("Can't execute code");
+///
+///     clock.run_for(runtime, Duration::from_millis(64), Duration::from_millis(16));
+///
+///     let frames: u32 = runtime.execute("frames").expect("Can't execute code");
+///     assert_eq!(frames, 4);
+/// });
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct FakeClock {
+    now: Instant,
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeClock {
+    /// Creates a new clock, that starts at the current instant.
+    pub fn new() -> Self {
+        Self {
+            now: Instant::now(),
+        }
+    }
+
+    /// Returns the current instant of the clock.
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// Advances the clock by the given duration.
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+
+    /// Ticks the event loop once with the current instant of the clock. Returns `true` if there
+    /// is work left.
+    pub fn tick<L>(&self, event_loop: &mut L) -> bool
+    where
+        L: ScriptEventLoop,
+    {
+        event_loop.tick(self.now)
+    }
+
+    /// Advances the clock in steps and ticks the event loop after each step, until the given
+    /// duration elapsed. Returns `true` if there is work left.
+    pub fn run_for<L>(&mut self, event_loop: &mut L, duration: Duration, step: Duration) -> bool
+    where
+        L: ScriptEventLoop,
+    {
+        let end = self.now + duration;
+        let step = step.max(Duration::from_nanos(1));
+
+        let mut pending = self.tick(event_loop);
+        while self.now < end {
+            self.now = (self.now + step).min(end);
+            pending = self.tick(event_loop);
+        }
+        pending
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use super::{with_runtime, with_runtime_options, FakeClock};
+    use crate::{Extension, RuntimeOptions, ScriptEventLoop, TimerExtension};
+
+    struct CountingLoop {
+        ticks: Vec<Instant>,
+    }
+
+    impl ScriptEventLoop for CountingLoop {
+        fn poll_timers(&mut self, now: Instant) -> usize {
+            self.ticks.push(now);
+            0
+        }
+
+        fn drain_microtasks(&mut self) {}
+
+        fn has_pending_work(&self) -> bool {
+            self.ticks.len() < 3
+        }
+    }
+
+    #[test]
+    fn assert_script_eq() {
+        with_runtime(|runtime| {
+            assert_script_eq!(runtime, "1 + 1", 2);
+            assert_script_eq!(runtime, "'a' + 'b'", "ab".to_string(), "must concatenate");
+        });
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let extension_calls = calls.clone();
+
+        let mut extension = Extension::new(Some("test"));
+        extension.add_function("call", move |()| {
+            extension_calls.fetch_add(1, Ordering::SeqCst) + 1
+        });
+
+        with_runtime_options(
+            RuntimeOptions {
+                extensions: vec![extension],
+                ..Default::default()
+            },
+            (),
+            |runtime| {
+                assert_script_eq!(runtime, "test.call() + test.call()", 3_u32);
+            },
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_script_eq_mismatch() {
+        with_runtime(|runtime| {
+            assert_script_eq!(runtime, "1 + 1", 3);
+        });
+    }
+
+    #[test]
+    fn fake_clock() {
+        let mut clock = FakeClock::new();
+        let start = clock.now();
+        let mut event_loop = CountingLoop { ticks: Vec::new() };
+
+        clock.advance(Duration::from_millis(10));
+        assert!(clock.tick(&mut event_loop));
+        assert_eq!(event_loop.ticks, vec![start + Duration::from_millis(10)]);
+
+        let pending = clock.run_for(
+            &mut event_loop,
+            Duration::from_millis(25),
+            Duration::from_millis(10),
+        );
+        assert!(!pending);
+        assert_eq!(clock.now(), start + Duration::from_millis(35));
+        assert_eq!(
+            event_loop.ticks,
+            vec![
+                start + Duration::from_millis(10),
+                start + Duration::from_millis(10),
+                start + Duration::from_millis(20),
+                start + Duration::from_millis(30),
+                start + Duration::from_millis(35),
+            ]
+        );
+    }
+
+    #[test]
+    fn fake_clock_drives_timers() {
+        let options = RuntimeOptions {
+            extensions: vec![TimerExtension::create()],
+            ..Default::default()
+        };
+
+        with_runtime_options(options, (), |runtime| {
+            let mut clock = FakeClock::new();
+            runtime
+                .execute::<(), _>("var fired = false; setTimeout(() => { fired = true; }, 100);")
+                .expect("Can't execute code");
+
+            assert!(clock.tick(runtime));
+            clock.advance(Duration::from_millis(99));
+            assert!(clock.tick(runtime));
+            assert_script_eq!(runtime, "fired", false);
+
+            clock.advance(Duration::from_millis(1));
+            assert!(!clock.tick(runtime));
+            assert_script_eq!(runtime, "fired", true);
+        });
+    }
+}
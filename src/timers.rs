@@ -0,0 +1,440 @@
+//! Implements the timers of scripts, that are run by the event loop of the host.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    runtime::RuntimeData,
+    value::{new_string, NewStringType},
+    Extension, StaticFunction,
+};
+
+/// Provides the timer functions of the HTML standard to scripts.
+///
+/// | Function                                  | Description                                         |
+/// |-------------------------------------------|-----------------------------------------------------|
+/// | `setTimeout(callback, delay?, ...args)`   | Calls the callback once after the delay.            |
+/// | `setInterval(callback, delay?, ...args)`  | Calls the callback repeatedly after each delay.     |
+/// | `clearTimeout(id)`                        | Cancels the timer with the given id.                |
+/// | `clearInterval(id)`                       | Cancels the timer with the given id.                |
+///
+/// All delays are given in milliseconds. The timers don't run by themselves, the host runs the
+/// expired timers with [`crate::ScriptEventLoop::poll_timers()`] and decides which instant is
+/// "now". The delay of a timer starts at the first poll after the timer was created, so a timer
+/// never runs early, but can run late by up to one iteration of the host loop. Timers that are
+/// created by a timer callback run at the earliest in the next poll. The delays stop while the
+/// runtime is paused with [`crate::Runtime::pause()`].
+///
+/// Exceptions that are thrown by timer callbacks are counted as thrown exceptions and logged.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use kopi::{initialize_with_defaults, Runtime, RuntimeOptions, ScriptEventLoop, TimerExtension};
+///
+/// initialize_with_defaults();
+///
+/// let mut runtime = Runtime::new(
+///     RuntimeOptions {
+///         extensions: vec![TimerExtension::create()],
+///         ..Default::default()
+///     },
+///     (),
+/// )
+/// .expect("Can't create runtime");
+///
+/// runtime
+///     .execute::<(), _>("var fired = false; setTimeout(() => { fired = true; }, 100);")
+///     .expect("Can't execute code");
+///
+/// let start = Instant::now();
+/// runtime.tick(start);
+/// runtime.tick(start + Duration::from_millis(100));
+///
+/// let val: bool = runtime.execute("fired").expect("Can't execute code");
+/// assert!(val);
+/// ```
+pub struct TimerExtension;
+
+impl TimerExtension {
+    /// Creates the timer extension.
+    pub fn create<STATE>() -> Extension<STATE> {
+        let mut extension = Extension::new(None);
+        extension.add_static_function("setTimeout", SetTimeout);
+        extension.add_static_function("setInterval", SetInterval);
+        extension.add_static_function("clearTimeout", ClearTimer);
+        extension.add_static_function("clearInterval", ClearTimer);
+        extension
+    }
+}
+
+/// A timer, that was created by a script.
+struct Timer {
+    id: u32,
+    callback: v8::Global<v8::Function>,
+    arguments: Vec<v8::Global<v8::Value>>,
+    delay: Duration,
+    repeat: bool,
+    /// The delay, that is left once the timer is seen by the next poll.
+    remaining: Duration,
+    /// The instant the timer expires. `None` until the timer was seen by the next poll.
+    due: Option<Instant>,
+}
+
+/// The callback of an expired timer together with its arguments.
+pub(crate) struct ExpiredTimer {
+    pub(crate) callback: v8::Global<v8::Function>,
+    pub(crate) arguments: Vec<v8::Global<v8::Value>>,
+}
+
+/// The pending timers of a runtime.
+#[derive(Default)]
+pub(crate) struct Timers {
+    timers: Vec<Timer>,
+    last_id: u32,
+    /// The instant of the last poll.
+    last_poll: Option<Instant>,
+}
+
+impl Timers {
+    /// Adds a timer and returns its id. Ids are never zero, so that scripts can use `0` as
+    /// "no timer".
+    fn add(
+        &mut self,
+        callback: v8::Global<v8::Function>,
+        arguments: Vec<v8::Global<v8::Value>>,
+        delay: Duration,
+        repeat: bool,
+    ) -> u32 {
+        self.last_id = self.last_id.checked_add(1).unwrap_or(1);
+        self.timers.push(Timer {
+            id: self.last_id,
+            callback,
+            arguments,
+            delay,
+            repeat,
+            remaining: delay,
+            due: None,
+        });
+        self.last_id
+    }
+
+    /// Removes the timer with the given id. Unknown ids are ignored.
+    fn remove(&mut self, id: u32) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+
+    /// Starts the delay of the new timers and returns the ids of the timers, that expired at
+    /// the given instant, in the order they expire.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<u32> {
+        self.last_poll = Some(now);
+
+        let mut expired = Vec::new();
+        for timer in self.timers.iter_mut() {
+            let due = *timer.due.get_or_insert(now + timer.remaining);
+            if due <= now {
+                expired.push((due, timer.id));
+            }
+        }
+
+        // Timers with the same due instant run in the order they were created.
+        expired.sort_unstable();
+        expired.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Takes the callback of the expired timer with the given id. Intervals are scheduled again
+    /// relative to the given instant. Returns `None` if the timer was removed in the meantime.
+    pub(crate) fn take(&mut self, id: u32, now: Instant) -> Option<ExpiredTimer> {
+        let index = self.timers.iter().position(|timer| timer.id == id)?;
+
+        if self.timers[index].repeat {
+            let timer = &mut self.timers[index];
+            timer.remaining = timer.delay;
+            timer.due = Some(now + timer.delay);
+            return Some(ExpiredTimer {
+                callback: timer.callback.clone(),
+                arguments: timer.arguments.clone(),
+            });
+        }
+
+        let timer = self.timers.remove(index);
+        Some(ExpiredTimer {
+            callback: timer.callback,
+            arguments: timer.arguments,
+        })
+    }
+
+    /// Stops the delays of all timers at the instant of the last poll. The remaining delays start
+    /// again with the next poll, so that the time in between doesn't count.
+    pub(crate) fn suspend(&mut self) {
+        let Some(last_poll) = self.last_poll else {
+            return;
+        };
+
+        for timer in self.timers.iter_mut() {
+            if let Some(due) = timer.due.take() {
+                timer.remaining = due.saturating_duration_since(last_poll);
+            }
+        }
+    }
+
+    /// Returns `true` if no timer is pending.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Removes all timers.
+    pub(crate) fn clear(&mut self) {
+        self.timers.clear();
+    }
+}
+
+/// The `setTimeout()` function.
+struct SetTimeout;
+
+impl StaticFunction for SetTimeout {
+    fn callback() -> v8::FunctionCallback {
+        use v8::MapFnTo;
+        set_timeout.map_fn_to()
+    }
+}
+
+/// The `setInterval()` function.
+struct SetInterval;
+
+impl StaticFunction for SetInterval {
+    fn callback() -> v8::FunctionCallback {
+        use v8::MapFnTo;
+        set_interval.map_fn_to()
+    }
+}
+
+/// The `clearTimeout()` and `clearInterval()` functions.
+struct ClearTimer;
+
+impl StaticFunction for ClearTimer {
+    fn callback() -> v8::FunctionCallback {
+        use v8::MapFnTo;
+        clear_timer.map_fn_to()
+    }
+}
+
+/// Adds a timer, that runs once.
+fn set_timeout(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    rv: v8::ReturnValue,
+) {
+    add_timer(scope, args, rv, false);
+}
+
+/// Adds a timer, that repeats until it's cleared.
+fn set_interval(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    rv: v8::ReturnValue,
+) {
+    add_timer(scope, args, rv, true);
+}
+
+/// Adds a timer and returns its id. Throws a type error if the callback is not a function.
+/// Strings are not evaluated as code.
+fn add_timer(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+    repeat: bool,
+) {
+    let Ok(callback) = v8::Local::<v8::Function>::try_from(args.get(0)) else {
+        let msg = new_string(
+            scope,
+            "The callback must be a function",
+            NewStringType::Normal,
+        );
+        let exception = v8::Exception::type_error(scope, msg);
+        scope.throw_exception(exception);
+        return;
+    };
+
+    // Like in the HTML standard, invalid and negative delays are treated as zero.
+    let Some(delay) = args.get(1).number_value(scope) else {
+        return;
+    };
+    let delay = if delay.is_finite() && delay > 0.0 {
+        Duration::from_secs_f64(delay.min(u32::MAX as f64) / 1000.0)
+    } else {
+        Duration::ZERO
+    };
+
+    let arguments = (2..args.length())
+        .map(|index| v8::Global::new(scope, args.get(index)))
+        .collect();
+    let callback = v8::Global::new(scope, callback);
+
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    let id = runtime_data
+        .timers
+        .borrow_mut()
+        .add(callback, arguments, delay, repeat);
+
+    rv.set_uint32(id);
+}
+
+/// Cancels the timer with the given id. Invalid ids are ignored.
+fn clear_timer(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    _rv: v8::ReturnValue,
+) {
+    let Some(id) = args.get(0).uint32_value(scope) else {
+        return;
+    };
+
+    let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+        return;
+    };
+
+    runtime_data.timers.borrow_mut().remove(id);
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        initialize_with_defaults, Runtime, RuntimeOptions, ScriptEventLoop, TimerExtension,
+    };
+
+    fn timer_runtime() -> Runtime<()> {
+        initialize_with_defaults();
+
+        Runtime::new(
+            RuntimeOptions {
+                extensions: vec![TimerExtension::create()],
+                ..Default::default()
+            },
+            (),
+        )
+        .expect("Can't create runtime")
+    }
+
+    #[test]
+    fn timeout_runs_once_after_its_delay() {
+        let mut runtime = timer_runtime();
+        let start = Instant::now();
+
+        runtime
+            .execute::<(), _>("var log = []; setTimeout((a, b) => log.push(a + b), 50, 1, 2);")
+            .expect("Can't execute code");
+        assert!(runtime.has_pending_work());
+
+        assert_eq!(runtime.poll_timers(start), 0);
+        assert_eq!(runtime.poll_timers(start + Duration::from_millis(49)), 0);
+        assert_eq!(runtime.poll_timers(start + Duration::from_millis(50)), 1);
+        assert!(!runtime.has_pending_work());
+        assert_eq!(runtime.poll_timers(start + Duration::from_millis(100)), 0);
+
+        let val: Vec<i32> = runtime.execute("log").expect("Can't execute code");
+        assert_eq!(val, vec![3]);
+    }
+
+    #[test]
+    fn timers_run_in_order_and_can_be_cleared() {
+        let mut runtime = timer_runtime();
+        let start = Instant::now();
+
+        runtime
+            .execute::<(), _>(
+                "var log = []; \
+                 setTimeout(() => log.push('b'), 20); \
+                 const c = setTimeout(() => log.push('c'), 20); \
+                 setTimeout(() => { log.push('a'); clearTimeout(c); }, 10); \
+                 setTimeout(() => log.push('d'), 20);",
+            )
+            .expect("Can't execute code");
+
+        runtime.poll_timers(start);
+        assert_eq!(runtime.poll_timers(start + Duration::from_millis(30)), 3);
+
+        let val: String = runtime.execute("log.join()").expect("Can't execute code");
+        assert_eq!(val, "a,b,d");
+    }
+
+    #[test]
+    fn interval_repeats_until_cleared() {
+        let mut runtime = timer_runtime();
+        let start = Instant::now();
+
+        runtime
+            .execute::<(), _>(
+                "var count = 0; \
+                 const id = setInterval(() => { if (++count === 3) clearInterval(id); }, 10);",
+            )
+            .expect("Can't execute code");
+
+        let mut now = start;
+        runtime.poll_timers(now);
+        while runtime.has_pending_work() {
+            now += Duration::from_millis(10);
+            assert_eq!(runtime.poll_timers(now), 1);
+        }
+
+        let val: i32 = runtime.execute("count").expect("Can't execute code");
+        assert_eq!(val, 3);
+        assert_eq!(now, start + Duration::from_millis(30));
+    }
+
+    #[test]
+    fn timers_created_by_callbacks_run_in_the_next_poll() {
+        let mut runtime = timer_runtime();
+        let start = Instant::now();
+
+        runtime
+            .execute::<(), _>("var count = 0; setTimeout(() => setTimeout(() => count++));")
+            .expect("Can't execute code");
+
+        assert_eq!(runtime.poll_timers(start), 1);
+        let val: i32 = runtime.execute("count").expect("Can't execute code");
+        assert_eq!(val, 0);
+
+        assert_eq!(runtime.poll_timers(start), 1);
+        let val: i32 = runtime.execute("count").expect("Can't execute code");
+        assert_eq!(val, 1);
+    }
+
+    #[test]
+    fn throwing_callback_does_not_stop_other_timers() {
+        let mut runtime = timer_runtime();
+        let start = Instant::now();
+
+        runtime
+            .execute::<(), _>(
+                "var ran = false; \
+                 setTimeout(() => { throw new Error('boom'); }); \
+                 setTimeout(() => { ran = true; });",
+            )
+            .expect("Can't execute code");
+
+        let thrown = runtime.counters().exceptions_thrown;
+        assert_eq!(runtime.poll_timers(start), 2);
+        assert_eq!(runtime.counters().exceptions_thrown, thrown + 1);
+
+        let val: bool = runtime.execute("ran").expect("Can't execute code");
+        assert!(val);
+    }
+
+    #[test]
+    fn callback_must_be_a_function() {
+        let mut runtime = timer_runtime();
+
+        let val: String = runtime
+            .execute("try { setTimeout('count++'); 'none' } catch (e) { e.name }")
+            .expect("Can't execute code");
+        assert_eq!(val, "TypeError");
+        assert!(!runtime.has_pending_work());
+    }
+}
@@ -1,6 +1,9 @@
 ///! Traits to abstract common operations on ECMAScript values.
+use std::borrow::Cow;
+
 use crate::{
     error::TypeError,
+    serialization::OneByteString,
     value::{Value, ValueScope},
 };
 
@@ -40,6 +43,16 @@ pub trait FastcallArgument: private::Sealed {
     const V8_TYPE: v8::fast_api::Type;
 }
 
+/// Converts the value, that V8 passes to a fast call, into a fastcall argument.
+///
+/// The value is borrowed from the frame of the fast call, so arguments that borrow data from
+/// the engine (like [`OneByteString`]) can't outlive the call.
+#[doc(hidden)]
+pub trait FromFastcallValue<'value>: FastcallArgument {
+    /// Converts the value into the implementor.
+    fn from_value(value: &'value Self::Value) -> Self;
+}
+
 /// Trait for types that are supported to be used as return value for fastcall functions.
 /// Sealed trait, since there is only a limited amount of types supported by V8.
 pub trait FastcallReturnValue: private::Sealed {
@@ -58,6 +71,13 @@ macro_rules! fastcall_argument {
 
             const V8_TYPE: v8::fast_api::Type = v8::fast_api::Type::$v8_type;
         }
+
+        impl<'value> super::FromFastcallValue<'value> for $value_type {
+            #[inline(always)]
+            fn from_value(value: &'value Self::Value) -> Self {
+                *value
+            }
+        }
     };
 }
 
@@ -69,6 +89,23 @@ fastcall_argument!(u32, Uint32);
 fastcall_argument!(f32, Float32);
 fastcall_argument!(f64, Float64);
 
+impl<'a> FastcallArgument for OneByteString<'a> {
+    type Value = *const v8::fast_api::FastApiOneByteString;
+
+    const V8_TYPE: v8::fast_api::Type = v8::fast_api::Type::SeqOneByteString;
+}
+
+impl<'value> FromFastcallValue<'value> for OneByteString<'value> {
+    #[inline(always)]
+    fn from_value(value: &'value Self::Value) -> Self {
+        // SAFETY: V8 passes a pointer to the data of a sequential one-byte string, that is
+        //         valid for the duration of the fast call. The pointer is borrowed from the frame
+        //         of the fast call, so the string can't outlive the call.
+        let string = unsafe { &**value };
+        OneByteString(Cow::Borrowed(string.as_bytes()))
+    }
+}
+
 macro_rules! fastcall_return_value {
     ($value_type:ty, $c_type:ident) => {
         impl super::FastcallReturnValue for $value_type {
@@ -98,6 +135,8 @@ fastcall_sealed!(u32);
 fastcall_sealed!(f32);
 fastcall_sealed!(f64);
 
+impl<'a> private::Sealed for OneByteString<'a> {}
+
 pub(crate) mod private {
     pub trait Sealed {}
 }
@@ -31,9 +31,11 @@ mod integer;
 mod map;
 mod message;
 mod name;
+mod name_cache;
 mod number;
 mod number_object;
 mod object;
+mod persistent;
 mod primitive;
 mod promise;
 mod promise_resolver;
@@ -53,8 +55,21 @@ mod uint8_array;
 mod uint8_clamped_array;
 mod wasm_memory_object;
 mod wasm_module_object;
+mod weak_ref;
+
+use std::{
+    cell::{Ref, RefMut},
+    collections::HashMap,
+    num::NonZeroI32,
+};
+
+use crate::{
+    error::{ErrorCode, TypeError},
+    runtime::RuntimeData,
+};
 
 pub(crate) use string::new_string;
+pub(crate) use weak_ref::WeakRefIntrinsics;
 
 pub use self::{
     array::Array,
@@ -81,16 +96,18 @@ pub use self::{
     map::Map,
     message::Message,
     name::Name,
+    name_cache::NameCache,
     number::Number,
     number_object::NumberObject,
-    object::Object,
+    object::{GetPropertyNamesArgs, IntegrityLevel, Object, PropertyAttribute, PropertyFilter},
+    persistent::{PersistentValue, WeakValue},
     primitive::Primitive,
     promise::{Promise, PromiseState},
     promise_resolver::PromiseResolver,
     proxy::Proxy,
     regexp::RegExp,
     set::Set,
-    stack_trace::{StackFrame, StackTrace},
+    stack_trace::{OwnedStackFrame, StackFrame, StackTrace},
     string::{NewStringType, String},
     string_object::StringObject,
     symbol::Symbol,
@@ -103,8 +120,31 @@ pub use self::{
     uint8_clamped_array::Uint8ClampedArray,
     wasm_memory_object::WasmMemoryObject,
     wasm_module_object::WasmModuleObject,
+    weak_ref::WeakRef,
 };
 
+pub mod prelude {
+    //! Re-exports the value types, traits and errors that are commonly used to implement
+    //! bindings.
+    //!
+    //! [`String`](super::String) and [`Error`](super::Error) are not re-exported, since they
+    //! would shadow the types of the standard library.
+    //!
+    //! ```rust
+    //! use kopi::value::prelude::*;
+    //! ```
+
+    pub use super::{
+        Array, ArrayBuffer, BigInt, Boolean, Date, Function, Integer, Map, Name, NewStringType,
+        Number, Object, PersistentValue, Primitive, Promise, PromiseState, Set, TypedArray, Value,
+        ValueScope,
+    };
+    pub use crate::{
+        error::{create_type_error, ErrorCode, TypeError},
+        traits::{Deserialize, DeserializeOwned, Serialize},
+    };
+}
+
 // TODO test the methods if they function as expected.
 
 /// Trait for sealing private types. `T` is the public type into which the private type is sealed.
@@ -145,6 +185,136 @@ impl<'borrow, 'scope> Unseal<&'borrow mut v8::HandleScope<'scope>>
     }
 }
 
+impl<'scope> ValueScope<'scope> {
+    /// Returns the private data of an extension, that was stored with
+    /// [`crate::Runtime::set_extension_data()`].
+    ///
+    /// Returns `None` if no data of the type `T` is stored under the key, or if the data is
+    /// currently borrowed mutably.
+    pub fn extension_data<T>(&self, key: &str) -> Option<Ref<'_, T>>
+    where
+        T: 'static,
+    {
+        let runtime_data = self.runtime_data()?;
+        let extension_data = runtime_data.extension_data.try_borrow().ok()?;
+        Ref::filter_map(extension_data, |data| data.get(key)?.downcast_ref::<T>()).ok()
+    }
+
+    /// Returns the mutable private data of an extension, that was stored with
+    /// [`crate::Runtime::set_extension_data()`].
+    ///
+    /// Returns `None` if no data of the type `T` is stored under the key, or if the data is
+    /// currently borrowed.
+    pub fn extension_data_mut<T>(&mut self, key: &str) -> Option<RefMut<'_, T>>
+    where
+        T: 'static,
+    {
+        let runtime_data = self.runtime_data()?;
+        let extension_data = runtime_data.extension_data.try_borrow_mut().ok()?;
+        RefMut::filter_map(extension_data, |data| {
+            data.get_mut(key)?.downcast_mut::<T>()
+        })
+        .ok()
+    }
+
+    /// Returns the data of the runtime, if the scope belongs to a runtime.
+    pub(crate) fn runtime_data(&self) -> Option<&RuntimeData> {
+        RuntimeData::from_isolate(&self.0)
+    }
+
+    /// Throws the given value as an exception inside the engine.
+    ///
+    /// The exception is raised once the host function returns to the script. Callers should
+    /// return without setting a result afterwards.
+    #[inline(always)]
+    pub fn throw(&mut self, exception: Value<'scope>) {
+        self.0.throw_exception(exception.unseal());
+    }
+
+    /// Throws a new `TypeError` with the given message inside the engine.
+    #[inline(always)]
+    pub fn throw_type_error<S>(&mut self, message: S)
+    where
+        S: AsRef<str>,
+    {
+        let message = String::new(self, message, NewStringType::Normal);
+        let exception = Error::new_type_error(self, message);
+        self.throw(exception);
+    }
+
+    /// Throws a new `RangeError` with the given message inside the engine.
+    #[inline(always)]
+    pub fn throw_range_error<S>(&mut self, message: S)
+    where
+        S: AsRef<str>,
+    {
+        let message = String::new(self, message, NewStringType::Normal);
+        let exception = Error::new_range_error(self, message);
+        self.throw(exception);
+    }
+
+    /// Runs the given function and catches the exceptions, that are thrown while it runs.
+    ///
+    /// Useful for operations that can run script code, like property getters of proxies or a
+    /// custom `toString()`, so that the exception can be handled locally instead of aborting
+    /// the outer host call. Returns the caught exception, if the function threw one.
+    ///
+    /// A termination of the execution can't be caught and is propagated to the outer scope.
+    pub fn try_catch<R, F>(&mut self, f: F) -> Result<R, CaughtException<'scope>>
+    where
+        F: FnOnce(&mut ValueScope<'scope>) -> R,
+    {
+        let try_catch_scope = &mut v8::TryCatch::new(&mut self.0);
+        let result = f(try_catch_scope.seal());
+
+        if try_catch_scope.has_terminated() {
+            try_catch_scope.rethrow();
+        }
+
+        let Some(exception) = try_catch_scope.exception() else {
+            return Ok(result);
+        };
+
+        let message = match try_catch_scope.message() {
+            Some(message) => message
+                .get(try_catch_scope)
+                .to_rust_string_lossy(try_catch_scope),
+            None => "Uncaught exception".to_string(),
+        };
+
+        Err(CaughtException {
+            exception: exception.seal(),
+            message,
+        })
+    }
+}
+
+/// An exception that was caught by [`ValueScope::try_catch()`].
+pub struct CaughtException<'scope> {
+    exception: Value<'scope>,
+    message: std::string::String,
+}
+
+impl<'scope> CaughtException<'scope> {
+    /// Returns the thrown value.
+    #[inline(always)]
+    pub fn exception(&self) -> Value<'scope> {
+        self.exception
+    }
+
+    /// Returns the message of the exception (e.g. `Uncaught Error: boom`).
+    #[inline(always)]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl<'scope> std::fmt::Display for CaughtException<'scope> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 /// The superclass of all types.
 #[derive(Copy, Clone)]
 #[repr(transparent)]
@@ -194,16 +364,336 @@ impl<'scope> Value<'scope> {
     pub fn to_boolean_representation(&self, scope: &mut ValueScope<'scope>) -> bool {
         self.0.boolean_value(scope.unseal())
     }
+
+    /// Creates a deep copy of the value using the structured clone algorithm.
+    ///
+    /// Primitives are returned as they are. Returns an error for values that can't be cloned,
+    /// like functions or symbols.
+    pub fn deep_clone(&self, scope: &mut ValueScope<'scope>) -> Result<Value<'scope>, TypeError> {
+        if !self.0.is_object() {
+            return Ok(*self);
+        }
+
+        let data = self.to_structured_bytes(scope)?;
+        Value::from_structured_bytes(scope, &data)
+    }
+
+    /// Serializes the value into bytes using the structured clone algorithm.
+    ///
+    /// Supports primitives, plain objects, arrays, Maps, Sets, Dates, RegExps, array buffers and
+    /// their views. Returns an error for values that can't be cloned, like functions or symbols.
+    /// The bytes can be deserialized with [`Value::from_structured_bytes()`] inside any runtime
+    /// of the same engine version.
+    pub fn to_structured_bytes(
+        &self,
+        scope: &mut ValueScope<'scope>,
+    ) -> Result<Vec<u8>, TypeError> {
+        let scope = scope.unseal();
+        let context = scope.get_current_context();
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let serializer = v8::ValueSerializer::new(try_catch_scope, Box::new(CloneSerializer));
+        serializer.write_header();
+        match serializer.write_value(context, self.0) {
+            Some(true) => Ok(serializer.release()),
+            _ => Err(clone_error(try_catch_scope)),
+        }
+    }
+
+    /// Deserializes a value from bytes, that were created with [`Value::to_structured_bytes()`].
+    pub fn from_structured_bytes(
+        scope: &mut ValueScope<'scope>,
+        data: &[u8],
+    ) -> Result<Value<'scope>, TypeError> {
+        let scope = scope.unseal();
+        let context = scope.get_current_context();
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+
+        let deserializer =
+            v8::ValueDeserializer::new(try_catch_scope, Box::new(CloneDeserializer), data);
+        if deserializer.read_header(context) != Some(true) {
+            return Err(clone_error(try_catch_scope));
+        }
+        match deserializer.read_value(context) {
+            Some(value) => Ok(value.seal()),
+            None => Err(clone_error(try_catch_scope)),
+        }
+    }
+
+    /// Freezes the value and all objects that are reachable through its own enumerable
+    /// properties.
+    ///
+    /// Primitives are left as they are. The content of typed arrays and array buffers can't be
+    /// frozen and stays mutable. Returns `false` if an object couldn't be frozen.
+    pub fn deep_freeze(&self, scope: &mut ValueScope<'scope>) -> bool {
+        let try_catch_scope = &mut v8::TryCatch::new(scope.unseal());
+
+        let mut visited: HashMap<NonZeroI32, Vec<v8::Local<v8::Object>>> = HashMap::new();
+        let mut pending = vec![self.0];
+
+        while let Some(value) = pending.pop() {
+            let Ok(object) = v8::Local::<v8::Object>::try_from(value) else {
+                continue;
+            };
+
+            // Objects can reference each other in cycles.
+            let objects = visited.entry(object.get_identity_hash()).or_default();
+            if objects.iter().any(|o| o.strict_equals(object.into())) {
+                continue;
+            }
+            objects.push(object);
+
+            if !value.is_array_buffer_view()
+                && object.set_integrity_level(try_catch_scope, v8::IntegrityLevel::Frozen)
+                    != Some(true)
+            {
+                return false;
+            }
+
+            let Some(names) =
+                object.get_own_property_names(try_catch_scope, v8::GetPropertyNamesArgs::default())
+            else {
+                return false;
+            };
+
+            for index in 0..names.length() {
+                let Some(name) = names.get_index(try_catch_scope, index) else {
+                    return false;
+                };
+                let Some(property) = object.get(try_catch_scope, name) else {
+                    return false;
+                };
+                pending.push(property);
+            }
+        }
+
+        true
+    }
+
+    /// Converts the value into a JSON string for logs and diagnostics. Never fails.
+    ///
+    /// Cyclic references are replaced with `"[Circular]"`, functions with `"[Function name]"`
+    /// and objects nested deeper than `max_depth` with `"[Object]"` or `"[Array]"`. Values that
+    /// have no JSON representation, like `undefined` or `NaN`, are written as `null`.
+    ///
+    /// Only the first 100 elements of an array and the first 100 properties of an object are
+    /// written. The rest is summarized as `"[... N more]"`, which is written as the last element
+    /// of an array or as the value of the property `"..."` of an object.
+    pub fn to_json_lossy(
+        &self,
+        scope: &mut ValueScope<'scope>,
+        max_depth: usize,
+    ) -> std::string::String {
+        let try_catch_scope = &mut v8::TryCatch::new(scope.unseal());
+        let mut json = std::string::String::new();
+        write_json_lossy(
+            try_catch_scope,
+            self.0,
+            max_depth,
+            &mut Vec::new(),
+            &mut json,
+        );
+        json
+    }
+}
+
+/// The maximal number of elements and properties [`Value::to_json_lossy()`] writes per array
+/// or object.
+const MAX_JSON_LOSSY_ENTRIES: u32 = 100;
+
+/// Writes the value as JSON. `ancestors` contains the objects that are currently visited.
+fn write_json_lossy<'scope>(
+    scope: &mut v8::HandleScope<'scope>,
+    value: v8::Local<'scope, v8::Value>,
+    max_depth: usize,
+    ancestors: &mut Vec<v8::Local<'scope, v8::Object>>,
+    json: &mut std::string::String,
+) {
+    use std::fmt::Write;
+
+    if value.is_null_or_undefined() {
+        json.push_str("null");
+    } else if value.is_boolean() {
+        json.push_str(if value.is_true() { "true" } else { "false" });
+    } else if value.is_int32() {
+        let _ = write!(json, "{}", value.int32_value(scope).unwrap_or_default());
+    } else if value.is_number() || value.is_number_object() {
+        match value.number_value(scope) {
+            Some(number) if number.is_finite() => {
+                let _ = write!(json, "{}", number);
+            }
+            _ => json.push_str("null"),
+        }
+    } else if value.is_string() || value.is_string_object() {
+        write_json_string(json, &value.to_rust_string_lossy(scope));
+    } else if value.is_big_int() || value.is_big_int_object() {
+        write_json_string(json, &format!("{}n", value.to_rust_string_lossy(scope)));
+    } else if let Ok(symbol) = v8::Local::<v8::Symbol>::try_from(value) {
+        let description = symbol.description(scope);
+        match description.is_undefined() {
+            true => write_json_string(json, "[Symbol]"),
+            false => write_json_string(
+                json,
+                &format!("[Symbol {}]", description.to_rust_string_lossy(scope)),
+            ),
+        }
+    } else if let Ok(function) = v8::Local::<v8::Function>::try_from(value) {
+        let name = function.get_name(scope).to_rust_string_lossy(scope);
+        match name.is_empty() {
+            true => write_json_string(json, "[Function (anonymous)]"),
+            false => write_json_string(json, &format!("[Function {}]", name)),
+        }
+    } else if value.is_date() || value.is_native_error() || value.is_reg_exp() {
+        write_json_string(json, &value.to_rust_string_lossy(scope));
+    } else if let Ok(object) = v8::Local::<v8::Object>::try_from(value) {
+        if ancestors.iter().any(|o| o.strict_equals(value)) {
+            write_json_string(json, "[Circular]");
+            return;
+        }
+
+        if let Ok(array) = v8::Local::<v8::Array>::try_from(value) {
+            if ancestors.len() >= max_depth {
+                write_json_string(json, "[Array]");
+                return;
+            }
+
+            ancestors.push(object);
+            json.push('[');
+            let length = array.length();
+            for index in 0..length.min(MAX_JSON_LOSSY_ENTRIES) {
+                if index != 0 {
+                    json.push(',');
+                }
+                match array.get_index(scope, index) {
+                    Some(element) => write_json_lossy(scope, element, max_depth, ancestors, json),
+                    None => json.push_str("null"),
+                }
+            }
+            if length > MAX_JSON_LOSSY_ENTRIES {
+                json.push(',');
+                write_json_remaining(json, length - MAX_JSON_LOSSY_ENTRIES);
+            }
+            json.push(']');
+            ancestors.pop();
+        } else {
+            if ancestors.len() >= max_depth {
+                write_json_string(json, "[Object]");
+                return;
+            }
+
+            let Some(names) =
+                object.get_own_property_names(scope, v8::GetPropertyNamesArgs::default())
+            else {
+                write_json_string(json, "[Object]");
+                return;
+            };
+
+            ancestors.push(object);
+            json.push('{');
+            let mut first = true;
+            let length = names.length();
+            for index in 0..length.min(MAX_JSON_LOSSY_ENTRIES) {
+                let Some(name) = names.get_index(scope, index) else {
+                    continue;
+                };
+                if !first {
+                    json.push(',');
+                }
+                first = false;
+                write_json_string(json, &name.to_rust_string_lossy(scope));
+                json.push(':');
+                match object.get(scope, name) {
+                    Some(property) => write_json_lossy(scope, property, max_depth, ancestors, json),
+                    None => json.push_str("null"),
+                }
+            }
+            if length > MAX_JSON_LOSSY_ENTRIES {
+                if !first {
+                    json.push(',');
+                }
+                json.push_str("\"...\":");
+                write_json_remaining(json, length - MAX_JSON_LOSSY_ENTRIES);
+            }
+            json.push('}');
+            ancestors.pop();
+        }
+    } else {
+        write_json_string(json, &value.to_rust_string_lossy(scope));
+    }
 }
 
+/// Writes the number of the elements or properties that were left out as a JSON string.
+fn write_json_remaining(json: &mut std::string::String, remaining: u32) {
+    write_json_string(json, &format!("[... {} more]", remaining));
+}
+
+/// Writes the text as an escaped JSON string.
+fn write_json_string(json: &mut std::string::String, text: &str) {
+    use std::fmt::Write;
+
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Creates a type error from the exception that was thrown while cloning a value.
+fn clone_error(try_catch_scope: &mut v8::TryCatch<v8::HandleScope>) -> TypeError {
+    let msg = match try_catch_scope.exception() {
+        Some(exception) => exception.to_rust_string_lossy(try_catch_scope),
+        None => "Value can't be cloned".to_string(),
+    };
+    TypeError {
+        code: ErrorCode::Conversion,
+        msg,
+    }
+}
+
+/// Serializer of the structured clone algorithm used by [`Value::to_structured_bytes()`].
+struct CloneSerializer;
+
+impl v8::ValueSerializerImpl for CloneSerializer {
+    fn throw_data_clone_error<'s>(
+        &mut self,
+        scope: &mut v8::HandleScope<'s>,
+        message: v8::Local<'s, v8::String>,
+    ) {
+        let error = v8::Exception::type_error(scope, message);
+        scope.throw_exception(error);
+    }
+}
+
+/// Deserializer of the structured clone algorithm used by [`Value::from_structured_bytes()`].
+struct CloneDeserializer;
+
+impl v8::ValueDeserializerImpl for CloneDeserializer {}
+
 #[cfg(test)]
 pub(crate) mod test {
-    use super::{new_string, NewStringType, Seal, Value, ValueScope};
+    use super::{new_string, NewStringType, Object, Seal, Unseal, Value, ValueScope};
     use crate::{error::create_error_from_exception, initialize_with_defaults};
 
     pub(crate) fn test_value<F>(source: &str, test: F)
     where
         F: for<'scope> FnOnce(Value<'scope>),
+    {
+        test_value_with_scope(source, |_, value| test(value))
+    }
+
+    pub(crate) fn test_value_with_scope<F>(source: &str, test: F)
+    where
+        F: for<'borrow, 'scope> FnOnce(&'borrow mut ValueScope<'scope>, Value<'scope>),
     {
         initialize_with_defaults();
 
@@ -212,6 +702,7 @@ pub(crate) mod test {
         let global_template = v8::ObjectTemplate::new(isolate_scope);
         let global_context = v8::Context::new_from_template(isolate_scope, global_template);
         let global_context_scope = &mut v8::ContextScope::new(isolate_scope, global_context);
+        WeakRefIntrinsics::capture(global_context_scope);
 
         let source = new_string(global_context_scope, source, NewStringType::Normal);
 
@@ -229,7 +720,7 @@ pub(crate) mod test {
             panic!("Can't run script: {}", err);
         };
 
-        test(v8_value.seal())
+        test(try_catch_scope.seal(), v8_value.seal())
     }
 
     #[macro_export]
@@ -271,4 +762,122 @@ pub(crate) mod test {
         // Make sure that we don't have a ZST.
         assert_ne!(std::mem::size_of::<Value>(), 0);
     }
+
+    #[test]
+    fn throw_exception() {
+        test_value_with_scope("undefined", |scope, _| {
+            let try_catch_scope = &mut v8::TryCatch::new(scope.unseal());
+            try_catch_scope.seal().throw_range_error("out of range");
+            assert!(try_catch_scope.has_caught());
+
+            let exception = try_catch_scope
+                .exception()
+                .expect("No exception was thrown");
+            let message = exception
+                .to_string(try_catch_scope)
+                .expect("Can't convert exception")
+                .to_rust_string_lossy(try_catch_scope);
+            assert_eq!(message, "RangeError: out of range");
+        });
+    }
+
+    #[test]
+    fn try_catch() {
+        test_value_with_scope(
+            "({ a: 1, get b() { throw new Error('boom'); } })",
+            |scope, value| {
+                let object = Object::try_from(value).expect("Not an object");
+
+                let key = super::String::new(scope, "a", NewStringType::Normal);
+                let ret = scope.try_catch(|scope| object.get(scope, key.into()));
+                assert!(matches!(ret, Ok(Some(_))));
+
+                let key = super::String::new(scope, "b", NewStringType::Normal);
+                let Err(caught) = scope.try_catch(|scope| object.get(scope, key.into())) else {
+                    panic!("Exception was not caught");
+                };
+                assert_eq!(caught.message(), "Uncaught Error: boom");
+                assert!(Object::try_from(caught.exception()).is_ok());
+            },
+        );
+    }
+
+    #[test]
+    fn deep_clone() {
+        test_value_with_scope("({ a: 1, b: { c: [1, 2, 3] } })", |scope, value| {
+            let clone = value.deep_clone(scope).expect("Can't clone value");
+            assert!(!clone.unseal().strict_equals(value.unseal()));
+
+            fn source<'scope>(scope: &mut ValueScope<'scope>, value: Value<'scope>) -> String {
+                v8::json::stringify(scope.unseal(), value.unseal())
+                    .expect("Can't stringify value")
+                    .to_rust_string_lossy(scope.unseal())
+            }
+            assert_eq!(source(scope, value), source(scope, clone));
+        });
+    }
+
+    #[test]
+    fn to_json_lossy() {
+        test_value_with_scope(
+            "var o = { a: 1, b: 'x\"y', c: [true, null, undefined, NaN, 1.5], f: function f() {} }; \
+            o.self = o; o.d = { e: { g: 1 } }; o",
+            |scope, value| {
+                assert_eq!(
+                    value.to_json_lossy(scope, 2),
+                    r#"{"a":1,"b":"x\"y","c":[true,null,null,null,1.5],"f":"[Function f]","self":"[Circular]","d":{"e":"[Object]"}}"#
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn to_json_lossy_truncated() {
+        test_value_with_scope(
+            "[Array.from({ length: 102 }, (_, i) => i), \
+            Object.fromEntries(Array.from({ length: 101 }, (_, i) => ['k' + i, i]))]",
+            |scope, value| {
+                let json = value.to_json_lossy(scope, 2);
+                let (array, object) = json.split_once("],{").expect("Unexpected JSON");
+                assert!(array.starts_with("[[0,1,2,"));
+                assert!(array.ends_with(",98,99,\"[... 2 more]\""));
+                assert!(object.starts_with("\"k0\":0,"));
+                assert!(object.ends_with(",\"k99\":99,\"...\":\"[... 1 more]\"}]"));
+            },
+        );
+    }
+
+    #[test]
+    fn deep_clone_function() {
+        test_value_with_scope("(function() {})", |scope, value| {
+            assert!(value.deep_clone(scope).is_err());
+        });
+    }
+
+    #[test]
+    fn deep_freeze() {
+        test_value_with_scope(
+            "var o = { a: 1, b: { c: [1, 2, 3] } }; o.b.self = o; o",
+            |scope, value| {
+                assert!(value.deep_freeze(scope));
+
+                let object = value
+                    .unseal()
+                    .to_object(scope.unseal())
+                    .expect("Not an object");
+                let key = new_string(scope.unseal(), "b", NewStringType::Normal);
+                let inner = object
+                    .get(scope.unseal(), key.into())
+                    .expect("Can't get property")
+                    .to_object(scope.unseal())
+                    .expect("Not an object");
+                let key = new_string(scope.unseal(), "d", NewStringType::Normal);
+                let value = v8::Integer::new(scope.unseal(), 42);
+                inner.set(scope.unseal(), key.into(), value.into());
+                assert!(!inner
+                    .has(scope.unseal(), key.into())
+                    .expect("Can't check property"));
+            },
+        );
+    }
 }
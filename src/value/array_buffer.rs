@@ -61,6 +61,44 @@ impl<'scope> ArrayBuffer<'scope> {
         v8::ArrayBuffer::with_backing_store(scope.unseal(), &store.into()).seal()
     }
 
+    /// Creates a new [`ArrayBuffer`] that maps the content of the file into memory, so that
+    /// scripts can access large files (e.g. game assets) without copying them.
+    ///
+    /// The file is mapped copy-on-write: the engine has no read-only array buffers, so scripts
+    /// can write into the buffer, but the writes only modify a private copy of the touched pages
+    /// and never reach the file. The mapping is owned by the backing store of the array buffer
+    /// and is released once the engine has garbage collected the array buffer or the runtime is
+    /// dropped.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated by this or any other process while it's
+    /// mapped. Modifications change the content of the buffer under existing slices of it and
+    /// accessing pages of a truncated file terminates the process with `SIGBUS`.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub unsafe fn new_from_file(
+        scope: &mut ValueScope<'scope>,
+        file: &std::fs::File,
+    ) -> std::io::Result<ArrayBuffer<'scope>> {
+        // SAFETY: The caller guarantees that the file is not modified while it's mapped. The
+        //         mapping is private, so writes to the mapping never reach the file.
+        let mut mapping = Box::new(memmap2::MmapOptions::new().map_copy(file)?);
+        let data = mapping.as_mut_ptr() as *mut std::ffi::c_void;
+        let length = mapping.len();
+
+        // SAFETY: The memory of the mapping is valid until the deleter drops the mapping, which
+        //         the engine calls once the backing store is released.
+        let store = v8::ArrayBuffer::new_backing_store_from_ptr(
+            data,
+            length,
+            drop_mapping,
+            Box::into_raw(mapping) as *mut std::ffi::c_void,
+        );
+
+        Ok(v8::ArrayBuffer::with_backing_store(scope.unseal(), &store.into()).seal())
+    }
+
     /// Returns length of the array in bytes.
     #[inline(always)]
     pub fn len(&self) -> usize {
@@ -87,3 +125,46 @@ impl<'scope> AsMut<[u8]> for ArrayBuffer<'scope> {
         unsafe { std::slice::from_raw_parts_mut(self.0.data() as *mut u8, self.0.byte_length()) }
     }
 }
+
+/// Releases the memory mapping of a backing store.
+#[cfg(feature = "mmap")]
+unsafe extern "C" fn drop_mapping(
+    _data: *mut std::ffi::c_void,
+    _byte_length: usize,
+    deleter_data: *mut std::ffi::c_void,
+) {
+    // SAFETY: The deleter data is the boxed mapping, that was leaked when the backing store
+    //         was created.
+    drop(Box::from_raw(deleter_data as *mut memmap2::MmapMut));
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod test {
+    use std::io::Write;
+
+    use crate::value::{test::test_value_with_scope, ArrayBuffer};
+
+    #[test]
+    fn new_from_file() {
+        let path = std::env::temp_dir().join("kopi_array_buffer_new_from_file.bin");
+        let mut file = std::fs::File::create(&path).expect("Can't create file");
+        file.write_all(&[1, 2, 3, 4]).expect("Can't write file");
+        drop(file);
+
+        test_value_with_scope("undefined", |scope, _| {
+            let file = std::fs::File::open(&path).expect("Can't open file");
+            // SAFETY: The file is not modified while it's mapped.
+            let mut buffer =
+                unsafe { ArrayBuffer::new_from_file(scope, &file) }.expect("Can't map file");
+            assert_eq!(buffer.as_ref(), &[1, 2, 3, 4]);
+
+            buffer.as_mut()[0] = 5;
+            assert_eq!(buffer.as_ref(), &[5, 2, 3, 4]);
+        });
+
+        let content = std::fs::read(&path).expect("Can't read file");
+        assert_eq!(content, &[1, 2, 3, 4]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Object, Seal, Unseal, Value};
+use super::{ArrayBuffer, Object, Seal, Unseal, Value, ValueScope};
 
 /// A super class for "views" on top of array buffers.
 ///
@@ -44,3 +44,57 @@ impl<'scope> From<ArrayBufferView<'scope>> for Object<'scope> {
         Object(value.0.into())
     }
 }
+
+impl<'scope> ArrayBufferView<'scope> {
+    /// Returns the offset of the view into the underlying array buffer in bytes.
+    #[inline(always)]
+    pub fn byte_offset(&self) -> usize {
+        self.0.byte_offset()
+    }
+
+    /// Returns the length of the view in bytes.
+    #[inline(always)]
+    pub fn byte_length(&self) -> usize {
+        self.0.byte_length()
+    }
+
+    /// Returns the underlying array buffer of the view.
+    #[inline(always)]
+    pub fn buffer(&self, scope: &mut ValueScope<'scope>) -> Option<ArrayBuffer<'scope>> {
+        self.0.buffer(scope.unseal()).map(|b| b.seal())
+    }
+
+    /// Copy the contents of the [`ArrayBufferView`] without the overhead of getting the
+    /// underlying array buffer.
+    ///
+    /// Returns the number of bytes actually written.
+    #[inline(always)]
+    pub fn copy_bytes(&self, dest: &mut [u8]) -> usize {
+        self.0.copy_contents(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArrayBufferView;
+    use crate::value::test::test_value_with_scope;
+
+    #[test]
+    fn accessors() {
+        test_value_with_scope(
+            "new Uint16Array(new Uint8Array([0, 1, 2, 3, 4, 5, 6, 7]).buffer, 2, 2)",
+            |scope, value| {
+                let view = ArrayBufferView::try_from(value).expect("Not an ArrayBufferView");
+                assert_eq!(view.byte_offset(), 2);
+                assert_eq!(view.byte_length(), 4);
+
+                let buffer = view.buffer(scope).expect("View has no array buffer");
+                assert_eq!(buffer.len(), 8);
+
+                let mut data = [0u8; 8];
+                assert_eq!(view.copy_bytes(&mut data), 4);
+                assert_eq!(&data[..4], &[2, 3, 4, 5]);
+            },
+        );
+    }
+}
@@ -201,10 +201,39 @@ impl<'scope> Float32Array<'scope> {
 
         self.0.copy_contents(byte_slice)
     }
+
+    /// Copies the given data into the [`Float32Array`] with a single memcpy.
+    ///
+    /// Copies at most as many elements as the [`Float32Array`] can hold. Returns the number of
+    /// **bytes** actually written.
+    #[inline(always)]
+    pub fn copy_from(&mut self, scope: &mut ValueScope<'scope>, src: &[f32]) -> usize {
+        let dest = self.as_mut(scope);
+        let length = usize::min(dest.len(), src.len());
+        dest[..length].copy_from_slice(&src[..length]);
+
+        length * std::mem::size_of::<f32>()
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::Float32Array;
+    use crate::value::test::test_value_with_scope;
+
+    #[test]
+    fn copy_from() {
+        test_value_with_scope(
+            "new Float32Array(new ArrayBuffer(16), 4, 2)",
+            |scope, value| {
+                let mut array = Float32Array::try_from(value).expect("Not a Float32Array");
+
+                let written = array.copy_from(scope, &[1.0, 2.0, 3.0]);
+                assert_eq!(written, 2 * std::mem::size_of::<f32>());
+                assert_eq!(array.as_ref(scope), &[1.0, 2.0]);
+            },
+        );
+    }
 
     #[test]
     fn u8_f32_compatibility() {
@@ -201,6 +201,19 @@ impl<'scope> Float64Array<'scope> {
 
         self.0.copy_contents(byte_slice)
     }
+
+    /// Copies the given data into the [`Float64Array`] with a single memcpy.
+    ///
+    /// Copies at most as many elements as the [`Float64Array`] can hold. Returns the number of
+    /// **bytes** actually written.
+    #[inline(always)]
+    pub fn copy_from(&mut self, scope: &mut ValueScope<'scope>, src: &[f64]) -> usize {
+        let dest = self.as_mut(scope);
+        let length = usize::min(dest.len(), src.len());
+        dest[..length].copy_from_slice(&src[..length]);
+
+        length * std::mem::size_of::<f64>()
+    }
 }
 
 #[cfg(test)]
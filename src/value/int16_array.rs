@@ -201,6 +201,19 @@ impl<'scope> Int16Array<'scope> {
 
         self.0.copy_contents(byte_slice)
     }
+
+    /// Copies the given data into the [`Int16Array`] with a single memcpy.
+    ///
+    /// Copies at most as many elements as the [`Int16Array`] can hold. Returns the number of
+    /// **bytes** actually written.
+    #[inline(always)]
+    pub fn copy_from(&mut self, scope: &mut ValueScope<'scope>, src: &[i16]) -> usize {
+        let dest = self.as_mut(scope);
+        let length = usize::min(dest.len(), src.len());
+        dest[..length].copy_from_slice(&src[..length]);
+
+        length * std::mem::size_of::<i16>()
+    }
 }
 
 #[cfg(test)]
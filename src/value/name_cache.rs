@@ -0,0 +1,59 @@
+use crate::runtime::RuntimeData;
+
+use super::{new_string, NewStringType, Seal, String, Unseal, ValueScope};
+
+/// Caches the internalized strings of frequently used property names per runtime.
+///
+/// Useful for custom [`crate::Serialize`] and [`crate::Deserialize`] implementations, that access
+/// the same properties over and over again. The string of a name is only created once and then
+/// re-used for the lifetime of the runtime.
+///
+/// Outside of a runtime, a new internalized string is created on each call.
+pub struct NameCache;
+
+impl NameCache {
+    /// Returns the internalized string of the given property name.
+    pub fn get<'scope>(scope: &mut ValueScope<'scope>, name: &'static str) -> String<'scope> {
+        let scope = scope.unseal();
+        let Some(runtime_data) = RuntimeData::from_isolate(scope) else {
+            return new_string(scope, name, NewStringType::Internalized).seal();
+        };
+
+        if let Some(global) = runtime_data.name_cache.borrow().get(name) {
+            return v8::Local::new(scope, global).seal();
+        }
+
+        let string = new_string(scope, name, NewStringType::Internalized);
+        runtime_data
+            .name_cache
+            .borrow_mut()
+            .insert(name, v8::Global::new(scope, string));
+
+        string.seal()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NameCache;
+    use crate::value::{test::test_value_with_scope, Number, Object, Unseal};
+
+    #[test]
+    fn get_and_set_cached() {
+        test_value_with_scope("({ x: 1 })", |scope, value| {
+            let object = Object::try_from(value).expect("Not an object");
+
+            let x = object.get_cached(scope, "x").expect("Can't get value");
+            assert_eq!(x.unseal().number_value(scope.unseal()), Some(1.0));
+
+            let y = Number::new(scope, 2.0);
+            assert!(object.set_cached(scope, "y", y.into()));
+            let y = object.get_cached(scope, "y").expect("Can't get value");
+            assert_eq!(y.unseal().number_value(scope.unseal()), Some(2.0));
+
+            let first = NameCache::get(scope, "x");
+            let second = NameCache::get(scope, "x");
+            assert!(first.unseal().strict_equals(second.unseal().into()));
+        });
+    }
+}
@@ -1,8 +1,12 @@
-use std::num::NonZeroI32;
+use std::{collections::HashMap, num::NonZeroI32};
 
-pub use v8::{GetPropertyNamesArgs, IntegrityLevel, PropertyAttribute};
+pub use v8::{GetPropertyNamesArgs, IntegrityLevel, PropertyAttribute, PropertyFilter};
 
-use super::{Array, Name, Seal, Unseal, Value, ValueScope};
+use super::{Array, Name, NameCache, Seal, Unseal, Value, ValueScope};
+use crate::{
+    error::{ErrorCode, TypeError},
+    traits::Deserialize,
+};
 
 /// An object.
 #[derive(Copy, Clone)]
@@ -170,6 +174,31 @@ impl<'scope> Object<'scope> {
         self.0.get(scope.unseal(), key.unseal()).map(|v| v.seal())
     }
 
+    /// Returns the value at the given property name if present. The string of the name is
+    /// cached inside the [`NameCache`].
+    #[inline(always)]
+    pub fn get_cached(
+        &self,
+        scope: &mut ValueScope<'scope>,
+        name: &'static str,
+    ) -> Option<Value<'scope>> {
+        let key = NameCache::get(scope, name);
+        self.get(scope, key.into())
+    }
+
+    /// Sets the value at the given property name. The string of the name is cached inside the
+    /// [`NameCache`].
+    #[inline(always)]
+    pub fn set_cached(
+        &self,
+        scope: &mut ValueScope<'scope>,
+        name: &'static str,
+        value: Value<'scope>,
+    ) -> bool {
+        let key = NameCache::get(scope, name);
+        self.set(scope, key.into(), value)
+    }
+
     /// Returns the value at the given index if present.
     #[inline(always)]
     pub fn get_index(&self, scope: &mut ValueScope<'scope>, index: u32) -> Option<Value<'scope>> {
@@ -302,4 +331,111 @@ impl<'scope> Object<'scope> {
     pub fn set_internal_field(&self, index: usize, value: Value<'scope>) -> bool {
         self.0.set_internal_field(index, value.unseal())
     }
+
+    /// Deserializes the own properties of the object, that pass the filter, into a map.
+    /// Properties with symbol keys are skipped and integer keys are converted to strings.
+    ///
+    /// The message of a conversion error contains the key of the property.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use kopi::value::{Object, PropertyFilter, ValueScope};
+    ///
+    /// fn scores<'scope>(
+    ///     scope: &mut ValueScope<'scope>,
+    ///     object: Object<'scope>,
+    /// ) -> HashMap<String, u32> {
+    ///     object
+    ///         .to_map(scope, PropertyFilter::ONLY_ENUMERABLE)
+    ///         .unwrap_or_default()
+    /// }
+    /// ```
+    pub fn to_map<T>(
+        &self,
+        scope: &mut ValueScope<'scope>,
+        filter: PropertyFilter,
+    ) -> Result<HashMap<String, T>, TypeError>
+    where
+        T: Deserialize<'scope>,
+    {
+        self.entries(scope, filter).map(HashMap::from_iter)
+    }
+
+    /// Deserializes the own properties of the object, that pass the filter, into key-value
+    /// pairs. Properties with symbol keys are skipped.
+    pub(crate) fn entries<T>(
+        &self,
+        scope: &mut ValueScope<'scope>,
+        filter: PropertyFilter,
+    ) -> Result<Vec<(String, T)>, TypeError>
+    where
+        T: Deserialize<'scope>,
+    {
+        let conversion_error = || TypeError {
+            code: ErrorCode::Conversion,
+            msg: "Can't read the properties of the object".to_string(),
+        };
+
+        let names = self
+            .own_property_names(
+                scope,
+                GetPropertyNamesArgs {
+                    property_filter: filter | PropertyFilter::SKIP_SYMBOLS,
+                    key_conversion: v8::KeyConversionMode::ConvertToString,
+                    ..Default::default()
+                },
+            )
+            .ok_or_else(conversion_error)?;
+
+        let mut entries = Vec::with_capacity(names.len() as usize);
+        for index in 0..names.len() {
+            let key = names.get(scope, index).ok_or_else(conversion_error)?;
+            let value = self.get(scope, key).ok_or_else(conversion_error)?;
+            let name = key.to_string_representation(scope);
+            let value = T::deserialize(scope, value).map_err(|err| TypeError {
+                code: err.code,
+                msg: format!("Property '{}': {}", name, err.msg),
+            })?;
+            entries.push((name, value));
+        }
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Object, PropertyFilter};
+    use crate::value::test::test_value_with_scope;
+
+    #[test]
+    fn to_map() {
+        test_value_with_scope(
+            "const o = { a: 1, 2: 2, [Symbol('s')]: 3 }; \
+            Object.defineProperty(o, 'hidden', { value: 4, enumerable: false }); o",
+            |scope, value| {
+                let object = Object::try_from(value).expect("Not an object");
+
+                let map = object
+                    .to_map::<i32>(scope, PropertyFilter::ONLY_ENUMERABLE)
+                    .expect("Can't convert object");
+                assert_eq!(map.len(), 2);
+                assert_eq!(map.get("a"), Some(&1));
+                assert_eq!(map.get("2"), Some(&2));
+
+                let map = object
+                    .to_map::<i32>(scope, PropertyFilter::ALL_PROPERTIES)
+                    .expect("Can't convert object");
+                assert_eq!(map.get("hidden"), Some(&4));
+
+                let err = object
+                    .to_map::<bool>(scope, PropertyFilter::ONLY_ENUMERABLE)
+                    .expect_err("Conversion didn't fail");
+                assert!(err.msg.starts_with("Property '"));
+            },
+        );
+    }
 }
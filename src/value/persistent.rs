@@ -0,0 +1,121 @@
+use super::{Seal, Unseal, Value, ValueScope};
+
+/// A handle to a value, that is not bound to a scope. The value is kept alive as long as the
+/// handle exists.
+///
+/// Use [`PersistentValue::downgrade()`] for caches, that shouldn't keep their values alive.
+#[derive(Clone)]
+pub struct PersistentValue(v8::Global<v8::Value>);
+
+impl PersistentValue {
+    /// Creates a new persistent handle to the value.
+    #[inline(always)]
+    pub fn new<'scope>(scope: &mut ValueScope<'scope>, value: Value<'scope>) -> PersistentValue {
+        Self(v8::Global::new(scope.unseal(), value.unseal()))
+    }
+
+    /// Returns the value inside the given scope.
+    #[inline(always)]
+    pub fn get<'scope>(&self, scope: &mut ValueScope<'scope>) -> Value<'scope> {
+        v8::Local::new(scope.unseal(), &self.0).seal()
+    }
+
+    /// Creates a weak handle to the value, that doesn't keep the value alive.
+    #[inline(always)]
+    pub fn downgrade(&self, scope: &mut ValueScope) -> WeakValue {
+        WeakValue(v8::Weak::new(scope.unseal(), &self.0))
+    }
+}
+
+/// A weak handle to a value, that is not bound to a scope. The value can be garbage collected,
+/// once no other references to it exist.
+///
+/// Created by [`PersistentValue::downgrade()`].
+#[derive(Clone)]
+pub struct WeakValue(v8::Weak<v8::Value>);
+
+impl WeakValue {
+    /// Creates a new weak handle to the value.
+    #[inline(always)]
+    pub fn new<'scope>(scope: &mut ValueScope<'scope>, value: Value<'scope>) -> WeakValue {
+        Self(v8::Weak::new(scope.unseal(), value.unseal()))
+    }
+
+    /// Returns `true` if the value was garbage collected.
+    #[inline(always)]
+    pub fn is_collected(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the value inside the given scope, or `None` if the value was garbage collected.
+    #[inline(always)]
+    pub fn get<'scope>(&self, scope: &mut ValueScope<'scope>) -> Option<Value<'scope>> {
+        self.0.to_local(scope.unseal()).map(|v| v.seal())
+    }
+
+    /// Creates a persistent handle to the value, or returns `None` if the value was garbage
+    /// collected.
+    #[inline(always)]
+    pub fn upgrade(&self, scope: &mut ValueScope) -> Option<PersistentValue> {
+        self.0.to_global(scope.unseal()).map(PersistentValue)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PersistentValue, WeakValue};
+    use crate::value::{test::test_value_with_scope, Object, Seal, Unseal, WeakRef};
+
+    #[test]
+    fn downgrade() {
+        test_value_with_scope("({ a: 1 })", |scope, value| {
+            let persistent = PersistentValue::new(scope, value);
+            let weak = persistent.downgrade(scope);
+            assert!(!weak.is_collected());
+
+            let upgraded = weak.upgrade(scope).expect("Value was collected");
+            assert!(upgraded.get(scope).unseal().strict_equals(value.unseal()));
+
+            let weak = {
+                let scope = &mut v8::HandleScope::new(scope.unseal());
+                let object = Object::new(scope.seal());
+                WeakValue::new(scope.seal(), object.into())
+            };
+            scope.unseal().low_memory_notification();
+            assert!(weak.is_collected());
+            assert!(weak.get(scope).is_none());
+        });
+    }
+
+    #[test]
+    fn weak_ref() {
+        test_value_with_scope("({ a: 1 })", |scope, value| {
+            let object = Object::try_from(value).expect("Not an object");
+            let weak_ref = WeakRef::new(scope, object).expect("Can't create WeakRef");
+
+            let target = weak_ref.deref(scope).expect("Target was collected");
+            assert!(target.unseal().strict_equals(value.unseal()));
+
+            assert!(WeakRef::from_value(scope, weak_ref.into()).is_some());
+            assert!(WeakRef::from_value(scope, value).is_none());
+        });
+    }
+
+    #[test]
+    fn weak_ref_replaced_constructor() {
+        let source = "const fake = Object.create(WeakRef.prototype); \
+                      globalThis.WeakRef = function () {}; \
+                      WeakRef.prototype.deref = () => 42; \
+                      fake";
+        test_value_with_scope(source, |scope, value| {
+            assert!(WeakRef::from_value(scope, value).is_none());
+
+            let object = Object::new(scope);
+            let weak_ref = WeakRef::new(scope, object).expect("Can't create WeakRef");
+            assert!(WeakRef::from_value(scope, weak_ref.into()).is_some());
+
+            let target = weak_ref.deref(scope).expect("Target was collected");
+            assert!(target.unseal().strict_equals(object.unseal().into()));
+        });
+    }
+}
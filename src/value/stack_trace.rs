@@ -54,6 +54,44 @@ impl<'scope> StackTrace<'scope> {
     ) -> Option<StackFrame<'scope>> {
         self.0.get_frame(scope.unseal(), index).map(|sf| sf.seal())
     }
+
+    /// Converts all frames of the stack trace into plain Rust data, that can be stored and
+    /// transmitted after the scope ended.
+    pub fn to_owned_frames(&self, scope: &mut ValueScope<'scope>) -> Vec<OwnedStackFrame> {
+        let mut frames = Vec::with_capacity(self.frame_count());
+        for index in 0..self.frame_count() {
+            if let Some(frame) = self.0.get_frame(scope.unseal(), index) {
+                frames.push(StackFrame(frame).to_owned_frame(scope));
+            }
+        }
+        frames
+    }
+}
+
+/// A stack frame as plain Rust data, that is not bound to a scope.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OwnedStackFrame {
+    /// The 1-based number of the line for the associated function call.
+    pub line_number: usize,
+    /// The 1-based column offset on the line for the associated function call.
+    pub column: usize,
+    /// The id of the script for the function for this stack frame.
+    pub script_id: usize,
+    /// The name of the resource that contains the script for the function for this stack frame.
+    pub script_name: Option<std::string::String>,
+    /// The name or url of the resource that contains the script for the function for this
+    /// stack frame.
+    pub script_name_or_source_url: Option<std::string::String>,
+    /// The name of the function associated with this stack frame.
+    pub function_name: Option<std::string::String>,
+    /// `true` when the associated function was compiled via a call to `eval()`.
+    pub is_eval: bool,
+    /// `true` when the associated function was called as a constructor via `new`.
+    pub is_constructor: bool,
+    /// `true` when the associated functions was defined in wasm.
+    pub is_wasm: bool,
+    /// `true` when the associated function was defined by the user.
+    pub is_user_javascript: bool,
 }
 
 /// A stack frame.
@@ -142,4 +180,23 @@ impl<'scope> StackFrame<'scope> {
     pub fn is_user_javascript(&self) -> bool {
         self.0.is_user_javascript()
     }
+
+    /// Converts the stack frame into plain Rust data, that can be stored and transmitted after
+    /// the scope ended.
+    pub fn to_owned_frame(&self, scope: &mut ValueScope<'scope>) -> OwnedStackFrame {
+        OwnedStackFrame {
+            line_number: self.line_number(),
+            column: self.column(),
+            script_id: self.script_id(),
+            script_name: self.script_name(scope).map(|s| s.value(scope)),
+            script_name_or_source_url: self
+                .script_name_or_source_url(scope)
+                .map(|s| s.value(scope)),
+            function_name: self.function_name(scope).map(|s| s.value(scope)),
+            is_eval: self.is_eval(),
+            is_constructor: self.is_constructor(),
+            is_wasm: self.is_wasm(),
+            is_user_javascript: self.is_user_javascript(),
+        }
+    }
 }
@@ -1,11 +1,12 @@
 pub use v8::NewStringType;
 
 use super::{Name, Primitive, Seal, Unseal, Value, ValueScope};
+use crate::error::StringTooLong;
 
 /// Maximal string length.
 /// As declared in "include/v8-primitive.h".
 #[cfg(target_pointer_width = "32")]
-static MAX_STRING_SIZE: usize = (1 << 28) - 16;
+static MAX_STRING_LENGTH: usize = (1 << 28) - 16;
 
 /// Maximal string length.
 /// As declared in "include/v8-primitive.h".
@@ -63,7 +64,12 @@ impl<'scope> From<String<'scope>> for Name<'scope> {
 }
 
 impl<'scope> String<'scope> {
-    /// Creates a new string. Will truncate string if they are too long.
+    /// Creates a new string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than the maximal string length. Use [`String::try_new()`]
+    /// to handle long strings or [`String::new_truncated()`] to truncate them.
     pub fn new<S>(
         scope: &mut ValueScope<'scope>,
         string: S,
@@ -72,20 +78,81 @@ impl<'scope> String<'scope> {
     where
         S: AsRef<str>,
     {
-        let data = string.as_ref().as_bytes();
-        let max_length = usize::min(MAX_STRING_LENGTH, data.len());
+        match Self::try_new(scope, string, string_type) {
+            Ok(string) => string,
+            Err(err) => panic!("{}", err),
+        }
+    }
 
-        v8::String::new_from_utf8(scope.unseal(), &data[..max_length], string_type)
+    /// Creates a new string. Returns an error if the string is longer than the maximal string
+    /// length.
+    ///
+    /// The length is checked in UTF-8 bytes, which is never smaller than the length in UTF-16
+    /// code units that the engine uses.
+    pub fn try_new<S>(
+        scope: &mut ValueScope<'scope>,
+        string: S,
+        string_type: NewStringType,
+    ) -> Result<String<'scope>, StringTooLong>
+    where
+        S: AsRef<str>,
+    {
+        let data = string.as_ref().as_bytes();
+        if data.len() > MAX_STRING_LENGTH {
+            return Err(StringTooLong {
+                len: data.len(),
+                max: MAX_STRING_LENGTH,
+            });
+        }
+
+        Ok(v8::String::new_from_utf8(scope.unseal(), data, string_type)
             .expect("String is too large for V8")
-            .seal()
+            .seal())
+    }
+
+    /// Creates a new string. Strings that are longer than the maximal string length are
+    /// truncated at the last character that fits.
+    pub fn new_truncated<S>(
+        scope: &mut ValueScope<'scope>,
+        string: S,
+        string_type: NewStringType,
+    ) -> String<'scope>
+    where
+        S: AsRef<str>,
+    {
+        let string = string.as_ref();
+        let mut max_length = usize::min(MAX_STRING_LENGTH, string.len());
+        while !string.is_char_boundary(max_length) {
+            max_length -= 1;
+        }
+
+        v8::String::new_from_utf8(
+            scope.unseal(),
+            &string.as_bytes()[..max_length],
+            string_type,
+        )
+        .expect("String is too large for V8")
+        .seal()
     }
 
-    /// Creates a new string from a static string. Will truncate string if they are too long.
+    /// Creates a new string from a static string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is longer than the maximal string length.
     pub fn new_from_static(scope: &mut ValueScope<'scope>, string: &'static str) -> String<'scope> {
         let data = string.as_bytes();
-        let max_length = usize::min(MAX_STRING_LENGTH, data.len());
-
-        v8::String::new_external_onebyte_static(scope.unseal(), &data[..max_length])
+        if data.len() > MAX_STRING_LENGTH {
+            panic!(
+                "{}",
+                StringTooLong {
+                    len: data.len(),
+                    max: MAX_STRING_LENGTH,
+                }
+            );
+        }
+
+        v8::String::new_external_onebyte_static(scope.unseal(), data)
             .expect("String is too large for V8")
             .seal()
     }
@@ -116,10 +183,23 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::value::string::MAX_STRING_LENGTH;
+    use crate::{
+        error::{ErrorCode, StringTooLong, TypeError},
+        value::string::MAX_STRING_LENGTH,
+    };
 
     #[test]
     fn verify_max_string_length() {
         assert_eq!(MAX_STRING_LENGTH, v8::String::max_length());
     }
+
+    #[test]
+    fn string_too_long() {
+        let err = TypeError::from(StringTooLong {
+            len: MAX_STRING_LENGTH + 1,
+            max: MAX_STRING_LENGTH,
+        });
+        assert_eq!(err.code, ErrorCode::ArgumentRange);
+        assert!(err.msg.contains(&MAX_STRING_LENGTH.to_string()));
+    }
 }
@@ -90,6 +90,29 @@ impl<'scope> Uint8Array<'scope> {
             .seal()
     }
 
+    /// Creates a new [`Uint8Array`] that maps the content of the file into memory. See
+    /// [`super::ArrayBuffer::new_from_file()`] for the details of the mapping.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it's mapped. See
+    /// [`super::ArrayBuffer::new_from_file()`].
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub unsafe fn new_from_file(
+        scope: &mut ValueScope<'scope>,
+        file: &std::fs::File,
+    ) -> std::io::Result<Uint8Array<'scope>> {
+        // SAFETY: The caller upholds the contract of the mapping.
+        let buffer = super::ArrayBuffer::new_from_file(scope, file)?;
+        let length = buffer.len();
+        Ok(
+            v8::Uint8Array::new(scope.unseal(), buffer.unseal(), 0, length)
+                .expect("Uint8Array could not be created")
+                .seal(),
+        )
+    }
+
     /// Returns the number of elements inside the uint8 array.
     #[inline(always)]
     pub fn len(&self) -> usize {
@@ -127,4 +150,27 @@ impl<'scope> Uint8Array<'scope> {
         // SAFETY: The API only allows to create array buffer with initialized data.
         unsafe { std::slice::from_raw_parts_mut(data as *mut u8, self.0.byte_length()) }
     }
+
+    /// Copies the given data into the [`Uint8Array`] with a single memcpy.
+    ///
+    /// Copies at most as many elements as the [`Uint8Array`] can hold. Returns the number of
+    /// **bytes** actually written.
+    #[inline(always)]
+    pub fn copy_from(&mut self, scope: &mut ValueScope<'scope>, src: &[u8]) -> usize {
+        let length = usize::min(self.0.byte_length(), src.len());
+
+        let data_ptr = self
+            .0
+            .buffer(scope.unseal())
+            .expect("Uint8Array has no backing array buffer")
+            .data()
+            .wrapping_add(self.0.byte_offset()) as *mut u8;
+
+        // SAFETY: The API only allows to create array buffer with initialized data and we
+        //         never write more bytes than the view contains.
+        let dest = unsafe { std::slice::from_raw_parts_mut(data_ptr, length) };
+        dest.copy_from_slice(&src[..length]);
+
+        length
+    }
 }
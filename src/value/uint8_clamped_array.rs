@@ -130,4 +130,27 @@ impl<'scope> Uint8ClampedArray<'scope> {
         // SAFETY: The API only allows to create array buffer with initialized data.
         unsafe { std::slice::from_raw_parts_mut(data as *mut u8, self.0.byte_length()) }
     }
+
+    /// Copies the given data into the [`Uint8ClampedArray`] with a single memcpy.
+    ///
+    /// Copies at most as many elements as the [`Uint8ClampedArray`] can hold. Returns the number of
+    /// **bytes** actually written.
+    #[inline(always)]
+    pub fn copy_from(&mut self, scope: &mut ValueScope<'scope>, src: &[u8]) -> usize {
+        let length = usize::min(self.0.byte_length(), src.len());
+
+        let data_ptr = self
+            .0
+            .buffer(scope.unseal())
+            .expect("Uint8ClampedArray has no backing array buffer")
+            .data()
+            .wrapping_add(self.0.byte_offset()) as *mut u8;
+
+        // SAFETY: The API only allows to create array buffer with initialized data and we
+        //         never write more bytes than the view contains.
+        let dest = unsafe { std::slice::from_raw_parts_mut(data_ptr, length) };
+        dest.copy_from_slice(&src[..length]);
+
+        length
+    }
 }
@@ -1,4 +1,4 @@
-use super::{Object, Seal, Unseal, Value};
+use super::{new_string, ArrayBuffer, NewStringType, Object, Seal, Unseal, Value, ValueScope};
 
 /// A WASM memory object.
 #[derive(Copy, Clone)]
@@ -44,5 +44,15 @@ impl<'scope> From<WasmMemoryObject<'scope>> for Object<'scope> {
 }
 
 impl<'scope> WasmMemoryObject<'scope> {
-    // TODO rusty_v8 doesn't expose the buffer accessor for the WasmMemoryObject.
+    /// Returns the array buffer, that is backed by the memory.
+    ///
+    /// The array buffer is detached, when the memory grows.
+    pub fn buffer(&self, scope: &mut ValueScope<'scope>) -> Option<ArrayBuffer<'scope>> {
+        // rusty_v8 doesn't expose the buffer accessor, so we read the `buffer` property.
+        let scope = scope.unseal();
+        let key = new_string(scope, "buffer", NewStringType::Internalized);
+        let buffer = self.0.get(scope, key.into())?;
+        let buffer = v8::Local::<v8::ArrayBuffer>::try_from(buffer).ok()?;
+        Some(buffer.seal())
+    }
 }
@@ -1,4 +1,4 @@
-use super::{Object, Seal, Unseal, Value};
+use super::{Object, Seal, Unseal, Value, ValueScope};
 
 /// A WASM module object.
 #[derive(Copy, Clone)]
@@ -44,5 +44,12 @@ impl<'scope> From<WasmModuleObject<'scope>> for Object<'scope> {
 }
 
 impl<'scope> WasmModuleObject<'scope> {
-    // TODO it's not clear yet how WASM integration should look like. So we don't expose an API for now.
+    /// Compiles the given WebAssembly binary into a module. Returns `None` if the binary is
+    /// invalid. The compile error is thrown as an exception.
+    pub fn compile(
+        scope: &mut ValueScope<'scope>,
+        wire_bytes: &[u8],
+    ) -> Option<WasmModuleObject<'scope>> {
+        v8::WasmModuleObject::compile(scope.unseal(), wire_bytes).map(|module| module.seal())
+    }
 }
@@ -0,0 +1,116 @@
+use std::rc::Rc;
+
+use super::{new_string, NewStringType, Object, Unseal, Value, ValueScope};
+
+/// A `WeakRef` object, that holds a weak reference to its target object.
+///
+/// The target stays alive at least until the end of the current execution, after it was
+/// created or dereferenced.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct WeakRef<'scope>(v8::Local<'scope, v8::Object>);
+
+impl<'scope> From<WeakRef<'scope>> for Value<'scope> {
+    #[inline(always)]
+    fn from(value: WeakRef<'scope>) -> Self {
+        Value(value.0.into())
+    }
+}
+
+impl<'scope> From<WeakRef<'scope>> for Object<'scope> {
+    #[inline(always)]
+    fn from(value: WeakRef<'scope>) -> Self {
+        Object(value.0)
+    }
+}
+
+impl<'scope> WeakRef<'scope> {
+    /// Creates a new `WeakRef` with the given target. Returns `None` if the `WeakRef`
+    /// constructor is not available.
+    pub fn new(scope: &mut ValueScope<'scope>, target: Object<'scope>) -> Option<WeakRef<'scope>> {
+        let scope = scope.unseal();
+        let intrinsics = WeakRefIntrinsics::get(scope)?;
+        let constructor = v8::Local::new(scope, &intrinsics.constructor);
+        let weak_ref = constructor.new_instance(scope, &[target.unseal().into()])?;
+        Some(Self(weak_ref))
+    }
+
+    /// Returns the value as a `WeakRef`, if it is an object that was created by the `WeakRef`
+    /// constructor.
+    ///
+    /// The check dereferences the `WeakRef`, so a target that is still alive stays alive until
+    /// the end of the current execution.
+    pub fn from_value(
+        scope: &mut ValueScope<'scope>,
+        value: Value<'scope>,
+    ) -> Option<WeakRef<'scope>> {
+        let object = v8::Local::<v8::Object>::try_from(value.0).ok()?;
+
+        // The `deref` method throws for every object, that is not a `WeakRef`. Other checks,
+        // like the prototype or the name of the constructor, can be faked by scripts.
+        let scope = scope.unseal();
+        let intrinsics = WeakRefIntrinsics::get(scope)?;
+        let try_catch_scope = &mut v8::TryCatch::new(scope);
+        let deref = v8::Local::new(try_catch_scope, &intrinsics.deref);
+        deref.call(try_catch_scope, object.into(), &[])?;
+
+        Some(Self(object))
+    }
+
+    /// Returns the target of the `WeakRef`, or `None` if the target was garbage collected.
+    pub fn deref(&self, scope: &mut ValueScope<'scope>) -> Option<Value<'scope>> {
+        let scope = scope.unseal();
+        let intrinsics = WeakRefIntrinsics::get(scope)?;
+        let deref = v8::Local::new(scope, &intrinsics.deref);
+        let target = deref.call(scope, self.0.into(), &[])?;
+
+        if target.is_undefined() {
+            None
+        } else {
+            Some(Value(target))
+        }
+    }
+}
+
+/// The `WeakRef` constructor and its `deref` method, as they were when the context was created.
+/// Scripts can replace both, so they must not be looked up while a script is running.
+pub(crate) struct WeakRefIntrinsics {
+    constructor: v8::Global<v8::Function>,
+    deref: v8::Global<v8::Function>,
+}
+
+impl WeakRefIntrinsics {
+    /// Captures the intrinsics of the current context. Must be called after the context was
+    /// created and before any script runs inside of it.
+    pub(crate) fn capture(scope: &mut v8::HandleScope) {
+        let context = scope.get_current_context();
+        if let Some(intrinsics) = Self::lookup(scope, context) {
+            context.set_slot(scope, Rc::new(intrinsics));
+        }
+    }
+
+    fn lookup(scope: &mut v8::HandleScope, context: v8::Local<v8::Context>) -> Option<Self> {
+        let key = new_string(scope, "WeakRef", NewStringType::Internalized);
+        let constructor = context.global(scope).get(scope, key.into())?;
+        let constructor = v8::Local::<v8::Function>::try_from(constructor).ok()?;
+
+        let key = new_string(scope, "prototype", NewStringType::Internalized);
+        let prototype = constructor.get(scope, key.into())?;
+        let prototype = v8::Local::<v8::Object>::try_from(prototype).ok()?;
+
+        let key = new_string(scope, "deref", NewStringType::Internalized);
+        let deref = prototype.get(scope, key.into())?;
+        let deref = v8::Local::<v8::Function>::try_from(deref).ok()?;
+
+        Some(Self {
+            constructor: v8::Global::new(scope, constructor),
+            deref: v8::Global::new(scope, deref),
+        })
+    }
+
+    /// Returns the intrinsics of the current context.
+    fn get(scope: &mut v8::HandleScope) -> Option<Rc<Self>> {
+        let context = scope.get_current_context();
+        context.get_slot::<Rc<Self>>(scope).cloned()
+    }
+}
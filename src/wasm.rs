@@ -0,0 +1,64 @@
+//! Implements the instantiation of WebAssembly modules and the calling of their exports.
+
+/// An instantiated WebAssembly module. Created by [`crate::Runtime::instantiate_wasm()`].
+///
+/// The exports of the instance are called with [`crate::Runtime::call_wasm()`] and exported
+/// memories are accessed with [`crate::Runtime::wasm_memory()`]. An instance can only be used
+/// with the runtime that created it.
+pub struct WasmInstance {
+    /// The id of the runtime, that created the instance.
+    pub(crate) runtime: u64,
+    pub(crate) exports: v8::Global<v8::Object>,
+}
+
+/// An argument of a WebAssembly function.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WasmValue {
+    /// A 32 bit integer.
+    I32(i32),
+    /// A 64 bit integer. Is passed as a `BigInt`.
+    I64(i64),
+    /// A 32 bit float.
+    F32(f32),
+    /// A 64 bit float.
+    F64(f64),
+}
+
+impl WasmValue {
+    /// Converts the argument into the value, that the engine passes to the WebAssembly function.
+    pub(crate) fn to_v8<'scope>(
+        self,
+        scope: &mut v8::HandleScope<'scope>,
+    ) -> v8::Local<'scope, v8::Value> {
+        match self {
+            WasmValue::I32(value) => v8::Integer::new(scope, value).into(),
+            WasmValue::I64(value) => v8::BigInt::new_from_i64(scope, value).into(),
+            WasmValue::F32(value) => v8::Number::new(scope, value as f64).into(),
+            WasmValue::F64(value) => v8::Number::new(scope, value).into(),
+        }
+    }
+}
+
+impl From<i32> for WasmValue {
+    fn from(value: i32) -> Self {
+        WasmValue::I32(value)
+    }
+}
+
+impl From<i64> for WasmValue {
+    fn from(value: i64) -> Self {
+        WasmValue::I64(value)
+    }
+}
+
+impl From<f32> for WasmValue {
+    fn from(value: f32) -> Self {
+        WasmValue::F32(value)
+    }
+}
+
+impl From<f64> for WasmValue {
+    fn from(value: f64) -> Self {
+        WasmValue::F64(value)
+    }
+}